@@ -7,12 +7,18 @@
 
 extern kmain
 global start
+global stack_bottom
+global stack_top
 
 section .text
 bits 32
 
 start:
     
+    ; Keep the multiboot info pointer for kmain. ebx is clobbered by the checks,
+    ; but nothing here touches edi
+    mov edi, ebx
+    
     ; Checks
     call check_multiboot ; Check if booted correctly
     call check_cpuid  ; Check if cpuid supported
@@ -119,10 +125,21 @@ setup_paging:
     or eax, 0b11
     mov [p4_table + 0], eax ; set 1st entry of p4 table to 1st entry of p3 table
     
-    ; Point entry #1 of page 3 to entry #1 of page 2
-    mov eax, p2_table + 0 ; set eax to 1st entry of p3 table
-    or eax, 0b11
-    mov [p3_table + 0], eax ; set 1st entry of p4 table to 1st entry of p3 table
+    ; Point the first 4 entries of page 3 to the 4 page 2 tables, covering 4gib, which
+    ; includes framebuffers and other memory mapped devices below 4gib
+    mov ecx, 0
+    .map_p3_table_loop:
+        
+        mov eax, ecx
+        shl eax, 12 ; multiply by 4096, the size of a p2 table
+        add eax, p2_table
+        or eax, 0b11
+        
+        mov [p3_table + ecx * 8], eax
+        
+        inc ecx
+        cmp ecx, 4
+        jne .map_p3_table_loop
     
     mov ecx, 0
     .map_p2_table_loop:
@@ -134,7 +151,7 @@ setup_paging:
         mov [p2_table + ecx * 8], eax
         
         inc ecx
-        cmp ecx, 512
+        cmp ecx, 512 * 4
         jne .map_p2_table_loop
     
     ; Set page table address to cr3
@@ -247,11 +264,12 @@ p4_table:
 p3_table:
     resb 4096
 p2_table:
-    resb 4096
+    resb 4096 * 4
 
 ; Stack grows the other way
+; 64kib, since panics format through the whole console stack, and tests keep text buffers on it
 stack_bottom:
-    resb 4096
+    resb 4096 * 16
 stack_top:
 
 section .rodata
@@ -282,6 +300,12 @@ long_mode_start:
     ; Setup stack
     mov esp, stack_top
     
+    ; Zero extend the multiboot info pointer into rdi, the first argument of kmain
+    mov edi, edi
+    
+    ; Null frame pointer, which ends backtraces
+    xor ebp, ebp
+    
     call kmain
     
     hlt
@@ -12,7 +12,18 @@ header_start:
     ; header checksum (0x100000000 - (magic number + mode + length))
     dd 0x100000000 - (0xe85250d6 + 0 + (header_end - header_start))
     
+    ; framebuffer tag, which asks for a linear framebuffer. It's optional, so bootloaders
+    ; which can't give one boot in text mode, as does GRUB with gfxpayload=text
+    align 8, db 0
+    dw 5 ; type
+    dw 1 ; flags (optional)
+    dd 20 ; size
+    dd 640 ; width
+    dd 480 ; height
+    dd 32 ; depth
+    
     ; end tag
+    align 8, db 0
     dw 0 ; type
     dw 0 ; flags
     dd 8 ; size
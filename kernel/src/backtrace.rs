@@ -0,0 +1,138 @@
+//! Stack unwinding by following the chain of saved frame pointers
+
+use core::fmt;
+use symbols;
+
+/// Maximum amount of frames walked, in case the chain is corrupt
+const MAX_FRAMES: usize = 64;
+
+extern {
+    static stack_bottom: u8;
+    static stack_top: u8;
+}
+
+/// Reads the frame pointer of the calling function
+#[inline(always)]
+pub fn frame_pointer() -> usize {
+    let rbp: usize;
+    unsafe { asm!("mov %rbp, $0" : "=r"(rbp) ::: "volatile") }
+    rbp
+}
+
+/// Gets the frame pointer saved by the function with the given frame, or 0 if it is invalid
+pub fn saved_frame_pointer(frame_pointer: usize) -> usize {
+    if is_valid_frame(frame_pointer) {
+        unsafe { *(frame_pointer as *const usize) }
+    } else {
+        0
+    }
+}
+
+/// Checks that a frame pointer points to a whole frame within the kernel stack
+fn is_valid_frame(frame_pointer: usize) -> bool {
+    let (bottom, top) = unsafe {
+        (&stack_bottom as *const u8 as usize, &stack_top as *const u8 as usize)
+    };
+
+    frame_pointer != 0 && frame_pointer % 8 == 0 && frame_pointer >= bottom && frame_pointer + 16 <= top
+}
+
+/// Iterator over the return addresses of each frame, from innermost to outermost
+pub struct Frames {
+    frame_pointer: usize,
+    depth: usize,
+}
+
+impl Frames {
+    pub fn new(frame_pointer: usize) -> Self {
+        Frames {
+            frame_pointer: frame_pointer,
+            depth: 0,
+        }
+    }
+}
+
+impl Iterator for Frames {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        let frame_pointer = self.frame_pointer;
+        if self.depth >= MAX_FRAMES || !is_valid_frame(frame_pointer) {
+            return None;
+        }
+
+        // The saved frame pointer is followed by the return address
+        let (saved, return_address) = unsafe {
+            (*(frame_pointer as *const usize), *((frame_pointer + 8) as *const usize))
+        };
+
+        // Callers' frames are always further up the stack, so anything else is corrupt or a loop
+        self.frame_pointer = if saved > frame_pointer { saved } else { 0 };
+        self.depth += 1;
+
+        if return_address == 0 {
+            None
+        } else {
+            Some(return_address)
+        }
+    }
+}
+
+/// A backtrace which is walked and symbolized when displayed
+pub struct Backtrace {
+    /// Address of the faulting instruction, if interrupted
+    instruction_pointer: Option<usize>,
+    frame_pointer: usize,
+}
+
+impl Backtrace {
+    /// Captures a backtrace starting at the calling function's caller
+    #[cfg_attr(test, allow(dead_code))] // Only the panic handler uses it, which host tests leave out
+    #[inline(always)]
+    pub fn capture() -> Self {
+        Backtrace::from_frame_pointer(frame_pointer())
+    }
+
+    pub fn from_frame_pointer(frame_pointer: usize) -> Self {
+        Backtrace {
+            instruction_pointer: None,
+            frame_pointer: frame_pointer,
+        }
+    }
+
+    /// Creates a backtrace for interrupted code, from the interrupted instruction and frame pointer
+    pub fn from_interrupt(instruction_pointer: usize, frame_pointer: usize) -> Self {
+        Backtrace {
+            instruction_pointer: Some(instruction_pointer),
+            frame_pointer: frame_pointer,
+        }
+    }
+}
+
+impl fmt::Display for Backtrace {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Backtrace:")?;
+
+        if let Some(address) = self.instruction_pointer {
+            write_frame(f, 0, address, address)?;
+        }
+
+        let first = if self.instruction_pointer.is_some() { 1 } else { 0 };
+        for (index, address) in Frames::new(self.frame_pointer).enumerate() {
+            // Return addresses point after the call, which may be the start of the next function
+            write_frame(f, first + index, address, address - 1)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn write_frame(f: &mut fmt::Formatter, index: usize, address: usize, lookup: usize) -> fmt::Result {
+    match symbols::resolve(lookup) {
+        Some(symbol) => writeln!(
+            f, "{:3}: {:016x} {}+{:#x}",
+            index, address, symbol.demangled(), address - symbol.address
+        ),
+        None => writeln!(f, "{:3}: {:016x} <unknown>", index, address),
+    }
+}
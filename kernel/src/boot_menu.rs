@@ -0,0 +1,116 @@
+//! A dialog of boot options, shown with `boot.menu` on the command line, picking the text mode and
+//! the color theme the kernel goes on booting with
+
+use core::fmt::Write;
+use drivers::keyboard::KeyEvent;
+use drivers::vga::{self, TextMode};
+use tui::{self, Focus, Menu, ProgressBar, Region, Response, Widget, Window};
+use memory;
+
+/// Size of the inside of the dialog's window
+const WIDTH: usize = 40;
+const HEIGHT: usize = 9;
+
+/// Column the theme menu starts at, right of the text mode menu
+const THEME_COLUMN: usize = WIDTH / 2;
+/// Rows of each menu
+const MENU_HEIGHT: usize = 3;
+
+const MODES: [TextMode; 3] = [TextMode::Text80x25, TextMode::Text80x50, TextMode::Text90x60];
+const MODE_NAMES: [&'static str; 3] = ["80x25", "80x50", "90x60"];
+const THEME_NAMES: [&'static str; 2] = [vga::DEFAULT_THEME.name, vga::SOLARIZED_THEME.name];
+
+/// Indices of the menus, as the focus knows them, after the memory bar
+const MODE_MENU: usize = 1;
+const THEME_MENU: usize = 2;
+
+/// The dialog's widgets: how much memory is in use, and a menu each of text modes and themes
+struct BootOptions<'a> {
+    memory: ProgressBar,
+    modes: Menu<'a>,
+    themes: Menu<'a>,
+    focus: Focus,
+}
+
+impl<'a> BootOptions<'a> {
+    /// Creates the dialog with the text mode menu focused, and the current text mode selected
+    fn new() -> Self {
+        let available = memory::available() / 1024;
+        let mut options = BootOptions {
+            memory: ProgressBar::new(available),
+            modes: Menu::new(&MODE_NAMES),
+            themes: Menu::new(&THEME_NAMES),
+            focus: Focus::new(),
+        };
+
+        options.memory.set(available.saturating_sub(memory::free() / 1024));
+        if let Some(index) = MODES.iter().position(|&mode| mode == vga::mode()) {
+            options.modes.select(index);
+        }
+
+        {
+            let widgets: [&mut Widget; 3] = [&mut options.memory, &mut options.modes, &mut options.themes];
+            options.focus.set(&widgets, MODE_MENU);
+        }
+        options
+    }
+
+    /// Gets the text mode selected
+    fn mode(&self) -> TextMode {
+        MODES[self.modes.selected()]
+    }
+
+    /// Gets the theme selected
+    fn theme(&self) -> &'static vga::Theme {
+        let name = self.themes.items()[self.themes.selected()];
+        vga::find_theme(name).expect("Theme names should be of themes")
+    }
+}
+
+impl<'a> Widget for BootOptions<'a> {
+    /// Draws how much memory is in use at the top, then the menus side by side, and which keys
+    /// do what at the bottom
+    fn draw(&self, region: &mut Region, focused: bool) {
+        region.clear();
+        region.set_cursor(0, 1);
+        let _ = write!(region, "Memory in use: {} of {} KiB", self.memory.value(), self.memory.total());
+        self.memory.draw(&mut region.region(1, 1, WIDTH - 2, 1), false);
+
+        let color = region.color();
+        region.text(3, 1, "Text mode", color);
+        region.text(3, THEME_COLUMN + 1, "Theme", color);
+
+        let focused = |index| focused && self.focus.is_focused(index);
+        self.modes.draw(&mut region.region(4, 0, THEME_COLUMN - 1, MENU_HEIGHT), focused(MODE_MENU));
+        self.themes.draw(&mut region.region(4, THEME_COLUMN, WIDTH - THEME_COLUMN, MENU_HEIGHT),
+                         focused(THEME_MENU));
+
+        region.text_centered(HEIGHT - 1, "Tab: switch  Enter: boot  Esc: skip", color);
+    }
+
+    /// Moves the focus between the menus, and boots with what they have selected once either
+    /// is chosen
+    fn handle_key(&mut self, event: &KeyEvent) -> Response {
+        let mut widgets: [&mut Widget; 3] = [&mut self.memory, &mut self.modes, &mut self.themes];
+        self.focus.handle_key(&mut widgets, event)
+    }
+}
+
+/// Shows the dialog if `boot.menu` is on the command line, and switches to the text mode and
+/// theme chosen. Nothing changes if it's skipped with Escape.
+pub fn show(command_line: &str) {
+    if !command_line.split_whitespace().any(|arg| arg == "boot.menu") {
+        return;
+    }
+
+    let mut options = BootOptions::new();
+    if tui::run_modal(&Window::new("Boot options"), &mut options, WIDTH, HEIGHT) == Response::Cancelled {
+        return;
+    }
+
+    vga::apply_theme(options.theme());
+    if let Err(error) = vga::set_mode(options.mode()) {
+        warn!("boot", "failed to switch to the chosen text mode: {:?}", error);
+        tui::message_box("Boot options", "The text mode couldn't be switched to.", &["Boot"]);
+    }
+}
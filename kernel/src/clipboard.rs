@@ -0,0 +1,88 @@
+//! The kernel's clipboard, holding text selected on the screen until it's pasted
+
+use core::{fmt, str};
+use spin::Mutex;
+
+/// Enough bytes for every cell of the largest screen, each taking up to 3 bytes of UTF-8
+pub const CLIPBOARD_CAPACITY: usize = 16 * 1024;
+
+pub static CLIPBOARD: Mutex<Clipboard> = Mutex::new(Clipboard::new());
+
+/// Holds UTF-8 text. Writes past the capacity are cut off at the last char which fits.
+pub struct Clipboard {
+    bytes: [u8; CLIPBOARD_CAPACITY],
+    len: usize,
+}
+
+impl Clipboard {
+    pub const fn new() -> Self {
+        Clipboard {
+            bytes: [0; CLIPBOARD_CAPACITY],
+            len: 0,
+        }
+    }
+
+    /// Gets the held text
+    pub fn text(&self) -> &str {
+        str::from_utf8(&self.bytes[..self.len]).unwrap_or("")
+    }
+
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+}
+
+impl fmt::Write for Clipboard {
+    fn write_str(&mut self, str: &str) -> fmt::Result {
+        for character in str.chars() {
+            let len = character.len_utf8();
+            if self.len + len > CLIPBOARD_CAPACITY {
+                break;
+            }
+
+            character.encode_utf8(&mut self.bytes[self.len..]);
+            self.len += len;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "kernel-test")]
+pub mod tests {
+    use core::fmt::Write;
+    use testing::{TestCase, TestResult};
+    use super::*;
+
+    pub const TESTS: &'static [TestCase] = &[
+        test_case!(holds_written_text),
+        test_case!(cuts_off_at_capacity),
+    ];
+
+    fn holds_written_text() -> TestResult {
+        let mut clipboard = Clipboard::new();
+        test_assert_eq!(clipboard.text(), "");
+
+        test_assert!(write!(clipboard, "caf\u{e9} {}\n", 42).is_ok());
+        test_assert_eq!(clipboard.text(), "caf\u{e9} 42\n");
+
+        clipboard.clear();
+        test_assert_eq!(clipboard.text(), "");
+        Ok(())
+    }
+
+    fn cuts_off_at_capacity() -> TestResult {
+        let mut clipboard = Clipboard::new();
+        for _ in 0..CLIPBOARD_CAPACITY - 1 {
+            test_assert!(clipboard.write_char('a').is_ok());
+        }
+
+        // A char which doesn't fit whole is left out
+        test_assert!(clipboard.write_str("\u{2502}b").is_ok());
+        test_assert_eq!(clipboard.text().len(), CLIPBOARD_CAPACITY - 1);
+        test_assert!(clipboard.write_str("b").is_ok());
+        test_assert_eq!(clipboard.text().len(), CLIPBOARD_CAPACITY);
+        test_assert!(clipboard.text().ends_with("ab"));
+        Ok(())
+    }
+}
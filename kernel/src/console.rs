@@ -0,0 +1,371 @@
+//! Kernel console output, fanned out to every registered console sink
+
+use core::fmt;
+use core::str::FromStr;
+use core::sync::atomic::{AtomicBool, Ordering};
+use spin::{Mutex, MutexGuard};
+use drivers::{vga, framebuffer, bga, serial, debugcon, pit};
+use multiboot;
+use log;
+use clipboard::CLIPBOARD;
+use input::INPUT;
+use drivers::vga::TextBuffer;
+use drivers::keyboard::{Key, KeyEvent};
+
+/// Maximum amount of consoles that can be registered at once
+const MAX_CONSOLES: usize = 8;
+
+pub static CONSOLES: Mutex<ConsoleRegistry> = Mutex::new(ConsoleRegistry::new());
+
+/// Whether the screen is drawn on a framebuffer rather than shown in VGA text mode
+static ON_FRAMEBUFFER: AtomicBool = AtomicBool::new(false);
+
+macro_rules! print {
+    ($($arg:tt)*) => ({
+        $crate::console::print(format_args!($($arg)*));
+    });
+}
+
+macro_rules! println {
+    ($fmt:expr) => (print!(concat!($fmt, "\n")));
+    ($fmt:expr, $($arg:tt)*) => (print!(concat!($fmt, "\n"), $($arg)*));
+}
+
+/// Prints to every enabled console, regardless of level
+pub fn print(args: fmt::Arguments) {
+    CONSOLES.lock().print(None, args);
+}
+
+/// Takes over the consoles to report a fatal error, even if they are held by code that will never
+/// run again. Shows the log terminal, and falls back to it if no consoles are registered yet.
+pub unsafe fn take_over() -> MutexGuard<'static, ConsoleRegistry> {
+    CONSOLES.force_unlock();
+    let mut consoles = CONSOLES.lock();
+    consoles.force_unlock_all();
+    vga::force_switch_to(vga::LOG_TERMINAL);
+
+    if consoles.is_empty() {
+        let _ = consoles.register("vga", &vga::TERMINALS[vga::LOG_TERMINAL]);
+    }
+
+    consoles
+}
+
+/// Registers the built in consoles, and configures them, the text mode, the font, the colors,
+/// the scrollback and how terminals scroll from the kernel command line. The screen is drawn on the
+/// bootloader's framebuffer if it gave one, or else on the Bochs adapter's if `video=` sets a mode
+/// on it, or else shown in VGA text mode.
+pub fn init(command_line: &str) {
+    serial::COM1.lock().initialize();
+    for terminal in vga::TERMINALS.iter() {
+        terminal.lock().set_bell(Some(pit::beep));
+    }
+    framebuffer::FRAMEBUFFER_CONSOLE.lock().set_bell(Some(pit::beep));
+
+    let boot_framebuffer = multiboot::info().and_then(|info| info.framebuffer());
+    let adapter_result = match boot_framebuffer {
+        Some(_) => None,
+        None => bga::configure(command_line),
+    };
+    let framebuffer_info = boot_framebuffer.or(adapter_result.and_then(|result| result.ok()));
+    let framebuffer_result = framebuffer_info.map(framebuffer::init);
+    let on_framebuffer = framebuffer_result == Some(Ok(()));
+    if !on_framebuffer {
+        // Back to text mode, if the adapter's mode was set
+        *bga::ADAPTER.lock() = None;
+    }
+
+    let mut consoles = CONSOLES.lock();
+    if on_framebuffer {
+        consoles.register("fb", &framebuffer::FRAMEBUFFER_CONSOLE).expect("Console registry should have space");
+    } else {
+        consoles.register("vga", &vga::TERMINALS[vga::LOG_TERMINAL]).expect("Console registry should have space");
+    }
+    consoles.register("serial", &serial::COM1).expect("Console registry should have space");
+    consoles.register("debugcon", &debugcon::DEBUGCON).expect("Console registry should have space");
+    consoles.configure(command_line);
+    drop(consoles);
+
+    if let Some(Err(error)) = framebuffer_result {
+        warn!("fb", "can't draw on the framebuffer, falling back to text mode: {:?}", error);
+    }
+    if let Some(Err(error)) = adapter_result {
+        warn!("bga", "can't set the video mode, staying in text mode: {:?}", error);
+    }
+    if let Some(ref bga) = *bga::ADAPTER.lock() {
+        info!("bga", "set {}x{} with {} bits per pixel and {} pages", bga.width(), bga.height(),
+              bga.bits_per_pixel(), bga.pages());
+    }
+
+    // The VGA registers only drive the screen in text mode
+    if on_framebuffer {
+        ON_FRAMEBUFFER.store(true, Ordering::SeqCst);
+        vga::disable_text();
+    } else {
+        vga::configure(command_line);
+        vga::configure_font(command_line);
+        vga::configure_palette(command_line);
+        vga::configure_scrollback(command_line);
+        vga::configure_scrolling(command_line);
+    }
+}
+
+/// Checks if the screen is drawn on a framebuffer, where the virtual terminals aren't shown
+pub fn on_framebuffer() -> bool {
+    ON_FRAMEBUFFER.load(Ordering::SeqCst)
+}
+
+/// Handles a key for the consoles. Alt+F1 to Alt+F6 switch virtual terminal, Shift+PageUp and
+/// Shift+PageDown scroll through the active terminal's history, Ctrl+Shift+V pastes the
+/// clipboard, Ctrl+Alt+D prints the kernel log again and Print Screen dumps the screen to serial.
+/// Any other key returns the active terminal to the live screen, and its char is queued as that
+/// terminal's input. On a framebuffer only the log and clipboard keys work, as the terminals
+/// aren't shown.
+pub fn handle_key(event: KeyEvent) {
+    if !event.pressed || event.key.is_modifier() {
+        return;
+    }
+
+    let text = !on_framebuffer();
+    if event.key == Key::PrintScreen {
+        if text {
+            vga::dump_screen();
+        }
+        return;
+    }
+
+    if event.modifiers.ctrl() && event.modifiers.alt() && event.key == Key::D {
+        log::dmesg();
+        return;
+    }
+
+    if event.modifiers.alt() {
+        if let Some(number) = event.key.function_number() {
+            if text {
+                vga::switch_to(number as usize - 1);
+            }
+            return;
+        }
+    }
+
+    let index = vga::active();
+    let mut terminal = vga::TERMINALS[index].lock();
+    let page = terminal.buffer().height() / 2;
+
+    match event.key {
+        Key::PageUp if text && event.modifiers.shift() => terminal.buffer_mut().view_up(page),
+        Key::PageDown if text && event.modifiers.shift() => terminal.buffer_mut().view_down(page),
+        Key::V if event.modifiers.ctrl() && event.modifiers.shift() => paste(index, &mut terminal),
+        _ => {
+            terminal.buffer_mut().return_to_live();
+
+            if !event.modifiers.ctrl() && !event.modifiers.alt() {
+                if let Some(character) = event.char() {
+                    INPUT[index].lock().push(character);
+                }
+            }
+        }
+    }
+}
+
+/// Returns the terminal with the given index to the live screen and queues the clipboard's text as
+/// its input, as if it was typed on the keyboard
+pub fn paste(index: usize, terminal: &mut vga::Terminal) {
+    terminal.buffer_mut().return_to_live();
+
+    let mut input = INPUT[index].lock();
+    for character in CLIPBOARD.lock().text().chars() {
+        input.push(character);
+    }
+}
+
+/// Something which kernel output can be written to
+pub trait Console: Send {
+    /// Writes a string to this console
+    fn write(&mut self, str: &str);
+
+    /// Writes a string logged at the given level. Consoles may override this to highlight the level.
+    fn write_leveled(&mut self, _level: Level, str: &str) {
+        self.write(str);
+    }
+
+    /// Shows what was written since the last flush, for consoles which batch their output. Called
+    /// after each print.
+    fn flush(&mut self) {}
+}
+
+/// Represents the severity of console output, from most to least severe
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[repr(u8)]
+pub enum Level {
+    Error = 0,
+    Warn = 1,
+    Info = 2,
+    Debug = 3,
+    Trace = 4,
+}
+
+impl FromStr for Level {
+    type Err = ();
+
+    fn from_str(str: &str) -> Result<Self, ()> {
+        match str {
+            "error" => Ok(Level::Error),
+            "warn" => Ok(Level::Warn),
+            "info" => Ok(Level::Info),
+            "debug" => Ok(Level::Debug),
+            "trace" => Ok(Level::Trace),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ConsoleError {
+    RegistryFull,
+    UnknownConsole,
+}
+
+/// A registered console, along with its filter
+#[derive(Copy, Clone)]
+struct Sink {
+    name: &'static str,
+    console: &'static Mutex<Console>,
+    enabled: bool,
+    level: Level,
+}
+
+/// Holds all registered consoles, and writes output to those which are enabled
+pub struct ConsoleRegistry {
+    sinks: [Option<Sink>; MAX_CONSOLES],
+}
+
+impl ConsoleRegistry {
+    const fn new() -> Self {
+        ConsoleRegistry {
+            sinks: [None; MAX_CONSOLES],
+        }
+    }
+
+    /// Registers a console under the given name, enabled for all levels
+    pub fn register(&mut self, name: &'static str, console: &'static Mutex<Console>) -> Result<(), ConsoleError> {
+        let slot = self.sinks.iter_mut()
+            .find(|sink| sink.is_none())
+            .ok_or(ConsoleError::RegistryFull)?;
+
+        *slot = Some(Sink {
+            name: name,
+            console: console,
+            enabled: true,
+            level: Level::Trace,
+        });
+
+        Ok(())
+    }
+
+    /// Enables or disables the console with the given name
+    pub fn set_enabled(&mut self, name: &str, enabled: bool) -> Result<(), ConsoleError> {
+        self.sink_mut(name)?.enabled = enabled;
+        Ok(())
+    }
+
+    /// Sets the least severe level that the console with the given name will output
+    pub fn set_level(&mut self, name: &str, level: Level) -> Result<(), ConsoleError> {
+        self.sink_mut(name)?.level = level;
+        Ok(())
+    }
+
+    fn sink_mut(&mut self, name: &str) -> Result<&mut Sink, ConsoleError> {
+        self.sinks.iter_mut()
+            .filter_map(|sink| sink.as_mut())
+            .find(|sink| sink.name == name)
+            .ok_or(ConsoleError::UnknownConsole)
+    }
+
+    /// Configures consoles from the kernel command line.
+    ///
+    /// `console=vga:info,serial` enables only the listed consoles, each optionally filtered to a
+    /// level. A level of `off` disables that console.
+    pub fn configure(&mut self, command_line: &str) {
+        for arg in command_line.split_whitespace() {
+            if !arg.starts_with("console=") {
+                continue;
+            }
+
+            // Only consoles that are listed are enabled
+            for sink in self.sinks.iter_mut().filter_map(|sink| sink.as_mut()) {
+                sink.enabled = false;
+            }
+
+            for spec in arg["console=".len()..].split(',') {
+                let mut parts = spec.splitn(2, ':');
+                let name = parts.next().unwrap_or("");
+
+                let result = match parts.next() {
+                    Some("off") => self.set_enabled(name, false),
+                    Some(level) => match level.parse() {
+                        Ok(level) => self.set_level(name, level)
+                            .and_then(|_| self.set_enabled(name, true)),
+                        Err(_) => {
+                            self.print(None, format_args!("console: unknown level `{}`\n", level));
+                            continue;
+                        }
+                    },
+                    None => self.set_enabled(name, true),
+                };
+
+                if result.is_err() {
+                    self.print(None, format_args!("console: unknown console `{}`\n", name));
+                }
+            }
+        }
+    }
+
+    /// Checks if no consoles are registered
+    pub fn is_empty(&self) -> bool {
+        self.sinks.iter().all(|sink| sink.is_none())
+    }
+
+    /// Forcibly unlocks every registered console, for when their holder can never release them
+    pub unsafe fn force_unlock_all(&self) {
+        for sink in self.sinks.iter().filter_map(|sink| sink.as_ref()) {
+            sink.console.force_unlock();
+        }
+    }
+
+    /// Prints to every registered console, ignoring whether it is enabled and its level filter
+    pub fn print_all(&self, level: Level, args: fmt::Arguments) {
+        for sink in self.sinks.iter().filter_map(|sink| sink.as_ref()) {
+            let mut console = sink.console.lock();
+            let _ = fmt::write(&mut ConsoleWriter(&mut *console, Some(level)), args);
+            console.flush();
+        }
+    }
+
+    /// Prints to every enabled console whose level filter allows the given level. Output without a
+    /// level is always printed.
+    pub fn print(&self, level: Option<Level>, args: fmt::Arguments) {
+        for sink in self.sinks.iter().filter_map(|sink| sink.as_ref()) {
+            if !sink.enabled || level.map_or(false, |level| level > sink.level) {
+                continue;
+            }
+
+            let mut console = sink.console.lock();
+            let _ = fmt::write(&mut ConsoleWriter(&mut *console, level), args);
+            console.flush();
+        }
+    }
+}
+
+/// Adapts a console to `fmt::Write`, writing at the given level if any
+struct ConsoleWriter<'a>(&'a mut Console, Option<Level>);
+
+impl<'a> fmt::Write for ConsoleWriter<'a> {
+    fn write_str(&mut self, str: &str) -> fmt::Result {
+        match self.1 {
+            Some(level) => self.0.write_leveled(level, str),
+            None => self.0.write(str),
+        }
+        Ok(())
+    }
+}
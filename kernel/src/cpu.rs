@@ -0,0 +1,133 @@
+//! Low level CPU control and state
+
+use core::fmt;
+
+/// RFLAGS bit which is set while maskable interrupts are enabled
+const INTERRUPT_FLAG: u64 = 1 << 9;
+
+/// Halts the CPU until the next interrupt
+pub fn halt() {
+    unsafe { asm!("hlt" :::: "volatile") }
+}
+
+/// Disables interrupts and halts the CPU forever
+pub fn halt_forever() -> ! {
+    unsafe { disable_interrupts() }
+    loop {
+        // Only an NMI can wake us, so halt again
+        halt();
+    }
+}
+
+/// Disables maskable interrupts
+pub unsafe fn disable_interrupts() {
+    asm!("cli" :::: "volatile");
+}
+
+/// Enables maskable interrupts
+pub unsafe fn enable_interrupts() {
+    asm!("sti" :::: "volatile");
+}
+
+/// Checks if maskable interrupts are enabled
+pub fn interrupts_enabled() -> bool {
+    let flags: u64;
+    unsafe { asm!("pushfq; popq $0" : "=r"(flags) ::: "volatile") }
+    flags & INTERRUPT_FLAG != 0
+}
+
+/// Runs the given function with maskable interrupts disabled, so that no interrupt handler runs
+/// in the middle of it. They're enabled again afterwards if they were before.
+pub fn without_interrupts<F: FnOnce() -> T, T>(function: F) -> T {
+    let enabled = interrupts_enabled();
+    unsafe { disable_interrupts() }
+
+    let result = function();
+
+    if enabled {
+        unsafe { enable_interrupts() }
+    }
+    result
+}
+
+/// A snapshot of the CPU's registers
+#[repr(C)]
+pub struct Registers {
+    pub rax: u64,
+    pub rbx: u64,
+    pub rcx: u64,
+    pub rdx: u64,
+    pub rsi: u64,
+    pub rdi: u64,
+    pub rbp: u64,
+    pub rsp: u64,
+    pub r8: u64,
+    pub r9: u64,
+    pub r10: u64,
+    pub r11: u64,
+    pub r12: u64,
+    pub r13: u64,
+    pub r14: u64,
+    pub r15: u64,
+    pub rflags: u64,
+    pub cr0: u64,
+    pub cr2: u64,
+    pub cr3: u64,
+    pub cr4: u64,
+}
+
+impl Registers {
+    /// Captures the current registers. The general purpose registers hold whatever the caller
+    /// left in them, so only the stack, flags and control registers are reliable.
+    #[cfg_attr(test, allow(dead_code))] // Only the panic handler uses it, which host tests leave out
+    #[inline(always)]
+    pub fn capture() -> Self {
+        let mut registers = Registers {
+            rax: 0, rbx: 0, rcx: 0, rdx: 0, rsi: 0, rdi: 0, rbp: 0, rsp: 0,
+            r8: 0, r9: 0, r10: 0, r11: 0, r12: 0, r13: 0, r14: 0, r15: 0,
+            rflags: 0, cr0: 0, cr2: 0, cr3: 0, cr4: 0,
+        };
+
+        unsafe {
+            asm!("mov %rax, 0x00($0)
+                  mov %rbx, 0x08($0)
+                  mov %rcx, 0x10($0)
+                  mov %rdx, 0x18($0)
+                  mov %rsi, 0x20($0)
+                  mov %rdi, 0x28($0)
+                  mov %rbp, 0x30($0)
+                  mov %rsp, 0x38($0)
+                  mov %r8, 0x40($0)
+                  mov %r9, 0x48($0)
+                  mov %r10, 0x50($0)
+                  mov %r11, 0x58($0)
+                  mov %r12, 0x60($0)
+                  mov %r13, 0x68($0)
+                  mov %r14, 0x70($0)
+                  mov %r15, 0x78($0)
+                  pushfq
+                  popq 0x80($0)"
+                 :: "r"(&mut registers) : "memory" : "volatile");
+
+            asm!("mov %cr0, $0" : "=r"(registers.cr0) ::: "volatile");
+            asm!("mov %cr2, $0" : "=r"(registers.cr2) ::: "volatile");
+            asm!("mov %cr3, $0" : "=r"(registers.cr3) ::: "volatile");
+            asm!("mov %cr4, $0" : "=r"(registers.cr4) ::: "volatile");
+        }
+
+        registers
+    }
+}
+
+impl fmt::Display for Registers {
+    /// Formats three registers to a line, to fit an 80 column screen
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "rax={:016x} rbx={:016x} rcx={:016x}", self.rax, self.rbx, self.rcx)?;
+        writeln!(f, "rdx={:016x} rsi={:016x} rdi={:016x}", self.rdx, self.rsi, self.rdi)?;
+        writeln!(f, "rbp={:016x} rsp={:016x} rfl={:016x}", self.rbp, self.rsp, self.rflags)?;
+        writeln!(f, "r8 ={:016x} r9 ={:016x} r10={:016x}", self.r8, self.r9, self.r10)?;
+        writeln!(f, "r11={:016x} r12={:016x} r13={:016x}", self.r11, self.r12, self.r13)?;
+        writeln!(f, "r14={:016x} r15={:016x} cr0={:016x}", self.r14, self.r15, self.cr0)?;
+        write!(f, "cr2={:016x} cr3={:016x} cr4={:016x}", self.cr2, self.cr3, self.cr4)
+    }
+}
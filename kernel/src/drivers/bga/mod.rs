@@ -0,0 +1,301 @@
+//! The Bochs Graphics Adapter, which the standard VGA of QEMU and Bochs exposes for setting any
+//! resolution and depth at runtime. Its linear framebuffer is found through PCI, and a virtual
+//! height of several screens allows double buffering by moving the shown page. A mode is set at
+//! boot with `video=<width>x<height>[x<depth>]` on the command line.
+
+use spin::Mutex;
+use io::IOPort;
+use multiboot::FramebufferInfo;
+use drivers::pci;
+use drivers::framebuffer::Framebuffer;
+use drivers::vga::{self, SuspendedText, VgaWriteError};
+use gfx::{RGB565, RGB888, XRGB8888};
+
+#[cfg(feature = "kernel-test")]
+pub mod tests;
+
+static INDEX_PORT: IOPort<u16> = IOPort::new(0x1CE);
+static DATA_PORT: IOPort<u16> = IOPort::new(0x1CF);
+
+/// Oldest and newest adapter versions read back from the ID register
+const VERSION_MIN: u16 = 0xB0C0;
+const VERSION_MAX: u16 = 0xB0C5;
+/// First version with a linear framebuffer and 32 bits per pixel
+const VERSION_LFB: u16 = 0xB0C2;
+
+const ENABLED_BIT: u16 = 1 << 0;
+const LFB_ENABLED_BIT: u16 = 1 << 6;
+
+/// PCI IDs of the adapter, whose first BAR is the linear framebuffer
+const PCI_VENDOR_ID: u16 = 0x1234;
+const PCI_DEVICE_ID: u16 = 0x1111;
+
+/// Depth of a mode set with `video=` when it doesn't give one
+const DEFAULT_DEPTH: u8 = 32;
+/// Pages of a mode set with `video=`, so that the boot splash can be drawn on the page which
+/// isn't shown
+const VIDEO_PAGES: usize = 2;
+
+/// The mode set with `video=`, whose shown page the framebuffer console draws on
+pub static ADAPTER: Mutex<Option<Bga>> = Mutex::new(None);
+
+#[derive(Copy, Clone, Debug)]
+#[repr(u16)]
+enum Register {
+    Id = 0x0,
+    XResolution = 0x1,
+    YResolution = 0x2,
+    BitsPerPixel = 0x3,
+    Enable = 0x4,
+    VirtualWidth = 0x6,
+    VirtualHeight = 0x7,
+    YOffset = 0x9,
+}
+
+fn read(register: Register) -> u16 {
+    INDEX_PORT.write(register as u16);
+    DATA_PORT.read()
+}
+
+fn write(register: Register, value: u16) {
+    INDEX_PORT.write(register as u16);
+    DATA_PORT.write(value);
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum BgaError {
+    /// There's no adapter, or it's too old to have a linear framebuffer
+    NotPresent,
+    /// A mode is already set, or a VGA graphics mode has the screen
+    InUse,
+    /// The adapter wasn't found on PCI, so its framebuffer is unknown
+    NoFramebuffer,
+    /// Only 16, 24 and 32 bits per pixel are supported
+    UnsupportedDepth(u8),
+    /// The adapter didn't take the mode, or hasn't the memory for all its pages
+    ModeRejected,
+    /// The framebuffer is beyond the memory mapped at boot
+    Unmapped,
+    /// The mode given with `video=` isn't written as `<width>x<height>[x<depth>]`
+    InvalidMode,
+}
+
+/// Gets the adapter's version, or `None` if there's no adapter
+pub fn version() -> Option<u16> {
+    match read(Register::Id) {
+        id @ VERSION_MIN...VERSION_MAX => Some(id),
+        _ => None,
+    }
+}
+
+/// Checks if there's an adapter able to set modes with a linear framebuffer
+pub fn is_present() -> bool {
+    version().map_or(false, |version| version >= VERSION_LFB)
+}
+
+/// Gets the address of the linear framebuffer from the adapter's PCI BAR
+pub fn framebuffer_address() -> Option<usize> {
+    pci::find(PCI_VENDOR_ID, PCI_DEVICE_ID)
+        .and_then(|function| function.memory_bar(0))
+        .map(|address| address as usize)
+}
+
+/// Represents a mode set on the adapter, of one or more pages of a screen each. One page is
+/// shown while the others can be drawn to. The adapter is disabled and the text mode put back
+/// when dropped.
+pub struct Bga {
+    address: usize,
+    width: usize,
+    height: usize,
+    bits_per_pixel: u8,
+    pages: usize,
+    shown: usize,
+    text: SuspendedText,
+}
+
+/// Sets a mode with the given amount of pages, such as 2 for double buffering. The terminals keep
+/// being written to off screen until `disable`.
+pub fn set_mode(width: usize, height: usize, bits_per_pixel: u8, pages: usize) -> Result<Bga, BgaError> {
+    match bits_per_pixel {
+        16 | 24 | 32 => {}
+        depth => return Err(BgaError::UnsupportedDepth(depth)),
+    }
+    if pages == 0 {
+        return Err(BgaError::ModeRejected);
+    }
+    if !is_present() {
+        return Err(BgaError::NotPresent);
+    }
+    let address = framebuffer_address().ok_or(BgaError::NoFramebuffer)?;
+
+    let pitch = width * bits_per_pixel as usize / 8;
+    if address.saturating_add(pitch * height * pages) > 1 << 32 {
+        return Err(BgaError::Unmapped);
+    }
+
+    let text = vga::suspend_text().ok_or(BgaError::InUse)?;
+    let bga = Bga {
+        address: address,
+        width: width,
+        height: height,
+        bits_per_pixel: bits_per_pixel,
+        pages: pages,
+        shown: 0,
+        text: text,
+    };
+
+    write(Register::Enable, 0);
+    write(Register::XResolution, width as u16);
+    write(Register::YResolution, height as u16);
+    write(Register::BitsPerPixel, bits_per_pixel as u16);
+    write(Register::Enable, ENABLED_BIT | LFB_ENABLED_BIT);
+    write(Register::VirtualWidth, width as u16);
+    write(Register::VirtualHeight, (height * pages) as u16);
+    write(Register::YOffset, 0);
+
+    // The adapter clamps what it can't do rather than failing, and sizes the virtual height to
+    // its memory
+    let taken = read(Register::XResolution) as usize == width &&
+        read(Register::YResolution) as usize == height &&
+        read(Register::BitsPerPixel) == bits_per_pixel as u16 &&
+        read(Register::VirtualHeight) as usize >= height * pages;
+
+    if !taken {
+        let _ = bga.disable();
+        return Err(BgaError::ModeRejected);
+    }
+
+    Ok(bga)
+}
+
+/// Sets the mode given with `video=` on the kernel command line, keeping it in `ADAPTER`, and
+/// gives its shown page to draw the console on. Gives `None` if no mode is given.
+pub fn configure(command_line: &str) -> Option<Result<FramebufferInfo, BgaError>> {
+    let mut mode = None;
+    for arg in command_line.split_whitespace() {
+        if arg.starts_with("video=") {
+            mode = Some(parse_mode(&arg["video=".len()..]));
+        }
+    }
+
+    let result = match mode {
+        Some(Some((width, height, bits_per_pixel))) => set_mode(width, height, bits_per_pixel, VIDEO_PAGES),
+        Some(None) => Err(BgaError::InvalidMode),
+        None => return None,
+    };
+
+    Some(result.map(|bga| {
+        let info = bga.info(bga.shown_page()).expect("Shown page should exist");
+        *ADAPTER.lock() = Some(bga);
+        info
+    }))
+}
+
+/// Parses a mode written as `<width>x<height>[x<depth>]`
+fn parse_mode(mode: &str) -> Option<(usize, usize, u8)> {
+    let mut parts = mode.split('x');
+    let width = parts.next()?.parse().ok()?;
+    let height = parts.next()?.parse().ok()?;
+    let bits_per_pixel = match parts.next() {
+        Some(depth) => depth.parse().ok()?,
+        None => DEFAULT_DEPTH,
+    };
+
+    if parts.next().is_some() {
+        return None;
+    }
+
+    Some((width, height, bits_per_pixel))
+}
+
+impl Bga {
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn bits_per_pixel(&self) -> u8 {
+        self.bits_per_pixel
+    }
+
+    pub fn pages(&self) -> usize {
+        self.pages
+    }
+
+    /// Gets the page being shown
+    pub fn shown_page(&self) -> usize {
+        self.shown
+    }
+
+    /// Gets the page to draw the next frame on, which is the shown page if there's only one
+    pub fn back_page(&self) -> usize {
+        (self.shown + 1) % self.pages
+    }
+
+    /// Describes a page as a framebuffer
+    pub fn info(&self, page: usize) -> Option<FramebufferInfo> {
+        if page >= self.pages {
+            return None;
+        }
+
+        let pitch = self.width * self.bits_per_pixel as usize / 8;
+        let format = match self.bits_per_pixel {
+            16 => RGB565,
+            24 => RGB888,
+            _ => XRGB8888,
+        };
+
+        Some(FramebufferInfo {
+            address: self.address + page * pitch * self.height,
+            pitch: pitch,
+            width: self.width,
+            height: self.height,
+            bits_per_pixel: self.bits_per_pixel,
+            red: format.red,
+            green: format.green,
+            blue: format.blue,
+        })
+    }
+
+    /// Gets a page to draw on
+    pub fn framebuffer(&self, page: usize) -> Option<Framebuffer> {
+        self.info(page).and_then(|info| Framebuffer::new(info).ok())
+    }
+
+    /// Gets the back page to draw on
+    pub fn back_buffer(&self) -> Framebuffer {
+        self.framebuffer(self.back_page()).expect("Back page checked when setting mode")
+    }
+
+    /// Shows the given page, by scrolling the virtual screen to it
+    pub fn show_page(&mut self, page: usize) {
+        if page < self.pages {
+            write(Register::YOffset, (page * self.height) as u16);
+            self.shown = page;
+        }
+    }
+
+    /// Shows the back page, making the shown page the back page
+    pub fn flip(&mut self) {
+        let back = self.back_page();
+        self.show_page(back);
+    }
+
+    /// Disables the adapter and goes back to text mode, redrawing the active terminal
+    pub fn disable(mut self) -> Result<(), VgaWriteError> {
+        write(Register::Enable, 0);
+
+        // The framebuffer shares video memory with the font and text, so they're rewritten
+        self.text.resume()
+    }
+}
+
+impl Drop for Bga {
+    /// Runs before the text mode is put back when dropping its fields
+    fn drop(&mut self) {
+        write(Register::Enable, 0);
+    }
+}
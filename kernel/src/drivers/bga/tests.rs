@@ -0,0 +1,150 @@
+use testing::{TestCase, TestResult};
+use drivers::vga::{TERMINALS, VgaBuffer, TextBuffer, active};
+use drivers::vga::{Rgb, RegisterState, GraphicsMode, enter_graphics};
+use super::*;
+
+pub const TESTS: &'static [TestCase] = &[
+    test_case!(detects_adapter),
+    test_case!(finds_framebuffer_through_pci),
+    test_case!(sets_modes),
+    test_case!(rejects_modes),
+    test_case!(flips_pages),
+    test_case!(disabling_restores_text),
+    test_case!(dropping_restores_text),
+    test_case!(excludes_vga_graphics),
+    test_case!(configures_video_mode),
+];
+
+fn detects_adapter() -> TestResult {
+    test_assert!(is_present());
+    test_assert!(version().unwrap() >= VERSION_LFB);
+    Ok(())
+}
+
+fn finds_framebuffer_through_pci() -> TestResult {
+    let function = pci::find(PCI_VENDOR_ID, PCI_DEVICE_ID);
+    test_assert!(function.is_some());
+
+    let function = function.unwrap();
+    test_assert_eq!(function.vendor_id(), PCI_VENDOR_ID);
+    test_assert_eq!(function.device_id(), PCI_DEVICE_ID);
+    test_assert_eq!(framebuffer_address(), function.memory_bar(0).map(|address| address as usize));
+    test_assert!(framebuffer_address().unwrap() != 0);
+    Ok(())
+}
+
+fn sets_modes() -> TestResult {
+    let color = Rgb::from_hex(0xFF8000);
+
+    for &(width, height, bits_per_pixel) in [(640, 480, 32), (800, 600, 16), (1024, 768, 24)].iter() {
+        let bga = set_mode(width, height, bits_per_pixel, 1).unwrap();
+        test_assert_eq!(read(Register::XResolution) as usize, width);
+        test_assert_eq!(read(Register::YResolution) as usize, height);
+        test_assert_eq!(read(Register::BitsPerPixel), bits_per_pixel as u16);
+        test_assert!(read(Register::Enable) & ENABLED_BIT != 0);
+
+        let mut framebuffer = bga.framebuffer(0).unwrap();
+        framebuffer.fill_rect(width - 1, height - 1, 1, 1, color);
+        test_assert_eq!(framebuffer.get_raw(width - 1, height - 1), framebuffer.encode(color));
+
+        test_assert!(bga.disable().is_ok());
+    }
+
+    test_assert_eq!(read(Register::Enable) & ENABLED_BIT, 0);
+    Ok(())
+}
+
+fn rejects_modes() -> TestResult {
+    test_assert_eq!(set_mode(640, 480, 8, 1).err(), Some(BgaError::UnsupportedDepth(8)));
+
+    let bga = set_mode(640, 480, 32, 1).unwrap();
+    test_assert_eq!(set_mode(800, 600, 32, 1).err(), Some(BgaError::InUse));
+    test_assert!(bga.disable().is_ok());
+
+    // More pages than video memory holds
+    test_assert_eq!(set_mode(640, 480, 32, 16).err(), Some(BgaError::ModeRejected));
+
+    let bga = set_mode(640, 480, 32, 1);
+    test_assert!(bga.is_ok());
+    test_assert!(bga.unwrap().disable().is_ok());
+    Ok(())
+}
+
+fn flips_pages() -> TestResult {
+    let mut bga = set_mode(640, 480, 32, 2).unwrap();
+    test_assert_eq!(bga.shown_page(), 0);
+    test_assert_eq!(bga.back_page(), 1);
+    test_assert!(bga.framebuffer(2).is_none());
+
+    let back = bga.info(1).unwrap();
+    test_assert_eq!(back.address, bga.info(0).unwrap().address + 640 * 4 * 480);
+
+    let mut framebuffer = bga.back_buffer();
+    framebuffer.fill_rect(0, 0, 16, 16, Rgb::from_hex(0x00FF00));
+
+    bga.flip();
+    test_assert_eq!(bga.shown_page(), 1);
+    test_assert_eq!(bga.back_page(), 0);
+    test_assert_eq!(read(Register::YOffset), 480);
+
+    bga.flip();
+    test_assert_eq!(bga.shown_page(), 0);
+    test_assert_eq!(read(Register::YOffset), 0);
+
+    test_assert!(bga.disable().is_ok());
+    Ok(())
+}
+
+fn disabling_restores_text() -> TestResult {
+    let registers = RegisterState::save();
+    let screen = VgaBuffer::new();
+    let cell = TERMINALS[active()].lock().buffer().get_char(0, 0);
+
+    let bga = set_mode(640, 480, 32, 1).unwrap();
+    bga.framebuffer(0).unwrap().fill_rect(0, 0, 640, 480, Rgb::from_hex(0xFFFFFF));
+    test_assert!(bga.disable().is_ok());
+
+    test_assert_eq!(RegisterState::save(), registers);
+    test_assert_eq!(screen.get_char(0, 0), cell);
+    Ok(())
+}
+
+fn dropping_restores_text() -> TestResult {
+    let registers = RegisterState::save();
+
+    {
+        let bga = set_mode(640, 480, 32, 1).unwrap();
+        test_assert_eq!(bga.width(), 640);
+    }
+
+    test_assert_eq!(read(Register::Enable) & ENABLED_BIT, 0);
+    test_assert_eq!(RegisterState::save(), registers);
+    Ok(())
+}
+
+fn excludes_vga_graphics() -> TestResult {
+    let graphics = enter_graphics(GraphicsMode::Mode13h).unwrap();
+    test_assert_eq!(set_mode(640, 480, 32, 1).err(), Some(BgaError::InUse));
+    test_assert!(graphics.leave().is_ok());
+
+    let bga = set_mode(640, 480, 32, 1).unwrap();
+    test_assert!(enter_graphics(GraphicsMode::Mode13h).is_none());
+    test_assert!(bga.disable().is_ok());
+    Ok(())
+}
+
+fn configures_video_mode() -> TestResult {
+    test_assert!(configure("vga=80x50").is_none());
+    test_assert_eq!(configure("video=800").unwrap().err(), Some(BgaError::InvalidMode));
+    test_assert_eq!(configure("video=800x600x16x2").unwrap().err(), Some(BgaError::InvalidMode));
+
+    let info = configure("video=640x480 video=800x600x16").unwrap().unwrap();
+    test_assert_eq!((info.width, info.height, info.bits_per_pixel), (800, 600, 16));
+    test_assert_eq!(info.pitch, 800 * 2);
+
+    let bga = ADAPTER.lock().take().unwrap();
+    test_assert_eq!(bga.pages(), VIDEO_PAGES);
+    test_assert_eq!(read(Register::XResolution), 800);
+    test_assert!(bga.disable().is_ok());
+    Ok(())
+}
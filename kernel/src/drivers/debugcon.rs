@@ -0,0 +1,26 @@
+use io::IOPort;
+use console::Console;
+use spin::Mutex;
+
+pub static DEBUGCON: Mutex<DebugCon> = Mutex::new(DebugCon::new(0xE9));
+
+/// Represents the QEMU and Bochs debug console port, which echoes every byte written to it
+pub struct DebugCon {
+    port: IOPort,
+}
+
+impl DebugCon {
+    const fn new(port: u16) -> Self {
+        DebugCon {
+            port: IOPort::new(port),
+        }
+    }
+}
+
+impl Console for DebugCon {
+    fn write(&mut self, str: &str) {
+        for byte in str.bytes() {
+            self.port.write(byte);
+        }
+    }
+}
@@ -0,0 +1,197 @@
+//! A text buffer drawn on a framebuffer with a bitmap font, so `VgaWriter` and everything built on
+//! it works the same as in text mode. Cells are drawn on `flush`, and only those which changed.
+
+use spin::Mutex;
+use drivers::vga::{self, Color, VgaColor, VgaChar, VgaWriter, TextBuffer, MemoryBuffer, CursorShape, Font,
+                   DirtyRows, DEFAULT_THEME};
+use multiboot::FramebufferInfo;
+use super::{Framebuffer, FramebufferError};
+
+/// Font the console is drawn with
+static FONT: &'static [u8] = include_bytes!("../../resources/fonts/console.psf");
+
+/// Width of a character cell in pixels
+const CELL_WIDTH: usize = 8;
+
+/// Scan lines from the bottom of the cell which the underline cursor covers
+const UNDERLINE_HEIGHT: usize = 2;
+
+const COLOR: VgaColor = VgaColor::new(Color::White, Color::Black);
+
+pub type FramebufferConsole = VgaWriter<FramebufferBuffer>;
+
+pub static FRAMEBUFFER_CONSOLE: Mutex<FramebufferConsole> =
+    Mutex::new(VgaWriter::with_buffer(FramebufferBuffer::new(), COLOR));
+
+/// Gets the font the console is drawn with
+pub fn font() -> Font<'static> {
+    vga::parse_psf(FONT).expect("Console font should be valid")
+}
+
+/// Starts drawing the framebuffer console on the given framebuffer, sized to fit it
+pub fn init(info: FramebufferInfo) -> Result<(), FramebufferError> {
+    let framebuffer = Framebuffer::new(info)?;
+    let font = font();
+
+    let mut console = FRAMEBUFFER_CONSOLE.lock();
+    console.buffer_mut().set_framebuffer(framebuffer, font);
+
+    let (columns, rows) = console.buffer().fitting_size();
+    console.resize(columns, rows).expect("Color code should be valid");
+    console.fill_screen(Color::Black);
+    console.flush();
+
+    Ok(())
+}
+
+/// A text buffer's cells, kept in memory and drawn on a framebuffer. Colors are shown as the
+/// standard VGA colors.
+pub struct FramebufferBuffer {
+    cells: MemoryBuffer,
+    framebuffer: Option<Framebuffer>,
+    font: Option<Font<'static>>,
+    cursor_shape: CursorShape,
+    /// Cells changed since they were last drawn
+    dirty: DirtyRows,
+}
+
+impl FramebufferBuffer {
+    pub const fn new() -> Self {
+        FramebufferBuffer {
+            cells: MemoryBuffer::new(),
+            framebuffer: None,
+            font: None,
+            cursor_shape: CursorShape::Underline,
+            dirty: DirtyRows::new(),
+        }
+    }
+
+    /// Sets the framebuffer and font to draw with, clearing the framebuffer. Cells are drawn
+    /// from then on.
+    pub fn set_framebuffer(&mut self, framebuffer: Framebuffer, font: Font<'static>) {
+        self.framebuffer = Some(framebuffer);
+        self.font = Some(font);
+        self.redraw();
+    }
+
+    /// Gets the amount of columns and rows of cells which fit on the framebuffer, up to
+    /// `MAX_COLUMNS` by `MAX_ROWS`
+    pub fn fitting_size(&self) -> (usize, usize) {
+        match (self.framebuffer.as_ref(), self.font) {
+            (Some(framebuffer), Some(font)) => (
+                framebuffer.width() / CELL_WIDTH,
+                framebuffer.height() / font.height(),
+            ),
+            _ => (self.cells.width(), self.cells.height()),
+        }
+    }
+
+    /// Clears the framebuffer, and draws every cell and the cursor
+    fn redraw(&mut self) {
+        if let Some(framebuffer) = self.framebuffer.as_mut() {
+            let (width, height) = (framebuffer.width(), framebuffer.height());
+            framebuffer.fill_rect(0, 0, width, height, DEFAULT_THEME.colors[Color::Black as usize]);
+        }
+
+        for row in 0..self.cells.height() {
+            for column in 0..self.cells.width() {
+                self.draw_cell(row, column);
+            }
+        }
+
+        self.dirty.clear();
+    }
+
+    /// Draws a cell, with the cursor if it's on it
+    fn draw_cell(&mut self, row: usize, column: usize) {
+        if row >= self.cells.height() || column >= self.cells.width() {
+            return;
+        }
+
+        let (framebuffer, font) = match (self.framebuffer.as_mut(), self.font) {
+            (Some(framebuffer), Some(font)) => (framebuffer, font),
+            _ => return,
+        };
+
+        let value = self.cells.get_char(row, column);
+        let foreground = framebuffer.encode(DEFAULT_THEME.colors[value.color.foreground() as usize]);
+        let background = framebuffer.encode(DEFAULT_THEME.colors[value.color.background() as usize]);
+
+        let height = font.height();
+        let cursor_rows = if self.cells.cursor() != (row, column) {
+            0
+        } else {
+            match self.cursor_shape {
+                CursorShape::Underline => UNDERLINE_HEIGHT,
+                CursorShape::Block => height,
+                CursorShape::Hidden => 0,
+            }
+        };
+
+        let glyph = font.glyph(value.character);
+        let (left, top) = (column * CELL_WIDTH, row * height);
+
+        for (y, &bits) in glyph.iter().enumerate() {
+            // The cursor inverts the bottom rows of the cell
+            let bits = if y >= height - cursor_rows { !bits } else { bits };
+
+            for x in 0..CELL_WIDTH {
+                let set = bits & (0x80 >> x) != 0;
+                framebuffer.put_raw(left + x, top + y, if set { foreground } else { background });
+            }
+        }
+    }
+}
+
+impl TextBuffer for FramebufferBuffer {
+    fn width(&self) -> usize {
+        self.cells.width()
+    }
+
+    fn height(&self) -> usize {
+        self.cells.height()
+    }
+
+    fn set_char(&mut self, row: usize, column: usize, value: VgaChar) {
+        if self.cells.get_char(row, column) != value {
+            self.cells.set_char(row, column, value);
+            self.dirty.mark_cell(row, column);
+        }
+    }
+
+    fn get_char(&self, row: usize, column: usize) -> VgaChar {
+        self.cells.get_char(row, column)
+    }
+
+    fn resize(&mut self, width: usize, height: usize) {
+        self.cells.resize(width, height);
+        self.redraw();
+    }
+
+    fn set_cursor(&mut self, row: usize, column: usize) {
+        let (previous_row, previous_column) = self.cells.cursor();
+        self.cells.set_cursor(row, column);
+
+        // Both cells are redrawn, to move the cursor drawn over them
+        self.dirty.mark_cell(previous_row, previous_column);
+        self.dirty.mark_cell(row, column);
+    }
+
+    fn set_cursor_shape(&mut self, shape: CursorShape) {
+        self.cursor_shape = shape;
+
+        let (row, column) = self.cells.cursor();
+        self.dirty.mark_cell(row, column);
+    }
+
+    /// Draws the changed cells
+    fn flush(&mut self) {
+        for row in 0..self.cells.height() {
+            if let Some((start, end)) = self.dirty.take_row(row) {
+                for column in start..end {
+                    self.draw_cell(row, column);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,128 @@
+//! Linear framebuffers set up by the bootloader, with a console drawing text on them
+
+use core::{cmp, ptr};
+use multiboot::FramebufferInfo;
+use drivers::vga::Rgb;
+use gfx::{Surface, PixelFormat};
+
+mod console;
+
+#[cfg(feature = "kernel-test")]
+pub mod tests;
+
+pub use self::console::{FramebufferBuffer, FramebufferConsole, FRAMEBUFFER_CONSOLE, font, init};
+
+/// End of the memory identity mapped at boot, which a framebuffer must be inside
+const MAPPED_END: usize = 1 << 32;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum FramebufferError {
+    /// Only 16, 24 and 32 bits per pixel are supported
+    UnsupportedDepth(u8),
+    /// The framebuffer is beyond the memory mapped at boot
+    Unmapped,
+}
+
+/// Represents a linear framebuffer of RGB pixels
+pub struct Framebuffer {
+    address: usize,
+    pitch: usize,
+    width: usize,
+    height: usize,
+    format: PixelFormat,
+}
+
+impl Framebuffer {
+    /// Creates a framebuffer from the bootloader's description of it
+    pub fn new(info: FramebufferInfo) -> Result<Self, FramebufferError> {
+        match info.bits_per_pixel {
+            16 | 24 | 32 => {}
+            depth => return Err(FramebufferError::UnsupportedDepth(depth)),
+        }
+
+        if info.address.saturating_add(info.pitch * info.height) > MAPPED_END {
+            return Err(FramebufferError::Unmapped);
+        }
+
+        Ok(Framebuffer {
+            address: info.address,
+            pitch: info.pitch,
+            width: info.width,
+            height: info.height,
+            format: PixelFormat::new(info.bits_per_pixel, info.red, info.green, info.blue),
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Encodes a color as a pixel value in this framebuffer's format
+    pub fn encode(&self, color: Rgb) -> u32 {
+        self.format.encode(color)
+    }
+
+    /// Writes an encoded pixel value. Pixels off screen are ignored.
+    pub fn put_raw(&mut self, x: usize, y: usize, value: u32) {
+        if x >= self.width || y >= self.height {
+            return;
+        }
+
+        let address = self.address + y * self.pitch + x * self.format.bytes_per_pixel;
+        for byte in 0..self.format.bytes_per_pixel {
+            unsafe { ptr::write_volatile((address + byte) as *mut u8, (value >> (byte * 8)) as u8) };
+        }
+    }
+
+    /// Reads an encoded pixel value, or 0 off screen
+    pub fn get_raw(&self, x: usize, y: usize) -> u32 {
+        if x >= self.width || y >= self.height {
+            return 0;
+        }
+
+        let address = self.address + y * self.pitch + x * self.format.bytes_per_pixel;
+        (0..self.format.bytes_per_pixel).fold(0, |value, byte| {
+            value | (unsafe { ptr::read_volatile((address + byte) as *const u8) } as u32) << (byte * 8)
+        })
+    }
+
+    /// Fills a rectangle, clipped to the screen
+    pub fn fill_rect(&mut self, x: usize, y: usize, width: usize, height: usize, color: Rgb) {
+        let value = self.encode(color);
+
+        let right = cmp::min(x.saturating_add(width), self.width);
+        let bottom = cmp::min(y.saturating_add(height), self.height);
+
+        for row in y..bottom {
+            for column in x..right {
+                self.put_raw(column, row, value);
+            }
+        }
+    }
+}
+
+impl Surface for Framebuffer {
+    fn width(&self) -> usize {
+        self.width
+    }
+
+    fn height(&self) -> usize {
+        self.height
+    }
+
+    fn format(&self) -> PixelFormat {
+        self.format
+    }
+
+    fn put_raw(&mut self, x: usize, y: usize, value: u32) {
+        Framebuffer::put_raw(self, x, y, value)
+    }
+
+    fn get_raw(&self, x: usize, y: usize) -> u32 {
+        Framebuffer::get_raw(self, x, y)
+    }
+}
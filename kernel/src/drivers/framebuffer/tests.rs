@@ -0,0 +1,148 @@
+use testing::{TestCase, TestResult};
+use multiboot::{FramebufferInfo, ColorField};
+use drivers::vga::{Color, VgaColor, VgaChar, TextBuffer, CursorShape};
+use gfx::{Canvas, Rect, RGB565};
+use super::*;
+
+pub const TESTS: &'static [TestCase] = &[
+    test_case!(encode_32_bpp),
+    test_case!(encode_16_bpp),
+    test_case!(pixels_24_bpp),
+    test_case!(unsupported_framebuffers),
+    test_case!(draws_glyphs),
+    test_case!(draws_cursor),
+    test_case!(draws_with_canvas),
+];
+
+const WIDTH: usize = 64;
+const HEIGHT: usize = 32;
+
+const WHITE: VgaColor = VgaColor::new(Color::White, Color::Black);
+
+/// Describes a framebuffer of `WIDTH` by `HEIGHT` pixels in the given memory
+fn info(memory: &mut [u8], bits_per_pixel: u8) -> FramebufferInfo {
+    let bytes_per_pixel = bits_per_pixel as usize / 8;
+    assert!(memory.len() >= WIDTH * HEIGHT * bytes_per_pixel, "Framebuffer memory too small");
+
+    let (red, green, blue) = if bits_per_pixel == 16 {
+        (ColorField { position: 11, size: 5 }, ColorField { position: 5, size: 6 }, ColorField { position: 0, size: 5 })
+    } else {
+        (ColorField { position: 16, size: 8 }, ColorField { position: 8, size: 8 }, ColorField { position: 0, size: 8 })
+    };
+
+    FramebufferInfo {
+        address: memory.as_mut_ptr() as usize,
+        pitch: WIDTH * bytes_per_pixel,
+        width: WIDTH,
+        height: HEIGHT,
+        bits_per_pixel: bits_per_pixel,
+        red: red,
+        green: green,
+        blue: blue,
+    }
+}
+
+fn encode_32_bpp() -> TestResult {
+    let mut memory = [0; WIDTH * HEIGHT * 4];
+    let framebuffer = Framebuffer::new(info(&mut memory, 32)).unwrap();
+
+    test_assert_eq!(framebuffer.encode(Rgb::new(0x12, 0x34, 0x56)), 0x123456);
+    Ok(())
+}
+
+fn encode_16_bpp() -> TestResult {
+    let mut memory = [0; WIDTH * HEIGHT * 2];
+    let mut framebuffer = Framebuffer::new(info(&mut memory, 16)).unwrap();
+
+    test_assert_eq!(framebuffer.encode(Rgb::new(0xFF, 0x80, 0x08)), 0xFC01);
+
+    framebuffer.fill_rect(1, 0, 1, 1, Rgb::new(0xFF, 0x80, 0x08));
+    test_assert_eq!(framebuffer.get_raw(1, 0), 0xFC01);
+    test_assert_eq!(&memory[2..4], &[0x01, 0xFC][..]);
+    Ok(())
+}
+
+fn pixels_24_bpp() -> TestResult {
+    let mut memory = [0; WIDTH * HEIGHT * 3];
+    let mut framebuffer = Framebuffer::new(info(&mut memory, 24)).unwrap();
+
+    framebuffer.fill_rect(1, 1, 1, 1, Rgb::from_hex(0xABCDEF));
+    framebuffer.fill_rect(WIDTH, 0, 1, 1, Rgb::from_hex(0xFFFFFF));
+    test_assert_eq!(framebuffer.get_raw(1, 1), 0xABCDEF);
+    test_assert_eq!(framebuffer.get_raw(0, 1), 0);
+    test_assert_eq!(framebuffer.get_raw(2, 1), 0);
+    Ok(())
+}
+
+fn unsupported_framebuffers() -> TestResult {
+    let mut memory = [0; WIDTH * HEIGHT];
+    test_assert_eq!(Framebuffer::new(info(&mut memory, 8)).err(), Some(FramebufferError::UnsupportedDepth(8)));
+
+    let mut high = info(&mut memory, 8);
+    high.bits_per_pixel = 32;
+    high.address = 0xFFFF_F000;
+    test_assert_eq!(Framebuffer::new(high).err(), Some(FramebufferError::Unmapped));
+    Ok(())
+}
+
+/// Creates a console buffer of 8 by 2 cells drawing on the given memory
+fn buffer(memory: &mut [u8]) -> FramebufferBuffer {
+    let mut buffer = FramebufferBuffer::new();
+    buffer.set_framebuffer(Framebuffer::new(info(memory, 32)).unwrap(), font());
+
+    let (columns, rows) = buffer.fitting_size();
+    buffer.resize(columns, rows);
+    buffer
+}
+
+fn draws_glyphs() -> TestResult {
+    let mut memory = [0; WIDTH * HEIGHT * 4];
+    let mut buffer = buffer(&mut memory);
+    test_assert_eq!((buffer.width(), buffer.height()), (8, 2));
+
+    // The top of the A is the middle 3 pixels of its 4th row
+    buffer.set_char(1, 1, VgaChar::new(WHITE, 'A'));
+    buffer.flush();
+    let framebuffer = Framebuffer::new(info(&mut memory, 32)).unwrap();
+    test_assert_eq!(framebuffer.get_raw(8 + 2, 16 + 3), 0xFFFFFF);
+    test_assert_eq!(framebuffer.get_raw(8 + 1, 16 + 3), 0);
+    test_assert_eq!(framebuffer.get_raw(8 + 2, 16 + 2), 0);
+    Ok(())
+}
+
+fn draws_cursor() -> TestResult {
+    let mut memory = [0; WIDTH * HEIGHT * 4];
+    let mut buffer = buffer(&mut memory);
+    buffer.set_char(0, 3, VgaChar::new(WHITE, ' '));
+    buffer.set_char(0, 4, VgaChar::new(WHITE, ' '));
+
+    buffer.set_cursor(0, 3);
+    buffer.set_cursor_shape(CursorShape::Underline);
+    buffer.flush();
+    test_assert_eq!(Framebuffer::new(info(&mut memory, 32)).unwrap().get_raw(3 * 8, 15), 0xFFFFFF);
+
+    // Moving the cursor away redraws the cell without it
+    buffer.set_cursor(0, 4);
+    buffer.flush();
+    let framebuffer = Framebuffer::new(info(&mut memory, 32)).unwrap();
+    test_assert_eq!(framebuffer.get_raw(3 * 8, 15), 0);
+    test_assert_eq!(framebuffer.get_raw(4 * 8, 14), 0xFFFFFF);
+    test_assert_eq!(framebuffer.get_raw(4 * 8, 13), 0);
+    Ok(())
+}
+
+fn draws_with_canvas() -> TestResult {
+    let mut memory = [0; WIDTH * HEIGHT * 2];
+    let mut framebuffer = Framebuffer::new(info(&mut memory, 16)).unwrap();
+    test_assert_eq!(Surface::format(&framebuffer), RGB565);
+
+    {
+        let mut canvas = Canvas::new(&mut framebuffer);
+        canvas.fill_rect(Rect::new(1, 0, 1, 1), Rgb::from_hex(0xFFFFFF));
+        test_assert_eq!(canvas.get_pixel(1, 0), Some(Rgb::from_hex(0xFFFFFF)));
+        test_assert_eq!(canvas.get_pixel(WIDTH as isize, 0), None);
+    }
+
+    test_assert_eq!(&memory[0..4], &[0x00, 0x00, 0xFF, 0xFF][..]);
+    Ok(())
+}
@@ -0,0 +1,30 @@
+//! Input from the PS/2 keyboard and mouse, which share the controller's output buffer
+
+use drivers::ps2::io;
+use drivers::keyboard::{KeyEvent, KEYBOARD};
+use drivers::mouse::{MouseEvent, MOUSE};
+
+/// Represents an event from either device
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum InputEvent {
+    Key(KeyEvent),
+    Mouse(MouseEvent),
+}
+
+/// Reads waiting bytes from the controller, handing each to the driver of the port it came from,
+/// until one completes an event or none are left
+pub fn poll() -> Option<InputEvent> {
+    while let Some((byte, second_port)) = io::poll_data() {
+        let event = if second_port {
+            MOUSE.lock().handle_byte(byte).map(InputEvent::Mouse)
+        } else {
+            KEYBOARD.lock().handle_polled_byte(byte).map(InputEvent::Key)
+        };
+
+        if event.is_some() {
+            return event;
+        }
+    }
+
+    None
+}
@@ -0,0 +1,151 @@
+/// Represents a physical key on a keyboard, independent of layout and modifiers
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Key {
+    A, B, C, D, E, F, G, H, I, J, K, L, M,
+    N, O, P, Q, R, S, T, U, V, W, X, Y, Z,
+    Num0, Num1, Num2, Num3, Num4, Num5, Num6, Num7, Num8, Num9,
+    F1, F2, F3, F4, F5, F6, F7, F8, F9, F10, F11, F12,
+
+    Backtick,
+    Minus,
+    Equals,
+    LeftBracket,
+    RightBracket,
+    Backslash,
+    Semicolon,
+    Quote,
+    Comma,
+    Period,
+    Slash,
+
+    Space,
+    Tab,
+    Enter,
+    Backspace,
+    Escape,
+
+    LeftShift,
+    RightShift,
+    LeftCtrl,
+    RightCtrl,
+    LeftAlt,
+    RightAlt,
+    LeftGui,
+    RightGui,
+    Menu,
+    CapsLock,
+    NumLock,
+    ScrollLock,
+    PrintScreen,
+
+    Insert,
+    Delete,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    Up,
+    Down,
+    Left,
+    Right,
+
+    Keypad0, Keypad1, Keypad2, Keypad3, Keypad4,
+    Keypad5, Keypad6, Keypad7, Keypad8, Keypad9,
+    KeypadPeriod,
+    KeypadPlus,
+    KeypadMinus,
+    KeypadStar,
+    KeypadSlash,
+    KeypadEnter,
+}
+
+impl Key {
+    /// Gets the char this key types on a US layout, given whether shift is held
+    pub fn char(self, shifted: bool) -> Option<char> {
+        use self::Key::*;
+
+        let (normal, shift) = match self {
+            A => ('a', 'A'), B => ('b', 'B'), C => ('c', 'C'), D => ('d', 'D'),
+            E => ('e', 'E'), F => ('f', 'F'), G => ('g', 'G'), H => ('h', 'H'),
+            I => ('i', 'I'), J => ('j', 'J'), K => ('k', 'K'), L => ('l', 'L'),
+            M => ('m', 'M'), N => ('n', 'N'), O => ('o', 'O'), P => ('p', 'P'),
+            Q => ('q', 'Q'), R => ('r', 'R'), S => ('s', 'S'), T => ('t', 'T'),
+            U => ('u', 'U'), V => ('v', 'V'), W => ('w', 'W'), X => ('x', 'X'),
+            Y => ('y', 'Y'), Z => ('z', 'Z'),
+
+            Num0 => ('0', ')'), Num1 => ('1', '!'), Num2 => ('2', '@'), Num3 => ('3', '#'),
+            Num4 => ('4', '$'), Num5 => ('5', '%'), Num6 => ('6', '^'), Num7 => ('7', '&'),
+            Num8 => ('8', '*'), Num9 => ('9', '('),
+
+            Backtick => ('`', '~'),
+            Minus => ('-', '_'),
+            Equals => ('=', '+'),
+            LeftBracket => ('[', '{'),
+            RightBracket => (']', '}'),
+            Backslash => ('\\', '|'),
+            Semicolon => (';', ':'),
+            Quote => ('\'', '"'),
+            Comma => (',', '<'),
+            Period => ('.', '>'),
+            Slash => ('/', '?'),
+
+            Space => (' ', ' '),
+            Tab => ('\t', '\t'),
+            Enter | KeypadEnter => ('\n', '\n'),
+            Backspace => ('\x08', '\x08'),
+
+            KeypadPlus => ('+', '+'),
+            KeypadMinus => ('-', '-'),
+            KeypadStar => ('*', '*'),
+            KeypadSlash => ('/', '/'),
+
+            _ => return None,
+        };
+
+        Some(if shifted { shift } else { normal })
+    }
+
+    /// Checks if this key is a letter, which caps lock applies to
+    pub fn is_letter(self) -> bool {
+        match self.char(false) {
+            Some('a'...'z') => true,
+            _ => false,
+        }
+    }
+
+    /// Checks if this key is a modifier or lock key, which doesn't type anything itself
+    pub fn is_modifier(self) -> bool {
+        use self::Key::*;
+
+        match self {
+            LeftShift | RightShift | LeftCtrl | RightCtrl | LeftAlt | RightAlt | LeftGui |
+            RightGui | CapsLock | NumLock | ScrollLock => true,
+            _ => false,
+        }
+    }
+
+    /// Gets the char this keypad key types while num lock is on
+    pub fn keypad_char(self) -> Option<char> {
+        use self::Key::*;
+
+        match self {
+            Keypad0 => Some('0'), Keypad1 => Some('1'), Keypad2 => Some('2'),
+            Keypad3 => Some('3'), Keypad4 => Some('4'), Keypad5 => Some('5'),
+            Keypad6 => Some('6'), Keypad7 => Some('7'), Keypad8 => Some('8'),
+            Keypad9 => Some('9'), KeypadPeriod => Some('.'),
+            _ => None,
+        }
+    }
+
+    /// Gets the function key number of this key, from 1 to 12
+    pub fn function_number(self) -> Option<u8> {
+        use self::Key::*;
+
+        match self {
+            F1 => Some(1), F2 => Some(2), F3 => Some(3), F4 => Some(4),
+            F5 => Some(5), F6 => Some(6), F7 => Some(7), F8 => Some(8),
+            F9 => Some(9), F10 => Some(10), F11 => Some(11), F12 => Some(12),
+            _ => None,
+        }
+    }
+}
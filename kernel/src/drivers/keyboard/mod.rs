@@ -0,0 +1,213 @@
+//! Polled driver for a PS/2 keyboard on the first port, using scan code set 2
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+use spin::Mutex;
+use drivers::ps2::{self, io};
+use drivers::ps2::io::DeviceCommand;
+use drivers::mouse::MOUSE;
+
+mod key;
+mod scancode;
+
+#[cfg(feature = "kernel-test")]
+pub mod tests;
+
+pub use self::key::Key;
+pub use self::scancode::{Decoder, ScanCode};
+
+const SCROLL_LOCK_LED: u8 = 1 << 0;
+const NUM_LOCK_LED: u8 = 1 << 1;
+const CAPS_LOCK_LED: u8 = 1 << 2;
+
+pub static KEYBOARD: Mutex<Keyboard> = Mutex::new(Keyboard::new());
+
+/// The LED bits last sent to the keyboard, readable without locking it
+static LIT_LEDS: AtomicUsize = AtomicUsize::new(0);
+
+/// Gets the keyboard's lit LEDs. This doesn't lock the keyboard, so interrupt handlers can use it.
+pub fn leds() -> Leds {
+    let leds = LIT_LEDS.load(Ordering::Relaxed) as u8;
+
+    Leds {
+        caps_lock: leds & CAPS_LOCK_LED != 0,
+        num_lock: leds & NUM_LOCK_LED != 0,
+        scroll_lock: leds & SCROLL_LOCK_LED != 0,
+    }
+}
+
+/// Represents which of the keyboard's lock LEDs are lit
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Leds {
+    pub caps_lock: bool,
+    pub num_lock: bool,
+    pub scroll_lock: bool,
+}
+
+/// Represents the held modifier keys and the lock states
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Modifiers {
+    pub left_shift: bool,
+    pub right_shift: bool,
+    pub left_ctrl: bool,
+    pub right_ctrl: bool,
+    pub left_alt: bool,
+    pub right_alt: bool,
+    pub caps_lock: bool,
+    pub num_lock: bool,
+    pub scroll_lock: bool,
+}
+
+impl Modifiers {
+    pub const fn new() -> Self {
+        Modifiers {
+            left_shift: false,
+            right_shift: false,
+            left_ctrl: false,
+            right_ctrl: false,
+            left_alt: false,
+            right_alt: false,
+            caps_lock: false,
+            num_lock: false,
+            scroll_lock: false,
+        }
+    }
+
+    pub fn shift(&self) -> bool {
+        self.left_shift || self.right_shift
+    }
+
+    pub fn ctrl(&self) -> bool {
+        self.left_ctrl || self.right_ctrl
+    }
+
+    pub fn alt(&self) -> bool {
+        self.left_alt || self.right_alt
+    }
+
+    /// Gets the LED bits for the keyboard's set LEDs command
+    fn leds(&self) -> u8 {
+        let mut leds = 0;
+        if self.scroll_lock {
+            leds |= SCROLL_LOCK_LED;
+        }
+        if self.num_lock {
+            leds |= NUM_LOCK_LED;
+        }
+        if self.caps_lock {
+            leds |= CAPS_LOCK_LED;
+        }
+        leds
+    }
+
+    /// Updates the modifiers for a key going down or up
+    fn update(&mut self, key: Key, pressed: bool) {
+        match key {
+            Key::LeftShift => self.left_shift = pressed,
+            Key::RightShift => self.right_shift = pressed,
+            Key::LeftCtrl => self.left_ctrl = pressed,
+            Key::RightCtrl => self.right_ctrl = pressed,
+            Key::LeftAlt => self.left_alt = pressed,
+            Key::RightAlt => self.right_alt = pressed,
+            Key::CapsLock if pressed => self.caps_lock = !self.caps_lock,
+            Key::NumLock if pressed => self.num_lock = !self.num_lock,
+            Key::ScrollLock if pressed => self.scroll_lock = !self.scroll_lock,
+            _ => {}
+        }
+    }
+}
+
+/// Represents a key being pressed or released, along with the modifiers after it
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct KeyEvent {
+    pub key: Key,
+    pub pressed: bool,
+    pub modifiers: Modifiers,
+}
+
+impl KeyEvent {
+    /// Gets the char this key types on a US layout, if any
+    pub fn char(&self) -> Option<char> {
+        if let Some(character) = self.key.keypad_char() {
+            return if self.modifiers.num_lock { Some(character) } else { None };
+        }
+
+        // Caps lock inverts shift for letters only
+        let shifted = self.modifiers.shift() != (self.modifiers.caps_lock && self.key.is_letter());
+        self.key.char(shifted)
+    }
+}
+
+/// Represents the keyboard, tracking modifiers across key events
+pub struct Keyboard {
+    decoder: Decoder,
+    modifiers: Modifiers,
+}
+
+impl Keyboard {
+    const fn new() -> Self {
+        Keyboard {
+            decoder: Decoder::new(),
+            modifiers: Modifiers::new(),
+        }
+    }
+
+    /// Reads waiting bytes from the controller until one completes a key event, or none are left.
+    /// Bytes from the mouse are still decoded, so that it stays in step, but its events are dropped.
+    pub fn poll(&mut self) -> Option<KeyEvent> {
+        while let Some((byte, second_port)) = io::poll_data() {
+            if second_port {
+                MOUSE.lock().handle_byte(byte);
+                continue;
+            }
+
+            let event = self.handle_polled_byte(byte);
+            if event.is_some() {
+                return event;
+            }
+        }
+
+        None
+    }
+
+    /// Handles a byte read from the controller, lighting the LEDs if a lock state changes
+    pub fn handle_polled_byte(&mut self, byte: u8) -> Option<KeyEvent> {
+        let locks = self.modifiers.leds();
+        let event = self.handle_byte(byte);
+
+        if self.modifiers.leds() != locks {
+            self.update_leds();
+        }
+
+        event
+    }
+
+    /// Handles a byte of scan code, returning the key event it completes if any
+    pub fn handle_byte(&mut self, byte: u8) -> Option<KeyEvent> {
+        let code = self.decoder.advance(byte)?;
+        self.modifiers.update(code.key, code.pressed);
+
+        Some(KeyEvent {
+            key: code.key,
+            pressed: code.pressed,
+            modifiers: self.modifiers,
+        })
+    }
+
+    /// Gets the currently held modifiers and lock states
+    #[cfg(feature = "kernel-test")]
+    pub fn modifiers(&self) -> Modifiers {
+        self.modifiers
+    }
+
+    /// Lights the keyboard's LEDs to match the lock states
+    fn update_leds(&mut self) {
+        let leds = self.modifiers.leds();
+        LIT_LEDS.store(leds as usize, Ordering::Relaxed);
+
+        let result = ps2::PS2.lock().devices[0].command_data(DeviceCommand::SetLeds, leds);
+
+        if !ps2::is_ok(result) {
+            warn!("kbd", "failed to set leds");
+        }
+    }
+}
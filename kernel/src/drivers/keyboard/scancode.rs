@@ -0,0 +1,101 @@
+//! Decoder for PS/2 scan code set 2, which the keyboard sends while the controller's translation
+//! is disabled
+
+use super::Key;
+use super::Key::*;
+
+const EXTENDED: u8 = 0xE0;
+const PAUSE: u8 = 0xE1;
+const RELEASE: u8 = 0xF0;
+
+/// Bytes following the `E1` prefix for the pause key, which has no release code
+const PAUSE_LENGTH: u8 = 7;
+
+/// Represents a key going down or up
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ScanCode {
+    pub key: Key,
+    pub pressed: bool,
+}
+
+/// Turns a stream of scan code bytes into key presses and releases
+pub struct Decoder {
+    extended: bool,
+    release: bool,
+    /// Bytes of the pause sequence left to skip
+    skip: u8,
+}
+
+impl Decoder {
+    pub const fn new() -> Self {
+        Decoder {
+            extended: false,
+            release: false,
+            skip: 0,
+        }
+    }
+
+    /// Feeds the next byte, returning the scan code it completes if any. Unknown keys are dropped.
+    pub fn advance(&mut self, byte: u8) -> Option<ScanCode> {
+        if self.skip > 0 {
+            self.skip -= 1;
+            return None;
+        }
+
+        match byte {
+            EXTENDED => self.extended = true,
+            RELEASE => self.release = true,
+            PAUSE => self.skip = PAUSE_LENGTH,
+            code => {
+                let key = if self.extended { extended_key(code) } else { key(code) };
+                let pressed = !self.release;
+
+                self.extended = false;
+                self.release = false;
+
+                return key.map(|key| ScanCode { key: key, pressed: pressed });
+            }
+        }
+
+        None
+    }
+}
+
+/// Gets the key for a single byte scan code
+fn key(code: u8) -> Option<Key> {
+    Some(match code {
+        0x01 => F9, 0x03 => F5, 0x04 => F3, 0x05 => F1, 0x06 => F2, 0x07 => F12,
+        0x09 => F10, 0x0A => F8, 0x0B => F6, 0x0C => F4, 0x0D => Tab, 0x0E => Backtick,
+        0x11 => LeftAlt, 0x12 => LeftShift, 0x14 => LeftCtrl, 0x15 => Q, 0x16 => Num1,
+        0x1A => Z, 0x1B => S, 0x1C => A, 0x1D => W, 0x1E => Num2,
+        0x21 => C, 0x22 => X, 0x23 => D, 0x24 => E, 0x25 => Num4, 0x26 => Num3,
+        0x29 => Space, 0x2A => V, 0x2B => F, 0x2C => T, 0x2D => R, 0x2E => Num5,
+        0x31 => N, 0x32 => B, 0x33 => H, 0x34 => G, 0x35 => Y, 0x36 => Num6,
+        0x3A => M, 0x3B => J, 0x3C => U, 0x3D => Num7, 0x3E => Num8,
+        0x41 => Comma, 0x42 => K, 0x43 => I, 0x44 => O, 0x45 => Num0, 0x46 => Num9,
+        0x49 => Period, 0x4A => Slash, 0x4B => L, 0x4C => Semicolon, 0x4D => P, 0x4E => Minus,
+        0x52 => Quote, 0x54 => LeftBracket, 0x55 => Equals,
+        0x58 => CapsLock, 0x59 => RightShift, 0x5A => Enter, 0x5B => RightBracket,
+        0x5D => Backslash, 0x66 => Backspace,
+        0x69 => Keypad1, 0x6B => Keypad4, 0x6C => Keypad7,
+        0x70 => Keypad0, 0x71 => KeypadPeriod, 0x72 => Keypad2, 0x73 => Keypad5,
+        0x74 => Keypad6, 0x75 => Keypad8, 0x76 => Escape, 0x77 => NumLock,
+        0x78 => F11, 0x79 => KeypadPlus, 0x7A => Keypad3, 0x7B => KeypadMinus,
+        0x7C => KeypadStar, 0x7D => Keypad9, 0x7E => ScrollLock,
+        0x83 => F7,
+        _ => return None,
+    })
+}
+
+/// Gets the key for a scan code following the `E0` prefix. The fake shifts sent around print
+/// screen and some navigation keys are dropped.
+fn extended_key(code: u8) -> Option<Key> {
+    Some(match code {
+        0x11 => RightAlt, 0x14 => RightCtrl, 0x1F => LeftGui, 0x27 => RightGui, 0x2F => Menu,
+        0x4A => KeypadSlash, 0x5A => KeypadEnter,
+        0x69 => End, 0x6B => Left, 0x6C => Home,
+        0x70 => Insert, 0x71 => Delete, 0x72 => Down, 0x74 => Right, 0x75 => Up,
+        0x7A => PageDown, 0x7C => PrintScreen, 0x7D => PageUp,
+        _ => return None,
+    })
+}
@@ -0,0 +1,118 @@
+use testing::{TestCase, TestResult};
+use super::*;
+
+pub const TESTS: &'static [TestCase] = &[
+    test_case!(press_and_release),
+    test_case!(extended_keys),
+    test_case!(print_screen_without_fake_shifts),
+    test_case!(pause_skipped),
+    test_case!(unknown_codes_dropped),
+    test_case!(shift_changes_chars),
+    test_case!(caps_lock_toggles),
+    test_case!(keypad_needs_num_lock),
+];
+
+/// Feeds scan code bytes to the keyboard, returning the last event
+fn feed(keyboard: &mut Keyboard, bytes: &[u8]) -> Option<KeyEvent> {
+    let mut last = None;
+    for &byte in bytes {
+        last = keyboard.handle_byte(byte);
+    }
+    last
+}
+
+fn press_and_release() -> TestResult {
+    let mut decoder = Decoder::new();
+    test_assert_eq!(decoder.advance(0x1C), Some(ScanCode { key: Key::A, pressed: true }));
+    test_assert_eq!(decoder.advance(0xF0), None);
+    test_assert_eq!(decoder.advance(0x1C), Some(ScanCode { key: Key::A, pressed: false }));
+    Ok(())
+}
+
+fn extended_keys() -> TestResult {
+    let mut decoder = Decoder::new();
+    test_assert_eq!(decoder.advance(0xE0), None);
+    test_assert_eq!(decoder.advance(0x7D), Some(ScanCode { key: Key::PageUp, pressed: true }));
+
+    test_assert_eq!(decoder.advance(0xE0), None);
+    test_assert_eq!(decoder.advance(0xF0), None);
+    test_assert_eq!(decoder.advance(0x7A), Some(ScanCode { key: Key::PageDown, pressed: false }));
+
+    // The prefix only applies to one code
+    test_assert_eq!(decoder.advance(0x7A), Some(ScanCode { key: Key::Keypad3, pressed: true }));
+    Ok(())
+}
+
+fn print_screen_without_fake_shifts() -> TestResult {
+    let mut decoder = Decoder::new();
+    for &byte in &[0xE0, 0x12, 0xE0] {
+        test_assert_eq!(decoder.advance(byte), None);
+    }
+    test_assert_eq!(decoder.advance(0x7C), Some(ScanCode { key: Key::PrintScreen, pressed: true }));
+
+    for &byte in &[0xE0, 0xF0] {
+        test_assert_eq!(decoder.advance(byte), None);
+    }
+    test_assert_eq!(decoder.advance(0x7C), Some(ScanCode { key: Key::PrintScreen, pressed: false }));
+    for &byte in &[0xE0, 0xF0, 0x12] {
+        test_assert_eq!(decoder.advance(byte), None);
+    }
+    Ok(())
+}
+
+fn pause_skipped() -> TestResult {
+    let mut decoder = Decoder::new();
+    for &byte in &[0xE1, 0x14, 0x77, 0xE1, 0xF0, 0x14, 0xF0, 0x77] {
+        test_assert_eq!(decoder.advance(byte), None);
+    }
+
+    test_assert_eq!(decoder.advance(0x05), Some(ScanCode { key: Key::F1, pressed: true }));
+    Ok(())
+}
+
+fn unknown_codes_dropped() -> TestResult {
+    let mut decoder = Decoder::new();
+    test_assert_eq!(decoder.advance(0x02), None);
+    test_assert_eq!(decoder.advance(0xE0), None);
+    test_assert_eq!(decoder.advance(0x12), None);
+    test_assert_eq!(decoder.advance(0x1C), Some(ScanCode { key: Key::A, pressed: true }));
+    Ok(())
+}
+
+fn shift_changes_chars() -> TestResult {
+    let mut keyboard = Keyboard::new();
+    test_assert_eq!(feed(&mut keyboard, &[0x16]).and_then(|event| event.char()), Some('1'));
+
+    test_assert!(feed(&mut keyboard, &[0x12]).map_or(false, |event| event.modifiers.shift()));
+    test_assert_eq!(feed(&mut keyboard, &[0x16]).and_then(|event| event.char()), Some('!'));
+    test_assert_eq!(feed(&mut keyboard, &[0x1C]).and_then(|event| event.char()), Some('A'));
+
+    feed(&mut keyboard, &[0xF0, 0x12]);
+    test_assert!(!keyboard.modifiers().shift());
+    test_assert_eq!(feed(&mut keyboard, &[0x1C]).and_then(|event| event.char()), Some('a'));
+    Ok(())
+}
+
+fn caps_lock_toggles() -> TestResult {
+    let mut keyboard = Keyboard::new();
+    feed(&mut keyboard, &[0x58, 0xF0, 0x58]);
+    test_assert!(keyboard.modifiers().caps_lock);
+
+    test_assert_eq!(feed(&mut keyboard, &[0x1C]).and_then(|event| event.char()), Some('A'));
+    // Caps lock doesn't apply to symbols, and shift inverts it for letters
+    test_assert_eq!(feed(&mut keyboard, &[0x16]).and_then(|event| event.char()), Some('1'));
+    test_assert_eq!(feed(&mut keyboard, &[0x12, 0x1C]).and_then(|event| event.char()), Some('a'));
+
+    feed(&mut keyboard, &[0xF0, 0x12, 0x58, 0xF0, 0x58]);
+    test_assert!(!keyboard.modifiers().caps_lock);
+    Ok(())
+}
+
+fn keypad_needs_num_lock() -> TestResult {
+    let mut keyboard = Keyboard::new();
+    test_assert_eq!(feed(&mut keyboard, &[0x69]).and_then(|event| event.char()), None);
+
+    feed(&mut keyboard, &[0x77, 0xF0, 0x77]);
+    test_assert_eq!(feed(&mut keyboard, &[0x69]).and_then(|event| event.char()), Some('1'));
+    Ok(())
+}
@@ -1,3 +1,13 @@
-#[macro_use]
 pub mod vga;
+pub mod framebuffer;
+pub mod bga;
+pub mod pci;
 pub mod ps2;
+pub mod keyboard;
+pub mod serial;
+pub mod debugcon;
+pub mod pit;
+pub mod pic;
+pub mod rtc;
+pub mod mouse;
+pub mod input;
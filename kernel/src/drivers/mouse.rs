@@ -0,0 +1,197 @@
+//! Polled driver for a PS/2 mouse on the second port, decoding its standard 3 byte packets
+
+use spin::Mutex;
+use drivers::ps2::{self, io, DEVICE_AVAILABLE_FLAG, PS2};
+use drivers::ps2::io::DeviceCommand;
+
+const LEFT_BUTTON_BIT: u8 = 1 << 0;
+const RIGHT_BUTTON_BIT: u8 = 1 << 1;
+const MIDDLE_BUTTON_BIT: u8 = 1 << 2;
+/// Always set in the first byte of a packet, which is used to find the start of one
+const ALWAYS_SET_BIT: u8 = 1 << 3;
+const X_SIGN_BIT: u8 = 1 << 4;
+const Y_SIGN_BIT: u8 = 1 << 5;
+const X_OVERFLOW_BIT: u8 = 1 << 6;
+const Y_OVERFLOW_BIT: u8 = 1 << 7;
+
+const PACKET_SIZE: usize = 3;
+
+pub static MOUSE: Mutex<Mouse> = Mutex::new(Mouse::new());
+
+/// Turns on reporting from the mouse on the second port, if there is one. Returns whether the
+/// mouse is reporting.
+pub fn init() -> bool {
+    let mut controller = PS2.lock();
+    let device = &mut controller.devices[1];
+    if !device.get_flag(DEVICE_AVAILABLE_FLAG) {
+        return false;
+    }
+
+    // Drop what's left of the reply to the reset
+    io::flush_output();
+
+    let reporting = ps2::is_ok(device.command(DeviceCommand::SetDefaults)) &&
+        ps2::is_ok(device.command(DeviceCommand::EnableScanning));
+    if reporting {
+        debug!("mouse", "enabled reporting");
+    } else {
+        warn!("mouse", "failed to enable reporting");
+    }
+
+    reporting
+}
+
+/// Represents the mouse buttons which are held
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Buttons {
+    pub left: bool,
+    pub right: bool,
+    pub middle: bool,
+}
+
+impl Buttons {
+    pub const fn new() -> Self {
+        Buttons {
+            left: false,
+            right: false,
+            middle: false,
+        }
+    }
+}
+
+/// Represents the mouse moving or its buttons changing. Movement is in the mouse's counts, with
+/// `dy` going up.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct MouseEvent {
+    pub dx: i16,
+    pub dy: i16,
+    pub buttons: Buttons,
+}
+
+/// Assembles packets from the bytes the mouse sends
+pub struct PacketDecoder {
+    bytes: [u8; PACKET_SIZE],
+    len: usize,
+}
+
+impl PacketDecoder {
+    pub const fn new() -> Self {
+        PacketDecoder {
+            bytes: [0; PACKET_SIZE],
+            len: 0,
+        }
+    }
+
+    /// Handles a byte, returning the event of the packet it completes if any. Bytes which can't
+    /// start a packet are dropped, so that the decoder gets back in step after a lost byte.
+    pub fn advance(&mut self, byte: u8) -> Option<MouseEvent> {
+        if self.len == 0 && byte & ALWAYS_SET_BIT == 0 {
+            return None;
+        }
+
+        self.bytes[self.len] = byte;
+        self.len += 1;
+        if self.len < PACKET_SIZE {
+            return None;
+        }
+
+        self.len = 0;
+        Some(decode(self.bytes))
+    }
+}
+
+/// Decodes a packet. Movement which overflowed is dropped, as its size isn't known.
+fn decode(packet: [u8; PACKET_SIZE]) -> MouseEvent {
+    let flags = packet[0];
+    let movement = |value: u8, sign_bit: u8, overflow_bit: u8| if flags & overflow_bit != 0 {
+        0
+    } else if flags & sign_bit != 0 {
+        value as i16 - 0x100
+    } else {
+        value as i16
+    };
+
+    MouseEvent {
+        dx: movement(packet[1], X_SIGN_BIT, X_OVERFLOW_BIT),
+        dy: movement(packet[2], Y_SIGN_BIT, Y_OVERFLOW_BIT),
+        buttons: Buttons {
+            left: flags & LEFT_BUTTON_BIT != 0,
+            right: flags & RIGHT_BUTTON_BIT != 0,
+            middle: flags & MIDDLE_BUTTON_BIT != 0,
+        },
+    }
+}
+
+/// Represents the mouse
+pub struct Mouse {
+    decoder: PacketDecoder,
+}
+
+impl Mouse {
+    const fn new() -> Self {
+        Mouse {
+            decoder: PacketDecoder::new(),
+        }
+    }
+
+    /// Handles a byte from the second port, returning the mouse event it completes if any
+    pub fn handle_byte(&mut self, byte: u8) -> Option<MouseEvent> {
+        self.decoder.advance(byte)
+    }
+}
+
+#[cfg(feature = "kernel-test")]
+pub mod tests {
+    use testing::{TestCase, TestResult};
+    use super::*;
+
+    pub const TESTS: &'static [TestCase] = &[
+        test_case!(decode_movement_and_buttons),
+        test_case!(decode_negative_movement),
+        test_case!(drop_overflowed_movement),
+        test_case!(resync_after_lost_byte),
+    ];
+
+    fn decode_all(decoder: &mut PacketDecoder, bytes: &[u8]) -> Option<MouseEvent> {
+        bytes.iter().fold(None, |_, &byte| decoder.advance(byte))
+    }
+
+    fn decode_movement_and_buttons() -> TestResult {
+        let mut decoder = PacketDecoder::new();
+
+        test_assert_eq!(decoder.advance(ALWAYS_SET_BIT | LEFT_BUTTON_BIT | MIDDLE_BUTTON_BIT), None);
+        test_assert_eq!(decoder.advance(5), None);
+        test_assert_eq!(decoder.advance(7), Some(MouseEvent {
+            dx: 5,
+            dy: 7,
+            buttons: Buttons { left: true, right: false, middle: true },
+        }));
+        Ok(())
+    }
+
+    fn decode_negative_movement() -> TestResult {
+        let mut decoder = PacketDecoder::new();
+        let event = decode_all(&mut decoder, &[ALWAYS_SET_BIT | X_SIGN_BIT | Y_SIGN_BIT, 0xFE, 0x80]);
+
+        test_assert_eq!(event.map(|event| (event.dx, event.dy)), Some((-2, -128)));
+        Ok(())
+    }
+
+    fn drop_overflowed_movement() -> TestResult {
+        let mut decoder = PacketDecoder::new();
+        let event = decode_all(&mut decoder, &[ALWAYS_SET_BIT | X_OVERFLOW_BIT | RIGHT_BUTTON_BIT, 0xFF, 3]);
+
+        test_assert_eq!(event.map(|event| (event.dx, event.dy)), Some((0, 3)));
+        test_assert_eq!(event.map(|event| event.buttons.right), Some(true));
+        Ok(())
+    }
+
+    fn resync_after_lost_byte() -> TestResult {
+        let mut decoder = PacketDecoder::new();
+
+        // A packet's movement bytes without its first byte are dropped
+        test_assert_eq!(decode_all(&mut decoder, &[0x01, 0x02]), None);
+        test_assert_eq!(decode_all(&mut decoder, &[ALWAYS_SET_BIT, 1, 2]).map(|event| event.dx), Some(1));
+        Ok(())
+    }
+}
@@ -0,0 +1,111 @@
+//! Reading the PCI configuration space through the legacy configuration ports
+
+use io::IOPort;
+
+/// Selects the bus, device, function and register to be accessed through the data port
+static CONFIG_ADDRESS_PORT: IOPort<u32> = IOPort::new(0xCF8);
+static CONFIG_DATA_PORT: IOPort<u32> = IOPort::new(0xCFC);
+
+const ENABLE_BIT: u32 = 1 << 31;
+
+/// Vendor ID read back for a function which isn't there
+const NO_VENDOR: u16 = 0xFFFF;
+
+const VENDOR_OFFSET: u8 = 0x00;
+const DEVICE_OFFSET: u8 = 0x02;
+const HEADER_TYPE_OFFSET: u8 = 0x0E;
+const BAR_OFFSET: u8 = 0x10;
+
+/// Header type bit set by devices with more than one function
+const MULTI_FUNCTION_BIT: u8 = 1 << 7;
+
+/// BAR bit set for I/O space rather than memory
+const BAR_IO_BIT: u32 = 1 << 0;
+/// BAR type bits for a 64 bit memory address, continued in the next BAR
+const BAR_64_BIT: u32 = 0b10 << 1;
+
+/// Represents a function of a PCI device
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct PciFunction {
+    pub bus: u8,
+    pub device: u8,
+    pub function: u8,
+}
+
+impl PciFunction {
+    pub fn new(bus: u8, device: u8, function: u8) -> Self {
+        PciFunction {
+            bus: bus,
+            device: device,
+            function: function,
+        }
+    }
+
+    /// Reads the aligned double word containing the given offset
+    pub fn read_u32(&self, offset: u8) -> u32 {
+        CONFIG_ADDRESS_PORT.write(ENABLE_BIT | (self.bus as u32) << 16 | (self.device as u32) << 11 |
+                                  (self.function as u32) << 8 | (offset & 0xFC) as u32);
+        CONFIG_DATA_PORT.read()
+    }
+
+    pub fn read_u16(&self, offset: u8) -> u16 {
+        (self.read_u32(offset) >> ((offset & 2) * 8)) as u16
+    }
+
+    pub fn read_u8(&self, offset: u8) -> u8 {
+        (self.read_u32(offset) >> ((offset & 3) * 8)) as u8
+    }
+
+    pub fn vendor_id(&self) -> u16 {
+        self.read_u16(VENDOR_OFFSET)
+    }
+
+    pub fn device_id(&self) -> u16 {
+        self.read_u16(DEVICE_OFFSET)
+    }
+
+    /// Checks if a function is at this address
+    pub fn exists(&self) -> bool {
+        self.vendor_id() != NO_VENDOR
+    }
+
+    /// Gets the memory address a base address register maps, or `None` if it's unused or for I/O
+    /// space
+    pub fn memory_bar(&self, index: u8) -> Option<u64> {
+        let bar = self.read_u32(BAR_OFFSET + index * 4);
+        if bar & BAR_IO_BIT != 0 {
+            return None;
+        }
+
+        let mut address = (bar & !0xF) as u64;
+        if bar & (0b11 << 1) == BAR_64_BIT {
+            address |= (self.read_u32(BAR_OFFSET + (index + 1) * 4) as u64) << 32;
+        }
+
+        if address == 0 { None } else { Some(address) }
+    }
+}
+
+/// Finds the first function with the given vendor and device ID, checking every bus
+pub fn find(vendor_id: u16, device_id: u16) -> Option<PciFunction> {
+    for bus in 0..256 {
+        for device in 0..32 {
+            let first = PciFunction::new(bus as u8, device, 0);
+            if !first.exists() {
+                continue;
+            }
+
+            let functions = if first.read_u8(HEADER_TYPE_OFFSET) & MULTI_FUNCTION_BIT != 0 { 8 } else { 1 };
+            for function in 0..functions {
+                let function = PciFunction::new(bus as u8, device, function);
+                if function.vendor_id() == vendor_id && function.device_id() == device_id {
+                    trace!("pci", "found {:04x}:{:04x} at {:02x}:{:02x}.{}", vendor_id, device_id, function.bus,
+                           function.device, function.function);
+                    return Some(function);
+                }
+            }
+        }
+    }
+
+    None
+}
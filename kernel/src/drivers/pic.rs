@@ -0,0 +1,97 @@
+//! The two chained 8259 programmable interrupt controllers, remapped so that their IRQs come after
+//! the CPU exceptions
+
+use io::IOPort;
+
+static MASTER_COMMAND_PORT: IOPort = IOPort::new(0x20);
+static MASTER_DATA_PORT: IOPort = IOPort::new(0x21);
+static SLAVE_COMMAND_PORT: IOPort = IOPort::new(0xA0);
+static SLAVE_DATA_PORT: IOPort = IOPort::new(0xA1);
+
+/// Unused port written to for a short delay between commands, for old controllers
+static WAIT_PORT: IOPort = IOPort::new(0x80);
+
+/// Interrupt vector of IRQ 0, with IRQs 0 to 7 on the master and 8 to 15 on the slave
+pub const IRQ_OFFSET: u8 = 32;
+
+pub const TIMER_IRQ: u8 = 0;
+/// IRQ the slave is chained to on the master
+const CASCADE_IRQ: u8 = 2;
+/// IRQs which a controller raises without a real interrupt, such as when one is masked as it
+/// comes in
+pub const MASTER_SPURIOUS_IRQ: u8 = 7;
+pub const SLAVE_SPURIOUS_IRQ: u8 = 15;
+
+/// Starts initialization, with a fourth initialization word to come
+const INIT_COMMAND: u8 = 0x11;
+const MODE_8086: u8 = 0x01;
+const END_OF_INTERRUPT_COMMAND: u8 = 0x20;
+/// Reads the in-service register on the next read of the command port
+const READ_IN_SERVICE_COMMAND: u8 = 0x0B;
+
+fn wait() {
+    WAIT_PORT.write(0);
+}
+
+/// Remaps the IRQs to start at `IRQ_OFFSET`, with every IRQ masked
+pub fn init() {
+    let words = [
+        (INIT_COMMAND, INIT_COMMAND),
+        (IRQ_OFFSET, IRQ_OFFSET + 8),
+        // The master is told which IRQ has the slave, and the slave its cascade identity
+        (1 << CASCADE_IRQ, CASCADE_IRQ),
+        (MODE_8086, MODE_8086),
+    ];
+
+    for (index, &(master, slave)) in words.iter().enumerate() {
+        let (master_port, slave_port) = if index == 0 {
+            (&MASTER_COMMAND_PORT, &SLAVE_COMMAND_PORT)
+        } else {
+            (&MASTER_DATA_PORT, &SLAVE_DATA_PORT)
+        };
+
+        master_port.write(master);
+        wait();
+        slave_port.write(slave);
+        wait();
+    }
+
+    // Every IRQ but the cascade is masked until it has a handler
+    MASTER_DATA_PORT.write(!(1 << CASCADE_IRQ));
+    SLAVE_DATA_PORT.write(0xFF);
+}
+
+/// Lets an IRQ through
+pub fn unmask(irq: u8) {
+    let (port, bit) = data_port(irq);
+    port.write(port.read() & !bit);
+}
+
+/// Gets the data port of the controller handling an IRQ, and the IRQ's bit in its mask
+fn data_port(irq: u8) -> (&'static IOPort, u8) {
+    if irq < 8 {
+        (&MASTER_DATA_PORT, 1 << irq)
+    } else {
+        (&SLAVE_DATA_PORT, 1 << (irq - 8))
+    }
+}
+
+/// Tells the controllers that an IRQ has been handled, so that they can raise the next one
+pub fn end_of_interrupt(irq: u8) {
+    if irq >= 8 {
+        SLAVE_COMMAND_PORT.write(END_OF_INTERRUPT_COMMAND);
+    }
+    MASTER_COMMAND_PORT.write(END_OF_INTERRUPT_COMMAND);
+}
+
+/// Checks if an IRQ is really being serviced, as spurious IRQs aren't marked in service
+pub fn in_service(irq: u8) -> bool {
+    let (port, bit) = if irq < 8 {
+        (&MASTER_COMMAND_PORT, 1 << irq)
+    } else {
+        (&SLAVE_COMMAND_PORT, 1 << (irq - 8))
+    };
+
+    port.write(READ_IN_SERVICE_COMMAND);
+    port.read() & bit != 0
+}
@@ -0,0 +1,100 @@
+use io::IOPort;
+use core::cmp;
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use time;
+
+/// Frequency of the PIT's input clock in Hz
+pub const FREQUENCY: u32 = 1_193_182;
+
+static CHANNEL_0_PORT: IOPort = IOPort::new(0x40);
+static CHANNEL_2_PORT: IOPort = IOPort::new(0x42);
+static COMMAND_PORT: IOPort = IOPort::new(0x43);
+
+/// Controls the channel 2 gate and PC speaker, and reads back channel 2's output
+static SPEAKER_PORT: IOPort = IOPort::new(0x61);
+
+const GATE_BIT: u8 = 1 << 0;
+const SPEAKER_BIT: u8 = 1 << 1;
+const OUTPUT_BIT: u8 = 1 << 5;
+
+/// Pitch of the terminal bell in Hz
+const BELL_FREQUENCY: u32 = 880;
+
+/// Length of the terminal bell in microseconds
+const BELL_MICROS: u64 = 100_000;
+
+/// Whether channel 0 raises the timer interrupt, which stops the bell
+static TIMER_STARTED: AtomicBool = AtomicBool::new(false);
+
+/// Uptime in microseconds at which the ringing bell is stopped, or 0 if it isn't ringing
+static BELL_END: AtomicUsize = AtomicUsize::new(0);
+
+/// Busy waits for the given amount of microseconds using channel 2. Waits longer than the
+/// maximum count (about 55ms) are cut short.
+pub fn wait_micros(micros: u32) {
+    let count = cmp::min(FREQUENCY as u64 * micros as u64 / 1_000_000, 0xFFFF) as u16;
+
+    // Stop channel 2, keeping the speaker disconnected
+    let control = SPEAKER_PORT.read() & !(GATE_BIT | SPEAKER_BIT);
+    SPEAKER_PORT.write(control);
+
+    // Channel 2, low then high byte, mode 0 (interrupt on terminal count), binary
+    COMMAND_PORT.write(0b1011_0000);
+    CHANNEL_2_PORT.write(count as u8);
+    CHANNEL_2_PORT.write((count >> 8) as u8);
+
+    // Start counting, and wait for the output to go high at the end of the count
+    SPEAKER_PORT.write(control | GATE_BIT);
+    while SPEAKER_PORT.read() & OUTPUT_BIT == 0 {}
+}
+
+/// Makes channel 0 raise IRQ 0 at the given frequency in Hz, as the timer interrupt
+pub fn start_timer(frequency: u32) {
+    let divisor = cmp::min(FREQUENCY / cmp::max(frequency, 1), 0xFFFF) as u16;
+
+    // Channel 0, low then high byte, mode 2 (rate generator), binary
+    COMMAND_PORT.write(0b0011_0100);
+    CHANNEL_0_PORT.write(divisor as u8);
+    CHANNEL_0_PORT.write((divisor >> 8) as u8);
+
+    TIMER_STARTED.store(true, Ordering::SeqCst);
+}
+
+/// Plays a square wave of the given frequency in Hz on the PC speaker, until `stop_tone`
+pub fn start_tone(frequency: u32) {
+    let divisor = cmp::min(FREQUENCY / cmp::max(frequency, 1), 0xFFFF) as u16;
+
+    // Channel 2, low then high byte, mode 3 (square wave), binary
+    COMMAND_PORT.write(0b1011_0110);
+    CHANNEL_2_PORT.write(divisor as u8);
+    CHANNEL_2_PORT.write((divisor >> 8) as u8);
+
+    SPEAKER_PORT.write(SPEAKER_PORT.read() | GATE_BIT | SPEAKER_BIT);
+}
+
+/// Silences the PC speaker
+pub fn stop_tone() {
+    SPEAKER_PORT.write(SPEAKER_PORT.read() & !(GATE_BIT | SPEAKER_BIT));
+}
+
+/// Sounds the terminal bell on the PC speaker without waiting for it, as it's called while the
+/// terminals are held. The tone is stopped from the timer interrupt once it has played long enough,
+/// so the bell is silent until the timer is started.
+pub fn beep() {
+    if !TIMER_STARTED.load(Ordering::SeqCst) {
+        return;
+    }
+
+    // Set before starting the tone, so that the timer can't stop it early
+    BELL_END.store((time::uptime_micros() + BELL_MICROS) as usize, Ordering::SeqCst);
+    start_tone(BELL_FREQUENCY);
+}
+
+/// Stops the bell once it has played long enough. Called from the timer interrupt.
+pub fn on_timer() {
+    let end = BELL_END.load(Ordering::SeqCst);
+    if end != 0 && time::uptime_micros() >= end as u64 {
+        stop_tone();
+        BELL_END.store(0, Ordering::SeqCst);
+    }
+}
@@ -8,6 +8,7 @@ pub const WAIT_TIMEOUT: u16 = 1000;
 
 const OUTPUT_STATUS_BIT: u8 = 1 << 0;
 const INPUT_STATUS_BIT: u8 = 1 << 1;
+const SECOND_PORT_STATUS_BIT: u8 = 1 << 5;
 
 /// Sends a controller command without a return
 pub fn command(cmd: ControllerCommand) {
@@ -41,6 +42,17 @@ pub fn read_data() -> Option<u8> {
     read(&DATA_PORT)
 }
 
+/// Reads from the data port if a byte is waiting, without waiting for one. Returns the byte and
+/// whether it came from the second device.
+pub fn poll_data() -> Option<(u8, bool)> {
+    let status = STATUS_PORT.read();
+    if status & OUTPUT_STATUS_BIT != 0 {
+        Some((DATA_PORT.read(), status & SECOND_PORT_STATUS_BIT != 0))
+    } else {
+        None
+    }
+}
+
 /// Writes to the given port, or waits until available
 pub fn write(port: &IOPort, value: u8) {
     wait_write();
@@ -124,6 +136,7 @@ pub enum ControllerReturnCommand {
 #[derive(Copy, Clone)]
 #[repr(u8)]
 pub enum DeviceCommand {
+    SetLeds = 0xED,
     EnableScanning = 0xF4,
     DisableScanning = 0xF5,
     SetDefaults = 0xF6,
@@ -37,21 +37,21 @@ impl Ps2Controller {
 
     /// Initializes this PS2 controller
     pub fn initialize(&mut self) {
-        println!("ps2c: initializing");
+        info!("ps2c", "initializing");
 
         for device in self.devices.iter_mut() {
             device.disable();
             device.set_flag(DEVICE_AVAILABLE_FLAG, false);
         }
 
-        println!("ps2c: disabled devices");
+        debug!("ps2c", "disabled devices");
 
         flush_output();
 
         self.initialize_config();
 
         if !self.test_controller() {
-            println!("ps2c: controller test failed");
+            error!("ps2c", "controller test failed");
         }
 
         // Test the first device
@@ -68,7 +68,7 @@ impl Ps2Controller {
         if self.config.get(ControllerConfigBit::PortOutputFull2) {
             self.devices[1].test();
         } else {
-            println!("ps2c: second device unsupported");
+            warn!("ps2c", "second device unsupported");
         }
 
         let mut available_count: u8 = 0;
@@ -83,9 +83,9 @@ impl Ps2Controller {
 
         // Check if any devices are available
         if available_count > 0 {
-            println!("ps2c: enabled devices");
+            info!("ps2c", "enabled devices");
         } else {
-            println!("ps2c: detected no available devices");
+            warn!("ps2c", "detected no available devices");
         }
 
         flush_output();
@@ -106,7 +106,7 @@ impl Ps2Controller {
         // Write the updated config back to the controller
         self.write_config();
 
-        println!("ps2c: initialized config");
+        debug!("ps2c", "initialized config");
     }
 
     /// Tests this controller
@@ -126,7 +126,6 @@ impl Ps2Controller {
 }
 
 /// Returns if the given code is OK
-#[allow(dead_code)] // To be used by keyboard / mouse driver depending on PS/2
 pub fn is_ok(code: Option<u8>) -> bool {
     code == Some(ACK) || code == Some(SELF_TEST_PASSED)
 }
@@ -252,3 +251,34 @@ pub enum ControllerConfigBit {
     PortOutputFull2 = 1 << 5,
     PortTranslation1 = 1 << 6,
 }
+
+#[cfg(feature = "kernel-test")]
+pub mod tests {
+    use testing::{TestCase, TestResult};
+    use super::*;
+
+    pub const TESTS: &'static [TestCase] = &[
+        test_case!(initialize),
+        test_case!(config_written),
+    ];
+
+    fn initialize() -> TestResult {
+        let mut controller = PS2.lock();
+        controller.initialize();
+
+        test_assert!(controller.initialized);
+        // QEMU always emulates a keyboard on the first port
+        test_assert!(controller.devices[0].get_flag(DEVICE_AVAILABLE_FLAG));
+        Ok(())
+    }
+
+    fn config_written() -> TestResult {
+        let mut controller = PS2.lock();
+        controller.read_config();
+
+        test_assert!(!controller.config.get(ControllerConfigBit::PortInterrupt1));
+        test_assert!(!controller.config.get(ControllerConfigBit::PortInterrupt2));
+        test_assert!(!controller.config.get(ControllerConfigBit::PortTranslation1));
+        Ok(())
+    }
+}
@@ -0,0 +1,224 @@
+//! The CMOS real time clock, which keeps the wall clock time while the machine is off
+
+use core::fmt;
+use io::IOPort;
+
+static INDEX_PORT: IOPort = IOPort::new(0x70);
+static DATA_PORT: IOPort = IOPort::new(0x71);
+
+const SECONDS_REGISTER: u8 = 0x00;
+const MINUTES_REGISTER: u8 = 0x02;
+const HOURS_REGISTER: u8 = 0x04;
+const DAY_REGISTER: u8 = 0x07;
+const MONTH_REGISTER: u8 = 0x08;
+const YEAR_REGISTER: u8 = 0x09;
+const STATUS_A_REGISTER: u8 = 0x0A;
+const STATUS_B_REGISTER: u8 = 0x0B;
+
+/// Set in status A while the clock is updating, when the time registers may be inconsistent
+const UPDATE_IN_PROGRESS_BIT: u8 = 1 << 7;
+/// Set in status B if the time is binary rather than BCD
+const BINARY_BIT: u8 = 1 << 2;
+/// Set in status B if hours go up to 23 rather than 12
+const HOURS_24_BIT: u8 = 1 << 1;
+/// Set in the hours of a 12 hour clock after noon
+const PM_BIT: u8 = 1 << 7;
+
+/// The clock only keeps the last two digits of the year
+const CENTURY: u16 = 2000;
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Represents a date and time read from the clock
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct DateTime {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+}
+
+impl DateTime {
+    /// Gets the date and time a number of seconds later
+    pub fn add_seconds(&self, seconds: u64) -> DateTime {
+        let total = self.hour as u64 * 3600 + self.minute as u64 * 60 + self.second as u64 + seconds;
+        let mut later = DateTime {
+            hour: (total % SECONDS_PER_DAY / 3600) as u8,
+            minute: (total % 3600 / 60) as u8,
+            second: (total % 60) as u8,
+            ..*self
+        };
+
+        for _ in 0..total / SECONDS_PER_DAY {
+            if later.day < days_in_month(later.year, later.month) {
+                later.day += 1;
+            } else if later.month < 12 {
+                later.day = 1;
+                later.month += 1;
+            } else {
+                later.day = 1;
+                later.month = 1;
+                later.year += 1;
+            }
+        }
+
+        later
+    }
+}
+
+/// Gets the amount of days in a month of a year, counting months from 1
+fn days_in_month(year: u16, month: u8) -> u8 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Formats as `YYYY-MM-DD HH:MM:SS`
+impl fmt::Display for DateTime {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f, "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+            self.year, self.month, self.day, self.hour, self.minute, self.second
+        )
+    }
+}
+
+/// The time registers as read, in the format set in status B
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+struct RawTime {
+    year: u8,
+    month: u8,
+    day: u8,
+    hours: u8,
+    minutes: u8,
+    seconds: u8,
+}
+
+fn read_register(register: u8) -> u8 {
+    INDEX_PORT.write(register);
+    DATA_PORT.read()
+}
+
+/// Reads the time registers once the clock isn't updating
+fn read_raw() -> RawTime {
+    while read_register(STATUS_A_REGISTER) & UPDATE_IN_PROGRESS_BIT != 0 {}
+
+    RawTime {
+        year: read_register(YEAR_REGISTER),
+        month: read_register(MONTH_REGISTER),
+        day: read_register(DAY_REGISTER),
+        hours: read_register(HOURS_REGISTER),
+        minutes: read_register(MINUTES_REGISTER),
+        seconds: read_register(SECONDS_REGISTER),
+    }
+}
+
+/// Reads the current date and time. The registers are read until they're the same twice in a
+/// row, as an update may start while reading them.
+pub fn read() -> DateTime {
+    let mut time = read_raw();
+    loop {
+        let again = read_raw();
+        if again == time {
+            break;
+        }
+        time = again;
+    }
+
+    decode(time, read_register(STATUS_B_REGISTER))
+}
+
+/// Converts a BCD byte to binary
+fn from_bcd(value: u8) -> u8 {
+    (value >> 4) * 10 + (value & 0x0F)
+}
+
+/// Converts raw time registers to a date and time, in the format given by status B
+fn decode(raw: RawTime, status_b: u8) -> DateTime {
+    let binary = status_b & BINARY_BIT != 0;
+    let convert = |value: u8| if binary { value } else { from_bcd(value) };
+
+    let pm = raw.hours & PM_BIT != 0;
+    let mut hour = convert(raw.hours & !PM_BIT);
+    if status_b & HOURS_24_BIT == 0 {
+        // Midnight and noon are 12
+        hour = hour % 12 + if pm { 12 } else { 0 };
+    }
+
+    DateTime {
+        year: CENTURY + convert(raw.year) as u16,
+        month: convert(raw.month),
+        day: convert(raw.day),
+        hour: hour,
+        minute: convert(raw.minutes),
+        second: convert(raw.seconds),
+    }
+}
+
+#[cfg(feature = "kernel-test")]
+pub mod tests {
+    use testing::{TestCase, TestResult};
+    use super::*;
+
+    pub const TESTS: &'static [TestCase] = &[
+        test_case!(decode_bcd_and_binary),
+        test_case!(decode_12_hour_clock),
+        test_case!(add_seconds_across_days),
+        test_case!(read_valid_time),
+    ];
+
+    const RAW: RawTime = RawTime { year: 0x26, month: 0x10, day: 0x18, hours: 0x21, minutes: 0x05, seconds: 0x59 };
+
+    fn decode_bcd_and_binary() -> TestResult {
+        let expected = DateTime { year: 2026, month: 10, day: 18, hour: 21, minute: 5, second: 59 };
+        test_assert_eq!(decode(RAW, HOURS_24_BIT), expected);
+
+        let binary = RawTime { year: 26, month: 10, day: 18, hours: 21, minutes: 5, seconds: 59 };
+        test_assert_eq!(decode(binary, HOURS_24_BIT | BINARY_BIT), expected);
+        Ok(())
+    }
+
+    fn decode_12_hour_clock() -> TestResult {
+        let hour = |hours: u8| decode(RawTime { hours: hours, ..RAW }, 0).hour;
+        test_assert_eq!(hour(0x12), 0);
+        test_assert_eq!(hour(0x12 | PM_BIT), 12);
+        test_assert_eq!(hour(0x09 | PM_BIT), 21);
+        test_assert_eq!(hour(0x09), 9);
+        Ok(())
+    }
+
+    fn add_seconds_across_days() -> TestResult {
+        let time = DateTime { year: 2024, month: 2, day: 28, hour: 23, minute: 59, second: 30 };
+        test_assert_eq!(time.add_seconds(0), time);
+        test_assert_eq!(time.add_seconds(45), DateTime { day: 29, hour: 0, minute: 0, second: 15, ..time });
+        test_assert_eq!(
+            time.add_seconds(SECONDS_PER_DAY + 30),
+            DateTime { month: 3, day: 1, hour: 0, minute: 0, second: 0, ..time }
+        );
+
+        let new_year = DateTime { year: 2025, month: 12, day: 31, hour: 12, minute: 0, second: 0 };
+        test_assert_eq!(
+            new_year.add_seconds(SECONDS_PER_DAY / 2),
+            DateTime { year: 2026, month: 1, day: 1, hour: 0, ..new_year }
+        );
+        test_assert_eq!(
+            DateTime { year: 2100, month: 2, day: 28, ..new_year }.add_seconds(SECONDS_PER_DAY),
+            DateTime { year: 2100, month: 3, day: 1, ..new_year }
+        );
+        Ok(())
+    }
+
+    fn read_valid_time() -> TestResult {
+        let time = read();
+        test_assert!(time.year >= 2000);
+        test_assert!(time.month >= 1 && time.month <= 12);
+        test_assert!(time.day >= 1 && time.day <= 31);
+        test_assert!(time.hour < 24 && time.minute < 60 && time.second < 60);
+        Ok(())
+    }
+}
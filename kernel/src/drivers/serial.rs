@@ -0,0 +1,80 @@
+use core::fmt;
+use io::IOPort;
+use console::Console;
+use spin::Mutex;
+
+/// Line status bit set when the transmitter holding register is empty
+const TRANSMIT_EMPTY_BIT: u8 = 1 << 5;
+
+/// Baud rate divisor for 38400 baud
+const BAUD_DIVISOR: u8 = 3;
+
+pub static COM1: Mutex<SerialPort> = Mutex::new(SerialPort::new(0x3F8));
+
+/// Represents a 16550 UART serial port
+pub struct SerialPort {
+    data: IOPort,
+    interrupt_enable: IOPort,
+    fifo_control: IOPort,
+    line_control: IOPort,
+    modem_control: IOPort,
+    line_status: IOPort,
+}
+
+impl SerialPort {
+    const fn new(base: u16) -> Self {
+        SerialPort {
+            data: IOPort::new(base),
+            interrupt_enable: IOPort::new(base + 1),
+            fifo_control: IOPort::new(base + 2),
+            line_control: IOPort::new(base + 3),
+            modem_control: IOPort::new(base + 4),
+            line_status: IOPort::new(base + 5),
+        }
+    }
+
+    /// Initializes this serial port to 38400 baud, 8 data bits, no parity and 1 stop bit
+    pub fn initialize(&mut self) {
+        // Disable interrupts
+        self.interrupt_enable.write(0x00);
+
+        // Set the baud rate divisor with the DLAB bit set
+        self.line_control.write(0x80);
+        self.data.write(BAUD_DIVISOR);
+        self.interrupt_enable.write(0x00);
+
+        // 8 bits, no parity, 1 stop bit, DLAB cleared
+        self.line_control.write(0x03);
+
+        // Enable and clear FIFOs with a 14 byte threshold
+        self.fifo_control.write(0xC7);
+
+        // Set DTR, RTS and OUT2
+        self.modem_control.write(0x0B);
+    }
+
+    /// Writes a byte, waiting until the transmitter is ready
+    pub fn write_byte(&mut self, value: u8) {
+        while self.line_status.read() & TRANSMIT_EMPTY_BIT == 0 {}
+        self.data.write(value);
+    }
+}
+
+impl Console for SerialPort {
+    fn write(&mut self, str: &str) {
+        for byte in str.bytes() {
+            // Terminals expect CRLF line endings
+            if byte == b'\n' {
+                self.write_byte(b'\r');
+            }
+            self.write_byte(byte);
+        }
+    }
+}
+
+impl fmt::Write for SerialPort {
+    fn write_str(&mut self, str: &str) -> fmt::Result {
+        self.write(str);
+        Ok(())
+    }
+}
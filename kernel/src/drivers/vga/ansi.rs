@@ -0,0 +1,140 @@
+//! Parser for ANSI/VT100 escape sequences
+
+const ESCAPE: char = '\x1b';
+
+/// Maximum amount of parameters kept for a control sequence. Further parameters are merged into
+/// the last.
+const MAX_PARAMS: usize = 8;
+
+/// Represents something to do for the input given to an `AnsiParser`
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Action {
+    /// Print a char, or handle it if it is a control char
+    Print(char),
+    /// Handle a control sequence (`ESC [`)
+    Csi(Csi),
+    SaveCursor,
+    RestoreCursor,
+    /// Set a tab stop at the cursor's column (`ESC H`)
+    SetTabStop,
+}
+
+/// Represents a control sequence introduced by `ESC [`
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Csi {
+    params: [u16; MAX_PARAMS],
+    count: usize,
+    /// Whether this is a private sequence, marked by `?`
+    pub private: bool,
+    pub final_byte: char,
+}
+
+impl Csi {
+    /// Gets all the given parameters
+    pub fn params(&self) -> &[u16] {
+        &self.params[..self.count]
+    }
+
+    /// Gets the parameter at the given index, or the default if it is missing or 0
+    pub fn param(&self, index: usize, default: u16) -> u16 {
+        match self.params().get(index) {
+            Some(&param) if param != 0 => param,
+            _ => default,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum State {
+    Ground,
+    Escape,
+    Csi,
+}
+
+/// Turns a stream of chars into chars to print and escape sequences to handle
+pub struct AnsiParser {
+    state: State,
+    params: [u16; MAX_PARAMS],
+    count: usize,
+    private: bool,
+}
+
+impl AnsiParser {
+    pub const fn new() -> Self {
+        AnsiParser {
+            state: State::Ground,
+            params: [0; MAX_PARAMS],
+            count: 0,
+            private: false,
+        }
+    }
+
+    /// Feeds the next char, returning the action it completes if any
+    pub fn advance(&mut self, character: char) -> Option<Action> {
+        match self.state {
+            State::Ground => {
+                if character == ESCAPE {
+                    self.state = State::Escape;
+                    None
+                } else {
+                    Some(Action::Print(character))
+                }
+            }
+            State::Escape => {
+                self.state = State::Ground;
+
+                match character {
+                    '[' => {
+                        self.state = State::Csi;
+                        self.params = [0; MAX_PARAMS];
+                        self.count = 0;
+                        self.private = false;
+                        None
+                    }
+                    '7' => Some(Action::SaveCursor),
+                    '8' => Some(Action::RestoreCursor),
+                    'H' => Some(Action::SetTabStop),
+                    // Unsupported escapes are dropped
+                    _ => None,
+                }
+            }
+            State::Csi => match character {
+                '0'...'9' => {
+                    if self.count == 0 {
+                        self.count = 1;
+                    }
+
+                    let digit = character as u16 - '0' as u16;
+                    let param = &mut self.params[self.count - 1];
+                    *param = param.saturating_mul(10).saturating_add(digit);
+                    None
+                }
+                ';' => {
+                    // An empty first parameter still counts
+                    if self.count == 0 {
+                        self.count = 1;
+                    }
+                    if self.count < MAX_PARAMS {
+                        self.count += 1;
+                    }
+                    None
+                }
+                '?' => {
+                    self.private = true;
+                    None
+                }
+                '@'...'~' => {
+                    self.state = State::Ground;
+                    Some(Action::Csi(Csi {
+                        params: self.params,
+                        count: self.count,
+                        private: self.private,
+                        final_byte: character,
+                    }))
+                }
+                // Intermediate bytes aren't supported, so are ignored
+                _ => None,
+            },
+        }
+    }
+}
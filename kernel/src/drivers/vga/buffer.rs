@@ -0,0 +1,423 @@
+use volatile::Volatile;
+use core::{cmp, slice};
+use core::ptr::Unique;
+use super::{Color, VgaColor, crtc, cp437, mode};
+use super::crtc::CursorShape;
+
+/// Size of the text mode set up at boot
+pub const RESOLUTION_X: usize = 80;
+pub const RESOLUTION_Y: usize = 25;
+
+/// Largest size of any text mode, which buffers are able to hold
+pub const MAX_COLUMNS: usize = 90;
+pub const MAX_ROWS: usize = 60;
+
+/// Amount of cells in the text memory at 0xB8000
+pub const MEMORY_CELLS: usize = 0x8000 / 2;
+
+/// A blank black cell
+pub const BLANK: VgaChar = VgaChar {
+    character: b' ',
+    color: VgaColor::new(Color::Black, Color::Black),
+};
+
+/// How a buffer moves its rows up to scroll
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ScrollStrategy {
+    /// Copy every row up
+    Copy,
+    /// Move the part of video memory shown on screen down, where the hardware supports it. The
+    /// rows scrolled off stay in video memory until it wraps around. Rows fixed at the bottom,
+    /// such as the status bar's, are split off the screen so that they stay put, while terminals
+    /// with rows fixed at the top copy their rows instead.
+    Hardware,
+}
+
+/// A grid of character cells which a `VgaWriter` draws into
+pub trait TextBuffer {
+    /// Gets the amount of columns in this buffer
+    fn width(&self) -> usize;
+
+    /// Gets the amount of rows in this buffer
+    fn height(&self) -> usize;
+
+    fn set_char(&mut self, row: usize, column: usize, value: VgaChar);
+
+    fn get_char(&self, row: usize, column: usize) -> VgaChar;
+
+    /// Changes the size of this buffer, up to `MAX_COLUMNS` by `MAX_ROWS`
+    fn resize(&mut self, width: usize, height: usize);
+
+    /// Moves every row up by the given amount, clearing the rows freed at the bottom
+    fn scroll_down(&mut self, amount: usize, background_color: Color) {
+        let height = self.height();
+        self.scroll_rows(0, height, amount, background_color);
+    }
+
+    /// Moves the rows from the top row up to the exclusive bottom row up by the given amount,
+    /// clearing the rows freed at the bottom
+    fn scroll_rows(&mut self, top: usize, bottom: usize, amount: usize, background_color: Color) {
+        let amount = cmp::min(amount, bottom - top);
+
+        for row in (top + amount)..bottom {
+            for column in 0..self.width() {
+                let value = self.get_char(row, column);
+                self.set_char(row - amount, column, value);
+            }
+        }
+
+        for row in (bottom - amount)..bottom {
+            self.clear_row(row, background_color);
+        }
+    }
+
+    /// Sets how `scroll_down` moves the rows, if this buffer is able to do it more than one way
+    fn set_scroll_strategy(&mut self, _strategy: ScrollStrategy) {}
+
+    fn scroll_strategy(&self) -> ScrollStrategy {
+        ScrollStrategy::Copy
+    }
+
+    /// Moves the visible cursor to the given cell, if this buffer has one
+    fn set_cursor(&mut self, _row: usize, _column: usize) {}
+
+    /// Sets the shape of the visible cursor, if this buffer has one
+    fn set_cursor_shape(&mut self, _shape: CursorShape) {}
+
+    /// Brings the screen up to date with the changes made so far, if this buffer draws off screen
+    /// first
+    fn flush(&mut self) {}
+
+    fn clear_row(&mut self, row: usize, color: Color) {
+        let blank = VgaChar::new(
+            VgaColor::new(Color::Black, color),
+            ' '
+        );
+
+        for column in 0..self.width() {
+            self.set_char(row, column, blank);
+        }
+    }
+}
+
+/// Represents the VGA text buffer in video memory. Hardware scrolling treats the text memory as a
+/// ring of rows, moving the CRTC start address down it and copying the screen back to the start
+/// when it reaches the end. Rows may be split off the bottom of the screen with the CRTC's line
+/// compare, which shows them from the start of video memory wherever the start address is, so
+/// that they don't scroll. The ring then starts after them.
+pub struct VgaBuffer {
+    cells: Unique<Volatile<VgaChar>>,
+    width: usize,
+    height: usize,
+    /// Offset of the cell shown at the top left
+    origin: usize,
+    /// Amount of rows split off the bottom of the screen
+    split: usize,
+    /// Cell the cursor was last moved to, as `(row, column)`
+    cursor: (usize, usize),
+    strategy: ScrollStrategy,
+}
+
+impl VgaBuffer {
+    pub const fn new() -> Self {
+        VgaBuffer {
+            cells: unsafe { Unique::new_unchecked(0xb8000 as *mut _) },
+            width: RESOLUTION_X,
+            height: RESOLUTION_Y,
+            origin: 0,
+            split: 0,
+            cursor: (0, 0),
+            strategy: ScrollStrategy::Copy,
+        }
+    }
+
+    /// Creates a buffer of the cells on screen, in the current text mode and from the start address
+    /// the screen is shown from, with the rows split off its bottom if there are any
+    pub fn shown() -> Self {
+        let (width, height) = mode::mode().size();
+        let split = crtc::line_compare().map_or(0, |line| {
+            let rows = (line + 1) / crtc::char_height();
+            if rows > 0 && rows < height { height - rows } else { 0 }
+        });
+
+        VgaBuffer {
+            width: width,
+            height: height,
+            origin: cmp::max(cmp::min(crtc::start_address(), MEMORY_CELLS - width * height), split * width),
+            split: split,
+            ..VgaBuffer::new()
+        }
+    }
+
+    fn cells(&self) -> &[Volatile<VgaChar>] {
+        unsafe { slice::from_raw_parts(self.cells.as_ptr(), MEMORY_CELLS) }
+    }
+
+    fn cells_mut(&mut self) -> &mut [Volatile<VgaChar>] {
+        unsafe { slice::from_raw_parts_mut(self.cells.as_ptr(), MEMORY_CELLS) }
+    }
+
+    /// Gets the offset of the cell shown at the top left
+    #[cfg(feature = "kernel-test")]
+    pub fn origin(&self) -> usize {
+        self.origin
+    }
+
+    /// Splits the given amount of rows off the bottom of the screen, so that hardware scrolling
+    /// leaves them in place. It's set on the screen by `reset_origin`, so the screen should be
+    /// redrawn afterwards.
+    pub fn set_split(&mut self, rows: usize) {
+        self.split = cmp::min(rows, self.height - 1);
+        self.origin = self.split * self.width;
+    }
+
+    /// Shows the start of video memory again, after the rows split off the bottom, such as before
+    /// redrawing the whole screen
+    pub fn reset_origin(&mut self) {
+        let scrolled = self.height - self.split;
+        self.origin = self.split * self.width;
+        crtc::set_start_address(self.origin);
+
+        // The line compare counts from 0, so it's the last scan line above the split
+        crtc::set_line_compare(if self.split == 0 {
+            None
+        } else {
+            Some(scrolled * crtc::char_height() - 1)
+        });
+
+        let (row, column) = self.cursor;
+        self.set_cursor(row, column);
+    }
+
+    /// Gets the offset in video memory of a cell of the screen
+    fn index(&self, row: usize, column: usize) -> usize {
+        let scrolled = self.height - self.split;
+
+        if row < scrolled {
+            self.origin + row * self.width + column
+        } else {
+            (row - scrolled) * self.width + column
+        }
+    }
+
+    /// Scrolls by moving the start address down by the given amount of rows, clearing the rows
+    /// which come into view. Rows split off the bottom stay in place.
+    fn scroll_hardware(&mut self, amount: usize, background_color: Color) {
+        let scrolled = self.height - self.split;
+        let amount = cmp::min(amount, scrolled);
+        let (screen, moved) = (self.width * scrolled, amount * self.width);
+
+        // Rows mustn't run past the end of memory, as the CRTC doesn't wrap around with it
+        let (start, ring) = (self.split * self.width, MEMORY_CELLS / self.width * self.width);
+
+        if self.origin + screen + moved <= ring {
+            self.origin += moved;
+        } else {
+            let origin = self.origin;
+            let cells = self.cells_mut();
+            for index in moved..screen {
+                let value = cells[origin + index].read();
+                cells[start + index - moved].write(value);
+            }
+
+            self.origin = start;
+        }
+
+        for row in (scrolled - amount)..scrolled {
+            self.clear_row(row, background_color);
+        }
+
+        crtc::set_start_address(self.origin);
+        let (row, column) = self.cursor;
+        self.set_cursor(row, column);
+    }
+}
+
+impl TextBuffer for VgaBuffer {
+    fn width(&self) -> usize {
+        self.width
+    }
+
+    fn height(&self) -> usize {
+        self.height
+    }
+
+    fn set_char(&mut self, row: usize, column: usize, value: VgaChar) {
+        assert!(row < self.height && column < self.width, "Cell out of bounds");
+        let index = self.index(row, column);
+        self.cells_mut()[index].write(value);
+    }
+
+    fn get_char(&self, row: usize, column: usize) -> VgaChar {
+        assert!(row < self.height && column < self.width, "Cell out of bounds");
+        self.cells()[self.index(row, column)].read()
+    }
+
+    /// Changes the size to match the text mode, going back to the start of video memory. Cells
+    /// aren't moved, so the screen should be redrawn afterwards.
+    fn resize(&mut self, width: usize, height: usize) {
+        self.width = cmp::max(cmp::min(width, MAX_COLUMNS), 1);
+        self.height = cmp::max(cmp::min(height, MAX_ROWS), 1);
+        self.cursor = (cmp::min(self.cursor.0, self.height - 1), cmp::min(self.cursor.1, self.width - 1));
+        self.split = cmp::min(self.split, self.height - 1);
+
+        if self.origin != self.split * self.width {
+            self.reset_origin();
+        }
+    }
+
+    /// Moves the rows up, apart from those split off the bottom
+    fn scroll_down(&mut self, amount: usize, background_color: Color) {
+        match self.strategy {
+            ScrollStrategy::Copy => {
+                let scrolled = self.height - self.split;
+                self.scroll_rows(0, scrolled, amount, background_color);
+            }
+            ScrollStrategy::Hardware => self.scroll_hardware(amount, background_color),
+        }
+    }
+
+    fn set_scroll_strategy(&mut self, strategy: ScrollStrategy) {
+        self.strategy = strategy;
+    }
+
+    fn scroll_strategy(&self) -> ScrollStrategy {
+        self.strategy
+    }
+
+    fn set_cursor(&mut self, row: usize, column: usize) {
+        self.cursor = (row, column);
+        crtc::set_cursor_location(self.index(row, column));
+    }
+
+    fn set_cursor_shape(&mut self, shape: CursorShape) {
+        crtc::set_cursor_shape(shape);
+    }
+}
+
+/// A text buffer held in memory, such as for drawing off screen
+pub struct MemoryBuffer {
+    cells: [VgaChar; MAX_COLUMNS * MAX_ROWS],
+    width: usize,
+    height: usize,
+    cursor: (usize, usize),
+}
+
+impl MemoryBuffer {
+    pub const fn new() -> Self {
+        MemoryBuffer {
+            cells: [BLANK; MAX_COLUMNS * MAX_ROWS],
+            width: RESOLUTION_X,
+            height: RESOLUTION_Y,
+            cursor: (0, 0),
+        }
+    }
+
+    /// Gets the position that the cursor was last moved to, as `(row, column)`
+    pub fn cursor(&self) -> (usize, usize) {
+        self.cursor
+    }
+}
+
+impl TextBuffer for MemoryBuffer {
+    fn width(&self) -> usize {
+        self.width
+    }
+
+    fn height(&self) -> usize {
+        self.height
+    }
+
+    fn set_char(&mut self, row: usize, column: usize, value: VgaChar) {
+        assert!(row < self.height && column < self.width, "Cell out of bounds");
+        self.cells[row * self.width + column] = value;
+    }
+
+    fn get_char(&self, row: usize, column: usize) -> VgaChar {
+        assert!(row < self.height && column < self.width, "Cell out of bounds");
+        self.cells[row * self.width + column]
+    }
+
+    /// Changes the size, keeping the cells which still fit in place and blanking new cells
+    fn resize(&mut self, width: usize, height: usize) {
+        let width = cmp::max(cmp::min(width, MAX_COLUMNS), 1);
+        let height = cmp::max(cmp::min(height, MAX_ROWS), 1);
+        let (old_width, old_height) = (self.width, self.height);
+        let kept_rows = cmp::min(height, old_height);
+
+        let cell = |cells: &[VgaChar], row: usize, column: usize| if column < old_width {
+            cells[row * old_width + column]
+        } else {
+            BLANK
+        };
+
+        // Rows are moved in place, so when widening they are moved from the end to avoid
+        // overwriting cells which haven't moved yet
+        if width > old_width {
+            for row in (0..kept_rows).rev() {
+                for column in (0..width).rev() {
+                    let value = cell(&self.cells, row, column);
+                    self.cells[row * width + column] = value;
+                }
+            }
+        } else {
+            for row in 0..kept_rows {
+                for column in 0..width {
+                    let value = cell(&self.cells, row, column);
+                    self.cells[row * width + column] = value;
+                }
+            }
+        }
+
+        for index in (kept_rows * width)..(height * width) {
+            self.cells[index] = BLANK;
+        }
+
+        self.width = width;
+        self.height = height;
+        self.cursor = (cmp::min(self.cursor.0, height - 1), cmp::min(self.cursor.1, width - 1));
+    }
+
+    fn scroll_down(&mut self, amount: usize, background_color: Color) {
+        let amount = cmp::min(amount, self.height);
+        let cell_count = self.width * self.height;
+        self.cells[..cell_count].rotate_left(amount * self.width);
+
+        for row in (self.height - amount)..self.height {
+            self.clear_row(row, background_color);
+        }
+    }
+
+    fn scroll_rows(&mut self, top: usize, bottom: usize, amount: usize, background_color: Color) {
+        assert!(top <= bottom && bottom <= self.height, "Rows out of bounds");
+        let amount = cmp::min(amount, bottom - top);
+        self.cells[top * self.width..bottom * self.width].rotate_left(amount * self.width);
+
+        for row in (bottom - amount)..bottom {
+            self.clear_row(row, background_color);
+        }
+    }
+
+    fn set_cursor(&mut self, row: usize, column: usize) {
+        self.cursor = (row, column);
+    }
+}
+
+/// Represents a full character in the VGA buffer, with a character code, foreground and background
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(C)]
+pub struct VgaChar {
+    pub character: u8,
+    pub color: VgaColor,
+}
+
+impl VgaChar {
+    /// Creates a cell showing the given char, or the default replacement glyph if it isn't in
+    /// code page 437
+    pub fn new(color: VgaColor, character: char) -> Self {
+        VgaChar {
+            color: color,
+            character: cp437::encode(character).unwrap_or(cp437::REPLACEMENT_GLYPH),
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use core::convert::TryFrom;
+
+/// Represents a VGA colour, with both a foreground and background
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VgaColor(u8);
+
+impl VgaColor {
+    /// Creates a new VgaColor for the given foreground and background
+    pub const fn new(foreground: Color, background: Color) -> VgaColor {
+        VgaColor((background as u8) << 4 | (foreground as u8))
+    }
+
+    pub fn foreground(self) -> Color {
+        Color::try_from(self.0 & 0x0F).unwrap_or(Color::Black)
+    }
+
+    pub fn background(self) -> Color {
+        Color::try_from((self.0 & 0xF0) >> 4).unwrap_or(Color::Black)
+    }
+
+    pub fn with_foreground(self, foreground: Color) -> VgaColor {
+        VgaColor(self.0 & 0xF0 | foreground as u8)
+    }
+
+    pub fn with_background(self, background: Color) -> VgaColor {
+        VgaColor((background as u8) << 4 | self.0 & 0x0F)
+    }
+
+    /// Sets the intensity bit of the foreground, making it the bright variant
+    pub fn brightened(self) -> VgaColor {
+        VgaColor(self.0 | 0x08)
+    }
+
+    /// Clears the intensity bit of the foreground, making it the normal variant
+    pub fn dimmed(self) -> VgaColor {
+        VgaColor(self.0 & !0x08)
+    }
+
+    /// Swaps the foreground and background
+    pub fn inverted(self) -> VgaColor {
+        VgaColor(self.0 << 4 | self.0 >> 4)
+    }
+}
+
+/// Converts VgaColor to tuple of `(background, foreground)`
+impl TryFrom<VgaColor> for (Color, Color) {
+    type Error = ColorCodeOutOfBounds;
+
+    fn try_from(color: VgaColor) -> Result<Self, Self::Error> {
+        Ok((Color::try_from((color.0 & 0xF0) >> 4)?, Color::try_from(color.0 & 0x0F)?))
+    }
+}
+
+/// Represents valid VGA colors
+#[allow(dead_code)] // dead variants for completeness
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Color {
+    Black = 0,
+    Blue = 1,
+    Green = 2,
+    Cyan = 3,
+    Red = 4,
+    Magenta = 5,
+    Brown = 6,
+    LightGray = 7,
+    DarkGray = 8,
+    LightBlue = 9,
+    LightGreen = 10,
+    LightCyan = 11,
+    LightRed = 12,
+    Pink = 13,
+    Yellow = 14,
+    White = 15,
+}
+
+impl Color {
+    /// Gets the color for an ANSI color index from 0 to 7, or its bright variant
+    pub fn from_ansi(index: u8, bright: bool) -> Color {
+        use self::Color::*;
+        const NORMAL: [Color; 8] = [Black, Red, Green, Brown, Blue, Magenta, Cyan, LightGray];
+        const BRIGHT: [Color; 8] = [DarkGray, LightRed, LightGreen, Yellow, LightBlue, Pink, LightCyan, White];
+
+        let index = (index & 0x07) as usize;
+        if bright { BRIGHT[index] } else { NORMAL[index] }
+    }
+
+    /// Gets the ANSI color index from 0 to 7 for this color, and whether it's the bright variant
+    pub fn to_ansi(self) -> (u8, bool) {
+        const INDICES: [u8; 8] = [0, 4, 2, 6, 1, 5, 3, 7];
+
+        let value = self as u8;
+        (INDICES[(value & 0x07) as usize], value & 0x08 != 0)
+    }
+}
+
+/// Struct to show that the color code was out of bounds for [TryFrom] for [Color]
+pub struct ColorCodeOutOfBounds(pub u8);
+
+impl TryFrom<u8> for Color {
+    /// The only type of error is out of bounds
+    type Error = ColorCodeOutOfBounds;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        use self::Color::*;
+        match value {
+            0 => Ok(Black),
+            1 => Ok(Blue),
+            2 => Ok(Green),
+            3 => Ok(Cyan),
+            4 => Ok(Red),
+            5 => Ok(Magenta),
+            6 => Ok(Brown),
+            7 => Ok(LightGray),
+            8 => Ok(DarkGray),
+            9 => Ok(LightBlue),
+            10 => Ok(LightGreen),
+            11 => Ok(LightCyan),
+            12 => Ok(LightRed),
+            13 => Ok(Pink),
+            14 => Ok(Yellow),
+            15 => Ok(White),
+            code => Err(ColorCodeOutOfBounds(code))
+        }
+    }
+}
@@ -0,0 +1,77 @@
+//! Translation between Unicode and code page 437, the character set of the VGA text mode font
+
+/// Glyph shown for chars which have no CP437 equivalent, unless configured otherwise
+pub const REPLACEMENT_GLYPH: u8 = b'?';
+
+/// Chars drawn by the glyphs 0x01 to 0x1F. In the buffer these are glyphs, not control codes.
+const LOW_GLYPHS: [char; 31] = [
+          '☺', '☻', '♥', '♦', '♣', '♠', '•', '◘', '○', '◙', '♂', '♀', '♪', '♫', '☼',
+    '►', '◄', '↕', '‼', '¶', '§', '▬', '↨', '↑', '↓', '→', '←', '∟', '↔', '▲', '▼',
+];
+
+/// Char drawn by the glyph 0x7F
+const HOUSE: char = '⌂';
+
+/// Chars drawn by the glyphs 0x80 to 0xFF
+const HIGH_GLYPHS: [char; 128] = [
+    'Ç', 'ü', 'é', 'â', 'ä', 'à', 'å', 'ç', 'ê', 'ë', 'è', 'ï', 'î', 'ì', 'Ä', 'Å',
+    'É', 'æ', 'Æ', 'ô', 'ö', 'ò', 'û', 'ù', 'ÿ', 'Ö', 'Ü', '¢', '£', '¥', '₧', 'ƒ',
+    'á', 'í', 'ó', 'ú', 'ñ', 'Ñ', 'ª', 'º', '¿', '⌐', '¬', '½', '¼', '¡', '«', '»',
+    '░', '▒', '▓', '│', '┤', '╡', '╢', '╖', '╕', '╣', '║', '╗', '╝', '╜', '╛', '┐',
+    '└', '┴', '┬', '├', '─', '┼', '╞', '╟', '╚', '╔', '╩', '╦', '╠', '═', '╬', '╧',
+    '╨', '╤', '╥', '╙', '╘', '╒', '╓', '╫', '╪', '┘', '┌', '█', '▄', '▌', '▐', '▀',
+    'α', 'ß', 'Γ', 'π', 'Σ', 'σ', 'µ', 'τ', 'Φ', 'Θ', 'Ω', 'δ', '∞', 'φ', 'ε', '∩',
+    '≡', '±', '≥', '≤', '⌠', '⌡', '÷', '≈', '°', '∙', '·', '√', 'ⁿ', '²', '■', '\u{A0}',
+];
+
+/// Other chars which look the same as a glyph, such as Greek letters which CP437 only has as
+/// mathematical symbols
+const ALIASES: [(char, u8); 11] = [
+    ('β', 0xE1),
+    ('μ', 0xE6),
+    ('Ω', 0xEA),
+    ('ϕ', 0xED),
+    ('∅', 0xED),
+    ('∈', 0xEE),
+    ('ϵ', 0xEE),
+    ('∑', 0xE4),
+    ('∎', 0xFE),
+    ('⋅', 0xF9),
+    ('✓', 0xFB),
+];
+
+/// Gets the CP437 glyph for a char, if there is one. ASCII is kept as is, including control
+/// chars, which draw the glyphs at their code.
+pub fn encode(character: char) -> Option<u8> {
+    if (character as u32) < 0x80 {
+        return Some(character as u8);
+    }
+
+    if character == HOUSE {
+        return Some(0x7F);
+    }
+
+    if let Some(index) = HIGH_GLYPHS.iter().position(|&glyph| glyph == character) {
+        return Some(0x80 + index as u8);
+    }
+
+    if let Some(index) = LOW_GLYPHS.iter().position(|&glyph| glyph == character) {
+        return Some(0x01 + index as u8);
+    }
+
+    ALIASES.iter()
+        .find(|&&(alias, _)| alias == character)
+        .map(|&(_, glyph)| glyph)
+}
+
+/// Gets the char which a CP437 glyph draws. Glyphs below 0x20 and 0x7F give their pictures
+/// rather than control chars.
+pub fn decode(glyph: u8) -> char {
+    match glyph {
+        0x00 => ' ',
+        0x01...0x1F => LOW_GLYPHS[glyph as usize - 0x01],
+        0x7F => HOUSE,
+        0x80...0xFF => HIGH_GLYPHS[glyph as usize - 0x80],
+        _ => glyph as char,
+    }
+}
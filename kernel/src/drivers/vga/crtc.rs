@@ -0,0 +1,159 @@
+//! The CRT controller, which controls the cursor and how video memory is scanned out. Registers
+//! are accessed with interrupts disabled, since the timer interrupt moves the cursor when it
+//! draws the status bar.
+
+use core::cmp;
+use cpu;
+use io::IOPort;
+
+static INDEX_PORT: IOPort = IOPort::new(0x3D4);
+static DATA_PORT: IOPort = IOPort::new(0x3D5);
+
+/// Cursor start register bit which hides the cursor
+const CURSOR_DISABLE_BIT: u8 = 1 << 5;
+
+/// Line compare value which is never reached, so the screen isn't split
+const LINE_COMPARE_OFF: usize = 0x3FF;
+/// Bits of the overflow and maximum scan line registers holding bits 8 and 9 of the line compare
+const OVERFLOW_LINE_COMPARE_BIT: u8 = 1 << 4;
+const MAXIMUM_SCAN_LINE_LINE_COMPARE_BIT: u8 = 1 << 6;
+
+/// Represents the index of a CRT controller register
+#[derive(Copy, Clone)]
+#[repr(u8)]
+pub enum CrtcRegister {
+    Overflow = 0x07,
+    MaximumScanLine = 0x09,
+    CursorStart = 0x0A,
+    CursorEnd = 0x0B,
+    StartAddressHigh = 0x0C,
+    StartAddressLow = 0x0D,
+    CursorLocationHigh = 0x0E,
+    CursorLocationLow = 0x0F,
+    LineCompare = 0x18,
+}
+
+/// Represents the shape of the hardware text cursor
+#[allow(dead_code)] // dead variants for completeness
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CursorShape {
+    /// The bottom two scan lines of the cell
+    Underline,
+    /// The whole cell
+    Block,
+    Hidden,
+}
+
+/// Reads a CRT controller register
+pub fn read(register: CrtcRegister) -> u8 {
+    read_index(register as u8)
+}
+
+/// Writes a CRT controller register
+pub fn write(register: CrtcRegister, value: u8) {
+    write_index(register as u8, value);
+}
+
+/// Reads the CRT controller register at the given index
+pub fn read_index(index: u8) -> u8 {
+    cpu::without_interrupts(|| {
+        INDEX_PORT.write(index);
+        DATA_PORT.read()
+    })
+}
+
+/// Writes the CRT controller register at the given index
+pub fn write_index(index: u8, value: u8) {
+    cpu::without_interrupts(|| {
+        INDEX_PORT.write(index);
+        DATA_PORT.write(value);
+    });
+}
+
+/// Moves the hardware cursor to the given cell offset in video memory
+pub fn set_cursor_location(offset: usize) {
+    cpu::without_interrupts(|| {
+        write(CrtcRegister::CursorLocationLow, offset as u8);
+        write(CrtcRegister::CursorLocationHigh, (offset >> 8) as u8);
+    });
+}
+
+/// Sets the cell offset in video memory shown at the top left of the screen
+pub fn set_start_address(offset: usize) {
+    cpu::without_interrupts(|| {
+        write(CrtcRegister::StartAddressHigh, (offset >> 8) as u8);
+        write(CrtcRegister::StartAddressLow, offset as u8);
+    });
+}
+
+/// Gets the cell offset in video memory shown at the top left of the screen
+pub fn start_address() -> usize {
+    cpu::without_interrupts(|| {
+        (read(CrtcRegister::StartAddressHigh) as usize) << 8 | read(CrtcRegister::StartAddressLow) as usize
+    })
+}
+
+/// Gets the height of a character in scan lines
+pub fn char_height() -> usize {
+    (read(CrtcRegister::MaximumScanLine) & 0x1F) as usize + 1
+}
+
+/// Splits the screen after the given scan line, below which video memory is shown from its start
+/// whatever the start address is, or stops splitting it
+pub fn set_line_compare(line: Option<usize>) {
+    let line = cmp::min(line.unwrap_or(LINE_COMPARE_OFF), LINE_COMPARE_OFF);
+    let bit = |set: bool, mask: u8| if set { mask } else { 0 };
+
+    cpu::without_interrupts(|| {
+        let overflow = read(CrtcRegister::Overflow) & !OVERFLOW_LINE_COMPARE_BIT;
+        let maximum_scan_line = read(CrtcRegister::MaximumScanLine) & !MAXIMUM_SCAN_LINE_LINE_COMPARE_BIT;
+
+        write(CrtcRegister::LineCompare, line as u8);
+        write(CrtcRegister::Overflow, overflow | bit(line & 0x100 != 0, OVERFLOW_LINE_COMPARE_BIT));
+        write(
+            CrtcRegister::MaximumScanLine,
+            maximum_scan_line | bit(line & 0x200 != 0, MAXIMUM_SCAN_LINE_LINE_COMPARE_BIT),
+        );
+    });
+}
+
+/// Gets the scan line the screen is split after, or `None` if it isn't split
+pub fn line_compare() -> Option<usize> {
+    let line = cpu::without_interrupts(|| {
+        let high = |register: CrtcRegister, mask: u8, bit: usize| {
+            if read(register) & mask != 0 { 1 << bit } else { 0 }
+        };
+
+        read(CrtcRegister::LineCompare) as usize
+            | high(CrtcRegister::Overflow, OVERFLOW_LINE_COMPARE_BIT, 8)
+            | high(CrtcRegister::MaximumScanLine, MAXIMUM_SCAN_LINE_LINE_COMPARE_BIT, 9)
+    });
+
+    if line == LINE_COMPARE_OFF { None } else { Some(line) }
+}
+
+/// Sets the hardware cursor's shape, fitting it to the current character height
+pub fn set_cursor_shape(shape: CursorShape) {
+    cpu::without_interrupts(|| write_cursor_shape(shape));
+}
+
+fn write_cursor_shape(shape: CursorShape) {
+    // The last scan line of a character
+    let last = read(CrtcRegister::MaximumScanLine) & 0x1F;
+
+    let (start, end) = match shape {
+        CursorShape::Underline => (last.saturating_sub(1), last),
+        CursorShape::Block => (0, last),
+        CursorShape::Hidden => {
+            let start = read(CrtcRegister::CursorStart);
+            write(CrtcRegister::CursorStart, start | CURSOR_DISABLE_BIT);
+            return;
+        }
+    };
+
+    // Keep the reserved bits, and clear the disable bit
+    let start_register = read(CrtcRegister::CursorStart) & 0xC0;
+    let end_register = read(CrtcRegister::CursorEnd) & 0xE0;
+    write(CrtcRegister::CursorStart, start_register | start);
+    write(CrtcRegister::CursorEnd, end_register | end);
+}
@@ -0,0 +1,94 @@
+//! Tracking which cells of a buffer drawn off screen have changed since the screen was last
+//! brought up to date
+
+use core::cmp;
+use super::{MAX_COLUMNS, MAX_ROWS};
+
+/// The changed cells of each row, as a span of columns per row
+pub struct DirtyRows {
+    /// The start and exclusive end of each row's span, which is empty if the start isn't before
+    /// the end
+    spans: [(usize, usize); MAX_ROWS],
+    cursor: bool,
+}
+
+impl DirtyRows {
+    pub const fn new() -> Self {
+        DirtyRows {
+            spans: [(0, 0); MAX_ROWS],
+            cursor: false,
+        }
+    }
+
+    /// Creates with every cell and the cursor marked as changed
+    pub const fn all() -> Self {
+        DirtyRows {
+            spans: [(0, MAX_COLUMNS); MAX_ROWS],
+            cursor: true,
+        }
+    }
+
+    /// Marks the columns from the start up to the exclusive end of a row as changed
+    pub fn mark(&mut self, row: usize, start: usize, end: usize) {
+        if row >= MAX_ROWS || start >= end {
+            return;
+        }
+
+        let span = &mut self.spans[row];
+        *span = if span.0 < span.1 {
+            (cmp::min(span.0, start), cmp::max(span.1, end))
+        } else {
+            (start, end)
+        };
+    }
+
+    /// Marks a single cell as changed
+    pub fn mark_cell(&mut self, row: usize, column: usize) {
+        self.mark(row, column, column + 1);
+    }
+
+    /// Marks the rows from the top up to the exclusive bottom as changed, across the given width
+    pub fn mark_rows(&mut self, top: usize, bottom: usize, width: usize) {
+        for row in top..cmp::min(bottom, MAX_ROWS) {
+            self.mark(row, 0, width);
+        }
+    }
+
+    /// Marks the cursor as moved
+    pub fn mark_cursor(&mut self) {
+        self.cursor = true;
+    }
+
+    /// Checks if nothing is marked as changed
+    #[cfg(test)]
+    pub fn is_clean(&self) -> bool {
+        !self.cursor && self.spans.iter().all(|&(start, end)| start >= end)
+    }
+
+    /// Gets the amount of cells marked as changed
+    #[cfg(test)]
+    pub fn cell_count(&self) -> usize {
+        self.spans.iter().map(|&(start, end)| end.saturating_sub(start)).sum()
+    }
+
+    /// Takes a row's changed columns as a range, marking them as unchanged
+    pub fn take_row(&mut self, row: usize) -> Option<(usize, usize)> {
+        let span = self.spans[row];
+        self.spans[row] = (0, 0);
+
+        if span.0 < span.1 { Some(span) } else { None }
+    }
+
+    /// Takes whether the cursor moved, marking it as unmoved
+    pub fn take_cursor(&mut self) -> bool {
+        let cursor = self.cursor;
+        self.cursor = false;
+        cursor
+    }
+
+    /// Marks everything as unchanged, such as after redrawing the whole screen
+    pub fn clear(&mut self) {
+        self.spans = [(0, 0); MAX_ROWS];
+        self.cursor = false;
+    }
+}
@@ -0,0 +1,96 @@
+//! Dumps of the text screen for checking what's shown from outside the machine, such as in snapshot
+//! tests. Each dump holds the screen as plain text, as text colored with ANSI escapes and as a grid
+//! of attribute bytes, each after a header line.
+
+use core::fmt;
+use drivers::serial;
+use super::{TextBuffer, VgaBuffer, VgaColor, cp437};
+
+/// Dumps the cells shown on screen to the first serial port
+pub fn dump_screen() {
+    let _ = write_dump(&VgaBuffer::shown(), &mut *serial::COM1.lock());
+}
+
+/// Dumps the screen if `vga.dump` is on the kernel command line, such as to check what boot shows
+pub fn dump_if_requested(command_line: &str) {
+    if command_line.split_whitespace().any(|arg| arg == "vga.dump") {
+        dump_screen();
+    }
+}
+
+/// Writes every format of dump of a buffer, each after a `--- screen <width>x<height> <format> ---`
+/// header, followed by a `--- end screen ---` line
+pub fn write_dump<W: fmt::Write>(buffer: &TextBuffer, out: &mut W) -> fmt::Result {
+    let (width, height) = (buffer.width(), buffer.height());
+
+    writeln!(out, "--- screen {}x{} text ---", width, height)?;
+    write_text(buffer, out)?;
+    writeln!(out, "--- screen {}x{} ansi ---", width, height)?;
+    write_ansi(buffer, out)?;
+    writeln!(out, "--- screen {}x{} attributes ---", width, height)?;
+    write_attributes(buffer, out)?;
+    writeln!(out, "--- end screen ---")
+}
+
+/// Writes each row's chars as a line, with the blanks at its end left out
+pub fn write_text<W: fmt::Write>(buffer: &TextBuffer, out: &mut W) -> fmt::Result {
+    for row in 0..buffer.height() {
+        let end = (0..buffer.width()).rev()
+            .find(|&column| buffer.get_char(row, column).character != b' ')
+            .map_or(0, |column| column + 1);
+
+        for column in 0..end {
+            out.write_char(cp437::decode(buffer.get_char(row, column).character))?;
+        }
+        out.write_char('\n')?;
+    }
+
+    Ok(())
+}
+
+/// Writes each row's chars as a line, setting the colors with an ANSI escape wherever they change
+/// and resetting them at the end of the line
+pub fn write_ansi<W: fmt::Write>(buffer: &TextBuffer, out: &mut W) -> fmt::Result {
+    for row in 0..buffer.height() {
+        let mut color = None;
+
+        for column in 0..buffer.width() {
+            let value = buffer.get_char(row, column);
+            if color != Some(value.color) {
+                write_sgr(value.color, out)?;
+                color = Some(value.color);
+            }
+
+            out.write_char(cp437::decode(value.character))?;
+        }
+        out.write_str("\x1b[0m\n")?;
+    }
+
+    Ok(())
+}
+
+/// Writes an ANSI escape setting the foreground and background to a color's
+fn write_sgr<W: fmt::Write>(color: VgaColor, out: &mut W) -> fmt::Result {
+    let (foreground, bright_foreground) = color.foreground().to_ansi();
+    let (background, bright_background) = color.background().to_ansi();
+
+    write!(
+        out, "\x1b[{};{}m",
+        foreground + if bright_foreground { 90 } else { 30 },
+        background + if bright_background { 100 } else { 40 }
+    )
+}
+
+/// Writes each row's attribute bytes as a line of two hex digits per cell, the background then the
+/// foreground
+pub fn write_attributes<W: fmt::Write>(buffer: &TextBuffer, out: &mut W) -> fmt::Result {
+    for row in 0..buffer.height() {
+        for column in 0..buffer.width() {
+            let color = buffer.get_char(row, column).color;
+            write!(out, "{:x}{:x}", color.background() as u8, color.foreground() as u8)?;
+        }
+        out.write_char('\n')?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,164 @@
+//! Access to the character glyphs in plane 2 of video memory, which text modes draw with
+
+use core::{ptr, slice};
+use cpu;
+use super::registers::{self, SequencerRegister, GraphicsRegister};
+
+/// Address plane 2 is mapped at while the glyphs are accessed
+const FONT_MEMORY: usize = 0xA0000;
+
+/// Bytes between the start of each glyph in plane 2, whatever the character height
+const GLYPH_STRIDE: usize = 32;
+
+/// Amount of glyphs in a font
+pub const GLYPH_COUNT: usize = 256;
+
+/// Character height of the font set up at boot
+pub const BOOT_HEIGHT: usize = 16;
+
+/// Character height of the font used by the 8 scan line text modes
+pub const SMALL_HEIGHT: usize = 8;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum FontError {
+    /// The data isn't in a known font format
+    UnknownFormat,
+    /// The data ends before all of its glyphs
+    Truncated,
+    /// Only 8 pixel wide glyphs of the boot or small height can be shown
+    UnsupportedSize { width: usize, height: usize },
+}
+
+/// Represents a font of 256 glyphs, each 8 pixels wide with a byte per row
+#[derive(Copy, Clone, Debug)]
+pub struct Font<'a> {
+    height: usize,
+    glyphs: &'a [u8],
+}
+
+impl<'a> Font<'a> {
+    /// Creates a font from `height` rows of each glyph in turn. The height must be
+    /// `BOOT_HEIGHT` or `SMALL_HEIGHT`.
+    pub fn new(height: usize, glyphs: &'a [u8]) -> Result<Self, FontError> {
+        if height != BOOT_HEIGHT && height != SMALL_HEIGHT {
+            return Err(FontError::UnsupportedSize { width: 8, height: height });
+        }
+        if glyphs.len() < GLYPH_COUNT * height {
+            return Err(FontError::Truncated);
+        }
+
+        Ok(Font {
+            height: height,
+            glyphs: &glyphs[..GLYPH_COUNT * height],
+        })
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Gets the rows of every glyph in turn
+    pub fn glyphs(&self) -> &'a [u8] {
+        self.glyphs
+    }
+
+    /// Gets the rows of a glyph
+    pub fn glyph(&self, index: u8) -> &'a [u8] {
+        let start = index as usize * self.height;
+        &self.glyphs[start..start + self.height]
+    }
+}
+
+/// Runs the given function with plane 2 mapped alone at `FONT_MEMORY`, then maps video memory
+/// back for text mode. Interrupts are disabled meanwhile, as the status bar can't be drawn while
+/// the text isn't mapped.
+fn with_plane_2<F: FnOnce(&mut [u8])>(function: F) {
+    cpu::without_interrupts(|| map_plane_2(function));
+}
+
+fn map_plane_2<F: FnOnce(&mut [u8])>(function: F) {
+    let map_mask = registers::read_sequencer(SequencerRegister::MapMask);
+    let memory_mode = registers::read_sequencer(SequencerRegister::MemoryMode);
+    let read_map = registers::read_graphics(GraphicsRegister::ReadMapSelect);
+    let graphics_mode = registers::read_graphics(GraphicsRegister::GraphicsMode);
+    let miscellaneous = registers::read_graphics(GraphicsRegister::Miscellaneous);
+
+    // Write and read plane 2 only, sequentially rather than odd/even, at 0xA0000
+    registers::write_sequencer(SequencerRegister::MapMask, 0x04);
+    registers::write_sequencer(SequencerRegister::MemoryMode, 0x07);
+    registers::write_graphics(GraphicsRegister::ReadMapSelect, 0x02);
+    registers::write_graphics(GraphicsRegister::GraphicsMode, 0x00);
+    registers::write_graphics(GraphicsRegister::Miscellaneous, 0x04);
+
+    function(unsafe { slice::from_raw_parts_mut(FONT_MEMORY as *mut u8, GLYPH_COUNT * GLYPH_STRIDE) });
+
+    registers::write_sequencer(SequencerRegister::MapMask, map_mask);
+    registers::write_sequencer(SequencerRegister::MemoryMode, memory_mode);
+    registers::write_graphics(GraphicsRegister::ReadMapSelect, read_map);
+    registers::write_graphics(GraphicsRegister::GraphicsMode, graphics_mode);
+    registers::write_graphics(GraphicsRegister::Miscellaneous, miscellaneous);
+}
+
+/// Reads every glyph at the given height into the buffer, which holds `height` rows of each
+/// glyph in turn
+pub fn read_glyphs(height: usize, glyphs: &mut [u8]) {
+    assert!(glyphs.len() >= GLYPH_COUNT * height, "Glyph buffer too small");
+
+    with_plane_2(|memory| {
+        for glyph in 0..GLYPH_COUNT {
+            let source = &memory[glyph * GLYPH_STRIDE..glyph * GLYPH_STRIDE + height];
+            for (row, value) in source.iter().enumerate() {
+                glyphs[glyph * height + row] = unsafe { ptr::read_volatile(value) };
+            }
+        }
+    });
+}
+
+/// Writes every glyph from the buffer, which holds `height` rows of each glyph in turn
+pub fn write_glyphs(height: usize, glyphs: &[u8]) {
+    assert!(glyphs.len() >= GLYPH_COUNT * height, "Glyph buffer too small");
+
+    with_plane_2(|memory| {
+        for glyph in 0..GLYPH_COUNT {
+            for row in 0..height {
+                let destination = &mut memory[glyph * GLYPH_STRIDE + row];
+                unsafe { ptr::write_volatile(destination, glyphs[glyph * height + row]) };
+            }
+        }
+    });
+}
+
+/// Writes a single glyph's rows, leaving the rest of the font alone
+pub fn write_glyph(index: u8, rows: &[u8]) {
+    assert!(rows.len() <= GLYPH_STRIDE, "Glyph too tall");
+
+    with_plane_2(|memory| {
+        let start = index as usize * GLYPH_STRIDE;
+        for (destination, &value) in memory[start..start + rows.len()].iter_mut().zip(rows) {
+            unsafe { ptr::write_volatile(destination, value) };
+        }
+    });
+}
+
+/// Halves the height of a font, merging each pair of rows so thin lines aren't lost
+pub fn halve(glyphs: &[u8], height: usize, halved: &mut [u8]) {
+    let half = height / 2;
+
+    for glyph in 0..GLYPH_COUNT {
+        for row in 0..half {
+            let source = glyph * height + row * 2;
+            halved[glyph * half + row] = glyphs[source] | glyphs[source + 1];
+        }
+    }
+}
+
+/// Doubles the height of a font, repeating each row
+pub fn double(glyphs: &[u8], height: usize, doubled: &mut [u8]) {
+    for glyph in 0..GLYPH_COUNT {
+        for row in 0..height {
+            let destination = glyph * height * 2 + row * 2;
+            doubled[destination] = glyphs[glyph * height + row];
+            doubled[destination + 1] = glyphs[glyph * height + row];
+        }
+    }
+}
@@ -0,0 +1,259 @@
+//! The VGA graphics modes 13h and 12h, with drawing primitives. The text mode is put back as it
+//! was when leaving them.
+
+use core::{cmp, ptr};
+use core::convert::TryFrom;
+use gfx::{Surface, PixelFormat};
+use multiboot::ColorField;
+use super::{Color, VgaWriteError, DAC_SIZE, PALETTE_SIZE};
+use super::mode::{self, SuspendedText};
+use super::palette;
+use super::registers::{self, RegisterState, GraphicsRegister};
+
+/// Address graphics modes map video memory at
+const GRAPHICS_MEMORY: usize = 0xA0000;
+
+/// Bytes in each row of a plane in mode 12h, holding a bit for each pixel
+const PLANAR_STRIDE: usize = 640 / 8;
+
+/// 320x200 with a byte for each pixel, chained across the planes
+const MODE_13H: RegisterState = RegisterState {
+    misc: 0x63,
+    sequencer: [0x03, 0x01, 0x0F, 0x00, 0x0E],
+    crtc: [
+        0x5F, 0x4F, 0x50, 0x82, 0x54, 0x80, 0xBF, 0x1F, 0x00, 0x41, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x9C, 0x0E, 0x8F, 0x28, 0x40, 0x96, 0xB9, 0xA3, 0xFF,
+    ],
+    graphics: [0x00, 0x00, 0x00, 0x00, 0x00, 0x40, 0x05, 0x0F, 0xFF],
+    attribute: [
+        0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0A, 0x0B, 0x0C, 0x0D, 0x0E,
+        0x0F, 0x41, 0x00, 0x0F, 0x00, 0x00,
+    ],
+};
+
+/// 640x480 with a bit for each pixel in each of the 4 planes. Writes use write mode 2, where the
+/// written byte is the color and the bit mask picks the pixels.
+const MODE_12H: RegisterState = RegisterState {
+    misc: 0xE3,
+    sequencer: [0x03, 0x01, 0x0F, 0x00, 0x06],
+    crtc: [
+        0x5F, 0x4F, 0x50, 0x82, 0x54, 0x80, 0x0B, 0x3E, 0x00, 0x40, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0xEA, 0x0C, 0xDF, 0x28, 0x00, 0xE7, 0x04, 0xE3, 0xFF,
+    ],
+    graphics: [0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0x05, 0x0F, 0xFF],
+    attribute: [
+        0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x14, 0x07, 0x38, 0x39, 0x3A, 0x3B, 0x3C, 0x3D, 0x3E,
+        0x3F, 0x01, 0x00, 0x0F, 0x00, 0x00,
+    ],
+};
+
+/// Mode 13h's colors, as 3 bits of red, 3 of green and 2 of blue
+const FORMAT_13H: PixelFormat = PixelFormat {
+    bytes_per_pixel: 1,
+    red: ColorField { position: 5, size: 3 },
+    green: ColorField { position: 2, size: 3 },
+    blue: ColorField { position: 0, size: 2 },
+};
+
+/// Mode 12h's colors, as a bit of red, 2 of green and a bit of blue
+const FORMAT_12H: PixelFormat = PixelFormat {
+    bytes_per_pixel: 1,
+    red: ColorField { position: 3, size: 1 },
+    green: ColorField { position: 1, size: 2 },
+    blue: ColorField { position: 0, size: 1 },
+};
+
+/// Represents a VGA graphics mode
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum GraphicsMode {
+    /// 320x200 in 256 colors
+    Mode13h,
+    /// 640x480 in 16 colors
+    Mode12h,
+}
+
+impl GraphicsMode {
+    /// Gets the width and height in pixels
+    pub fn size(&self) -> (usize, usize) {
+        match *self {
+            GraphicsMode::Mode13h => (320, 200),
+            GraphicsMode::Mode12h => (640, 480),
+        }
+    }
+
+    /// Gets how each color is encoded as a palette index
+    pub fn format(&self) -> PixelFormat {
+        match *self {
+            GraphicsMode::Mode13h => FORMAT_13H,
+            GraphicsMode::Mode12h => FORMAT_12H,
+        }
+    }
+
+    fn registers(&self) -> &'static RegisterState {
+        match *self {
+            GraphicsMode::Mode13h => &MODE_13H,
+            GraphicsMode::Mode12h => &MODE_12H,
+        }
+    }
+}
+
+/// Represents the screen while in a graphics mode, keeping the text mode to put back on `leave`,
+/// or when dropped. Colors are indices into the palette, which is set up so that each index is a
+/// color encoded in the mode's `format`. It's a `Surface`, so shapes, images and text are drawn on
+/// it with `gfx`.
+pub struct Graphics {
+    mode: GraphicsMode,
+    text: SuspendedText,
+}
+
+/// Switches to a graphics mode, or gives `None` if a graphics mode, this one or the adapter's,
+/// is already in use. The terminals keep being written to off screen.
+pub fn enter(mode: GraphicsMode) -> Option<Graphics> {
+    let text = match mode::suspend_text() {
+        Some(text) => text,
+        None => return None,
+    };
+
+    let mut graphics = Graphics {
+        mode: mode,
+        text: text,
+    };
+
+    mode.registers().restore();
+    graphics.load_palette();
+    graphics.clear(0);
+
+    Some(graphics)
+}
+
+impl Graphics {
+    pub fn width(&self) -> usize {
+        self.mode.size().0
+    }
+
+    pub fn height(&self) -> usize {
+        self.mode.size().1
+    }
+
+    /// Sets the palette so that each color index is shown as the color it encodes
+    fn load_palette(&self) {
+        let format = self.mode.format();
+
+        match self.mode {
+            GraphicsMode::Mode13h => for index in 0..DAC_SIZE {
+                palette::write_dac(index as u8, format.decode(index as u32));
+            },
+            // Only the palette registers' DAC entries are shown
+            GraphicsMode::Mode12h => for index in 0..PALETTE_SIZE {
+                if let Ok(color) = Color::try_from(index as u8) {
+                    palette::set_rgb(color, format.decode(index as u32));
+                }
+            },
+        }
+    }
+
+    /// Sets a pixel's color. Pixels off screen are ignored.
+    pub fn put_pixel(&mut self, x: usize, y: usize, color: u8) {
+        if x >= self.width() || y >= self.height() {
+            return;
+        }
+
+        match self.mode {
+            GraphicsMode::Mode13h => unsafe {
+                ptr::write_volatile((GRAPHICS_MEMORY + y * 320 + x) as *mut u8, color);
+            },
+            GraphicsMode::Mode12h => {
+                registers::write_graphics(GraphicsRegister::BitMask, 0x80 >> (x & 7));
+
+                // Reading loads the latches, so the pixels outside the bit mask are kept
+                let address = (GRAPHICS_MEMORY + y * PLANAR_STRIDE + x / 8) as *mut u8;
+                unsafe {
+                    ptr::read_volatile(address);
+                    ptr::write_volatile(address, color);
+                }
+            }
+        }
+    }
+
+    /// Gets a pixel's color, or 0 off screen
+    pub fn get_pixel(&self, x: usize, y: usize) -> u8 {
+        if x >= self.width() || y >= self.height() {
+            return 0;
+        }
+
+        match self.mode {
+            GraphicsMode::Mode13h => unsafe {
+                ptr::read_volatile((GRAPHICS_MEMORY + y * 320 + x) as *const u8)
+            },
+            GraphicsMode::Mode12h => {
+                let address = (GRAPHICS_MEMORY + y * PLANAR_STRIDE + x / 8) as *const u8;
+                let mut color = 0;
+
+                for plane in 0..4 {
+                    registers::write_graphics(GraphicsRegister::ReadMapSelect, plane);
+                    let bits = unsafe { ptr::read_volatile(address) };
+                    color |= (bits >> (7 - (x & 7)) & 1) << plane;
+                }
+
+                color
+            }
+        }
+    }
+
+    /// Fills a rectangle, clipped to the screen
+    pub fn fill_rect(&mut self, x: usize, y: usize, width: usize, height: usize, color: u8) {
+        let right = cmp::min(x.saturating_add(width), self.width());
+        let bottom = cmp::min(y.saturating_add(height), self.height());
+
+        for row in y..bottom {
+            for column in x..right {
+                self.put_pixel(column, row, color);
+            }
+        }
+    }
+
+    /// Fills the whole screen
+    pub fn clear(&mut self, color: u8) {
+        match self.mode {
+            GraphicsMode::Mode13h => {
+                let (width, height) = self.mode.size();
+                self.fill_rect(0, 0, width, height, color);
+            }
+            GraphicsMode::Mode12h => {
+                // Every pixel of each byte is written at once
+                registers::write_graphics(GraphicsRegister::BitMask, 0xFF);
+                for offset in 0..PLANAR_STRIDE * 480 {
+                    unsafe { ptr::write_volatile((GRAPHICS_MEMORY + offset) as *mut u8, color) };
+                }
+            }
+        }
+    }
+
+    /// Goes back to text mode, putting back its registers, font and palette, and redrawing the
+    /// active terminal
+    pub fn leave(mut self) -> Result<(), VgaWriteError> {
+        self.text.resume()
+    }
+}
+
+impl Surface for Graphics {
+    fn width(&self) -> usize {
+        Graphics::width(self)
+    }
+
+    fn height(&self) -> usize {
+        Graphics::height(self)
+    }
+
+    fn format(&self) -> PixelFormat {
+        self.mode.format()
+    }
+
+    fn put_raw(&mut self, x: usize, y: usize, value: u32) {
+        self.put_pixel(x, y, value as u8);
+    }
+
+    fn get_raw(&self, x: usize, y: usize) -> u32 {
+        self.get_pixel(x, y) as u32
+    }
+}
@@ -0,0 +1,624 @@
+use core::{cmp, fmt, ptr, str};
+use gfx::Canvas;
+use testing::{TestCase, TestResult};
+use super::*;
+
+pub const TESTS: &'static [TestCase] = &[
+    test_case!(hardware_buffer_round_trip),
+    test_case!(inactive_terminal_stays_off_screen),
+    test_case!(activating_terminal_draws_it),
+    test_case!(switch_terminals),
+    test_case!(register_state_round_trip),
+    test_case!(switch_text_mode),
+    test_case!(replace_glyph),
+    test_case!(loaded_font_kept_across_modes),
+    test_case!(dac_round_trip),
+    test_case!(remap_palette_register),
+    test_case!(toggle_blink),
+    test_case!(apply_themes),
+    test_case!(mode_switch_keeps_palette),
+    test_case!(mode_13h_primitives),
+    test_case!(mode_12h_pixels),
+    test_case!(leaving_graphics_restores_text),
+    test_case!(dropping_graphics_restores_text),
+    test_case!(switching_in_graphics_waits_for_text),
+    test_case!(terminal_flushes_only_changed_cells),
+    test_case!(hardware_scroll_moves_start_address),
+    test_case!(hardware_scroll_wraps_around),
+    test_case!(terminal_scrolls_in_hardware),
+    test_case!(fixed_bottom_rows_split_off_in_hardware),
+    test_case!(fixed_rows_stay_on_screen),
+    test_case!(pointer_inverts_cell),
+    test_case!(selection_inverts_and_writes_text),
+    test_case!(dump_shown_screen),
+];
+
+const HIGHLIGHT: VgaColor = VgaColor::new(Color::Yellow, Color::Blue);
+
+fn hardware_buffer_round_trip() -> TestResult {
+    let mut buffer = VgaBuffer::new();
+    let previous = buffer.get_char(0, 0);
+
+    buffer.set_char(0, 0, VgaChar::new(HIGHLIGHT, 'a'));
+    test_assert_eq!(buffer.get_char(0, 0), VgaChar::new(HIGHLIGHT, 'a'));
+
+    buffer.set_char(0, 0, previous);
+    Ok(())
+}
+
+fn inactive_terminal_stays_off_screen() -> TestResult {
+    let screen = VgaBuffer::new();
+    let previous = screen.get_char(0, 0);
+
+    let mut terminal = TerminalBuffer::new(false);
+    terminal.set_char(0, 0, VgaChar::new(HIGHLIGHT, 'a'));
+
+    test_assert_eq!(terminal.get_char(0, 0), VgaChar::new(HIGHLIGHT, 'a'));
+    test_assert_eq!(screen.get_char(0, 0), previous);
+    Ok(())
+}
+
+fn activating_terminal_draws_it() -> TestResult {
+    let mut screen = VgaBuffer::new();
+    let previous = screen.get_char(0, 0);
+
+    let mut terminal = TerminalBuffer::new(false);
+    terminal.set_char(0, 0, VgaChar::new(HIGHLIGHT, 'a'));
+    terminal.set_active(true);
+    test_assert_eq!(screen.get_char(0, 0), VgaChar::new(HIGHLIGHT, 'a'));
+
+    // Writes reach the screen on flush while active
+    terminal.set_char(0, 0, VgaChar::new(HIGHLIGHT, 'b'));
+    test_assert_eq!(screen.get_char(0, 0), VgaChar::new(HIGHLIGHT, 'a'));
+    terminal.flush();
+    test_assert_eq!(screen.get_char(0, 0), VgaChar::new(HIGHLIGHT, 'b'));
+
+    screen.set_char(0, 0, previous);
+    Ok(())
+}
+
+fn switch_terminals() -> TestResult {
+    let previous = active();
+
+    switch_to(1);
+    test_assert_eq!(active(), 1);
+    test_assert!(TERMINALS[1].lock().buffer().buffer().is_active());
+    test_assert!(!TERMINALS[previous].lock().buffer().buffer().is_active());
+
+    // Out of range terminals are ignored
+    switch_to(TERMINAL_COUNT);
+    test_assert_eq!(active(), 1);
+
+    switch_to(previous);
+    test_assert!(!TERMINALS[1].lock().buffer().buffer().is_active());
+    Ok(())
+}
+
+fn register_state_round_trip() -> TestResult {
+    let state = RegisterState::save();
+    state.restore();
+    test_assert_eq!(RegisterState::save(), state);
+    Ok(())
+}
+
+fn switch_text_mode() -> TestResult {
+    let mut boot_font = [0; font::GLYPH_COUNT * font::BOOT_HEIGHT];
+    font::read_glyphs(font::BOOT_HEIGHT, &mut boot_font);
+
+    test_assert!(set_mode(TextMode::Text80x50).is_ok());
+    test_assert_eq!(mode(), TextMode::Text80x50);
+    test_assert_eq!(crtc::read(crtc::CrtcRegister::MaximumScanLine) & 0x1F, 7);
+    {
+        let terminal = TERMINALS[LOG_TERMINAL].lock();
+        test_assert_eq!((terminal.buffer().width(), terminal.buffer().height()), (80, 50));
+    }
+
+    test_assert!(set_mode(TextMode::Text80x25).is_ok());
+    test_assert_eq!(crtc::read(crtc::CrtcRegister::MaximumScanLine) & 0x1F, 15);
+    test_assert_eq!(TERMINALS[LOG_TERMINAL].lock().buffer().height(), 25);
+
+    let mut restored_font = [0; font::GLYPH_COUNT * font::BOOT_HEIGHT];
+    font::read_glyphs(font::BOOT_HEIGHT, &mut restored_font);
+    test_assert!(&restored_font[..] == &boot_font[..]);
+    Ok(())
+}
+
+fn replace_glyph() -> TestResult {
+    let rows = [0x81; 16];
+    test_assert!(set_glyph(1, &rows).is_ok());
+    test_assert!(set_glyph(1, &[0; 3]).is_err());
+
+    let mut glyphs = [0; font::GLYPH_COUNT * font::BOOT_HEIGHT];
+    font::read_glyphs(font::BOOT_HEIGHT, &mut glyphs);
+    test_assert_eq!(&glyphs[16..32], &rows[..]);
+
+    test_assert!(restore_boot_mode().is_ok());
+    Ok(())
+}
+
+fn loaded_font_kept_across_modes() -> TestResult {
+    let mut glyphs = [0; font::GLYPH_COUNT * font::SMALL_HEIGHT];
+    glyphs[b'x' as usize * 8] = 0x3C;
+    load_font(&Font::new(8, &glyphs).unwrap());
+
+    // Doubled to fit the boot height
+    let mut tall = [0; font::GLYPH_COUNT * font::BOOT_HEIGHT];
+    font::read_glyphs(font::BOOT_HEIGHT, &mut tall);
+    test_assert_eq!(&tall[b'x' as usize * 16..b'x' as usize * 16 + 3], &[0x3C, 0x3C, 0x00][..]);
+
+    test_assert!(set_mode(TextMode::Text80x50).is_ok());
+    let mut small = [0; font::GLYPH_COUNT * font::SMALL_HEIGHT];
+    font::read_glyphs(font::SMALL_HEIGHT, &mut small);
+    test_assert!(&small[..] == &glyphs[..]);
+
+    test_assert!(restore_boot_mode().is_ok());
+    Ok(())
+}
+
+fn dac_round_trip() -> TestResult {
+    let previous = read_dac(200);
+
+    write_dac(200, Rgb::from_hex(0xFF55AA));
+    test_assert_eq!(read_dac(200), Rgb::new(0xFF, 0x55, 0xAA));
+
+    write_dac(200, previous);
+    Ok(())
+}
+
+fn remap_palette_register() -> TestResult {
+    let previous = palette_register(Color::Blue);
+
+    set_palette_register(Color::Blue, 200);
+    test_assert_eq!(palette_register(Color::Blue), 200);
+
+    // Setting a color's rgb changes the entry it's mapped to
+    let entry = read_dac(200);
+    set_rgb(Color::Blue, Rgb::from_hex(0x123456));
+    test_assert_eq!(read_dac(200), rgb(Color::Blue));
+
+    write_dac(200, entry);
+    set_palette_register(Color::Blue, previous);
+    Ok(())
+}
+
+fn toggle_blink() -> TestResult {
+    let previous = is_blink_enabled();
+
+    set_blink(false);
+    test_assert!(!is_blink_enabled());
+    set_blink(true);
+    test_assert!(is_blink_enabled());
+
+    set_blink(previous);
+    Ok(())
+}
+
+fn apply_themes() -> TestResult {
+    test_assert!(find_theme("plaid").is_none());
+
+    // The DAC keeps 6 bits of each channel
+    apply_theme(find_theme("solarized").unwrap());
+    test_assert_eq!(rgb(Color::Red), Rgb::from_hex(0xDF302C));
+
+    apply_theme(&DEFAULT_THEME);
+    test_assert_eq!(rgb(Color::Brown), Rgb::from_hex(0xAA5500));
+    test_assert_eq!(palette_register(Color::White), 0x3F);
+    Ok(())
+}
+
+fn mode_switch_keeps_palette() -> TestResult {
+    set_blink(false);
+    set_palette_register(Color::Green, 0x3A);
+
+    test_assert!(set_mode(TextMode::Text80x50).is_ok());
+    test_assert!(!is_blink_enabled());
+    test_assert_eq!(palette_register(Color::Green), 0x3A);
+
+    test_assert!(set_mode(TextMode::Text80x25).is_ok());
+    set_blink(true);
+    reset_palette_registers();
+    Ok(())
+}
+
+fn mode_13h_primitives() -> TestResult {
+    let mut graphics = enter_graphics(GraphicsMode::Mode13h).unwrap();
+    test_assert!(enter_graphics(GraphicsMode::Mode12h).is_none());
+
+    graphics.put_pixel(319, 199, 200);
+    graphics.put_pixel(320, 0, 200);
+    test_assert_eq!(graphics.get_pixel(319, 199), 200);
+
+    graphics.fill_rect(20, 20, 2, 2, 9);
+    test_assert_eq!(graphics.get_pixel(21, 21), 9);
+    test_assert_eq!(graphics.get_pixel(22, 21), 0);
+
+    // Colors are drawn as the index encoding them, which the palette shows as that color
+    {
+        let mut canvas = Canvas::new(&mut graphics);
+        canvas.line((0, 0), (10, 5), Rgb::new(0xFF, 0xFF, 0));
+        test_assert_eq!(canvas.get_pixel(10, 5), Some(Rgb::new(0xFF, 0xFF, 0)));
+    }
+    test_assert_eq!(graphics.get_pixel(0, 0), 0xFC);
+    test_assert_eq!(read_dac(0xFC), Rgb::new(0xFF, 0xFF, 0));
+
+    test_assert!(graphics.leave().is_ok());
+    Ok(())
+}
+
+fn mode_12h_pixels() -> TestResult {
+    let mut graphics = enter_graphics(GraphicsMode::Mode12h).unwrap();
+    test_assert_eq!((graphics.width(), graphics.height()), (640, 480));
+
+    // Neighbouring pixels share a byte in each plane
+    graphics.put_pixel(8, 1, 0x0A);
+    graphics.put_pixel(9, 1, 0x05);
+    test_assert_eq!(graphics.get_pixel(8, 1), 0x0A);
+    test_assert_eq!(graphics.get_pixel(9, 1), 0x05);
+    test_assert_eq!(graphics.get_pixel(10, 1), 0);
+
+    test_assert!(graphics.leave().is_ok());
+    Ok(())
+}
+
+fn leaving_graphics_restores_text() -> TestResult {
+    let mut boot_font = [0; font::GLYPH_COUNT * font::BOOT_HEIGHT];
+    font::read_glyphs(font::BOOT_HEIGHT, &mut boot_font);
+    let registers = RegisterState::save();
+    let screen = VgaBuffer::new();
+    let cell = TERMINALS[active()].lock().buffer().get_char(0, 0);
+
+    let mut graphics = enter_graphics(GraphicsMode::Mode12h).unwrap();
+    graphics.clear(0x0F);
+    test_assert!(graphics.leave().is_ok());
+
+    let mut restored_font = [0; font::GLYPH_COUNT * font::BOOT_HEIGHT];
+    font::read_glyphs(font::BOOT_HEIGHT, &mut restored_font);
+    test_assert!(&restored_font[..] == &boot_font[..]);
+    test_assert_eq!(RegisterState::save(), registers);
+    test_assert_eq!(screen.get_char(0, 0), cell);
+    Ok(())
+}
+
+fn dropping_graphics_restores_text() -> TestResult {
+    let registers = RegisterState::save();
+
+    {
+        let graphics = enter_graphics(GraphicsMode::Mode13h).unwrap();
+        test_assert!(enter_graphics(GraphicsMode::Mode12h).is_none());
+        test_assert_eq!((graphics.width(), graphics.height()), (320, 200));
+    }
+
+    test_assert_eq!(RegisterState::save(), registers);
+    test_assert!(TERMINALS[active()].lock().buffer().buffer().is_active());
+
+    let graphics = enter_graphics(GraphicsMode::Mode12h);
+    test_assert!(graphics.is_some());
+    test_assert!(graphics.unwrap().leave().is_ok());
+    Ok(())
+}
+
+fn switching_in_graphics_waits_for_text() -> TestResult {
+    let previous = active();
+    let other = (previous + 1) % TERMINAL_COUNT;
+
+    let mut graphics = enter_graphics(GraphicsMode::Mode13h).unwrap();
+    graphics.put_pixel(0, 0, 5);
+    switch_to(other);
+    let pixel = graphics.get_pixel(0, 0);
+    let shown = TERMINALS[other].lock().buffer().buffer().is_active();
+    test_assert!(graphics.leave().is_ok());
+
+    test_assert_eq!(pixel, 5);
+    test_assert!(!shown);
+    test_assert_eq!(active(), other);
+    test_assert!(TERMINALS[other].lock().buffer().buffer().is_active());
+    test_assert!(!TERMINALS[previous].lock().buffer().buffer().is_active());
+
+    switch_to(previous);
+    Ok(())
+}
+
+fn terminal_flushes_only_changed_cells() -> TestResult {
+    let mut screen = VgaBuffer::new();
+    let previous = [screen.get_char(0, 0), screen.get_char(0, 1)];
+
+    let mut terminal = TerminalBuffer::new(false);
+    terminal.set_active(true);
+
+    // Cells written straight to the screen are kept until the terminal changes them
+    screen.set_char(0, 1, VgaChar::new(HIGHLIGHT, 'x'));
+    terminal.set_char(0, 0, VgaChar::new(HIGHLIGHT, 'a'));
+    terminal.set_char(0, 1, buffer::BLANK);
+    terminal.flush();
+    test_assert_eq!(screen.get_char(0, 0), VgaChar::new(HIGHLIGHT, 'a'));
+    test_assert_eq!(screen.get_char(0, 1), VgaChar::new(HIGHLIGHT, 'x'));
+
+    // Scrolling changes every row
+    terminal.scroll_down(1, Color::Black);
+    test_assert_eq!(screen.get_char(0, 0), VgaChar::new(HIGHLIGHT, 'a'));
+    terminal.flush();
+    test_assert_eq!(screen.get_char(0, 0), buffer::BLANK);
+    test_assert_eq!(screen.get_char(0, 1), buffer::BLANK);
+
+    screen.set_char(0, 0, previous[0]);
+    screen.set_char(0, 1, previous[1]);
+    Ok(())
+}
+
+/// A cell marking the given step of a scrolling test
+fn scroll_marker(step: usize) -> VgaChar {
+    VgaChar::new(HIGHLIGHT, (b'a' + (step % 26) as u8) as char)
+}
+
+/// Gets a cell of the screen where the CRTC currently starts showing video memory
+fn shown_char(row: usize, column: usize) -> VgaChar {
+    let index = crtc::start_address() + row * RESOLUTION_X + column;
+    unsafe { ptr::read_volatile((0xb8000 as *const VgaChar).offset(index as isize)) }
+}
+
+/// Shows the start of video memory and redraws the active terminal there
+fn restore_screen(screen: &mut VgaBuffer) {
+    screen.reset_origin();
+    TERMINALS[active()].lock().buffer_mut().buffer_mut().set_active(true);
+}
+
+fn hardware_scroll_moves_start_address() -> TestResult {
+    let mut screen = VgaBuffer::new();
+    let blank = VgaChar::new(VgaColor::new(Color::Black, Color::Black), ' ');
+
+    screen.set_scroll_strategy(ScrollStrategy::Hardware);
+    screen.set_char(1, 0, scroll_marker(0));
+    screen.set_char(RESOLUTION_Y - 1, 0, scroll_marker(1));
+    screen.set_cursor(3, 4);
+    screen.scroll_down(1, Color::Black);
+
+    let cursor = (crtc::read(crtc::CrtcRegister::CursorLocationHigh) as usize) << 8 |
+        crtc::read(crtc::CrtcRegister::CursorLocationLow) as usize;
+    let (origin, start) = (screen.origin(), crtc::start_address());
+    let shown = [shown_char(0, 0), shown_char(RESOLUTION_Y - 2, 0), shown_char(RESOLUTION_Y - 1, 0)];
+    let cell = screen.get_char(0, 0);
+    restore_screen(&mut screen);
+
+    test_assert_eq!(origin, RESOLUTION_X);
+    test_assert_eq!(start, RESOLUTION_X);
+    // The cursor stays on the same cell of the screen
+    test_assert_eq!(cursor, RESOLUTION_X + 3 * RESOLUTION_X + 4);
+    test_assert_eq!(cell, scroll_marker(0));
+    test_assert_eq!(shown, [scroll_marker(0), scroll_marker(1), blank]);
+    test_assert_eq!(crtc::start_address(), 0);
+    Ok(())
+}
+
+fn hardware_scroll_wraps_around() -> TestResult {
+    let mut screen = VgaBuffer::new();
+    let bottom = RESOLUTION_Y - 1;
+    let (mut wrapped, mut in_memory, mut rows_kept) = (false, true, true);
+
+    screen.set_scroll_strategy(ScrollStrategy::Hardware);
+    for step in 0..buffer::MEMORY_CELLS / RESOLUTION_X + RESOLUTION_Y {
+        screen.set_char(bottom, 0, scroll_marker(step));
+        let previous = screen.origin();
+        screen.scroll_down(1, Color::Black);
+        wrapped |= screen.origin() < previous;
+
+        // The screen never runs past the end of video memory, and the rows written keep their
+        // place whether or not it wrapped around
+        in_memory &= screen.origin() + RESOLUTION_X * RESOLUTION_Y <= buffer::MEMORY_CELLS;
+        for rows_up in 0..cmp::min(step + 1, bottom) {
+            rows_kept &= shown_char(bottom - 1 - rows_up, 0) == scroll_marker(step - rows_up);
+        }
+    }
+
+    restore_screen(&mut screen);
+    test_assert!(wrapped);
+    test_assert!(in_memory);
+    test_assert!(rows_kept);
+    Ok(())
+}
+
+fn terminal_scrolls_in_hardware() -> TestResult {
+    let mut screen = VgaBuffer::new();
+    let mut terminal = TerminalBuffer::new(false);
+    let bottom = RESOLUTION_Y - 1;
+    let mut in_sync = true;
+
+    terminal.set_scroll_strategy(ScrollStrategy::Hardware);
+    terminal.set_active(true);
+    for step in 0..buffer::MEMORY_CELLS / RESOLUTION_X + RESOLUTION_Y {
+        terminal.set_char(bottom, step % RESOLUTION_X, scroll_marker(step));
+        terminal.scroll_down(1, Color::Black);
+        terminal.flush();
+
+        for &row in [0, bottom - 1, bottom].iter() {
+            for column in 0..RESOLUTION_X {
+                in_sync &= shown_char(row, column) == terminal.get_char(row, column);
+            }
+        }
+    }
+    let start = crtc::start_address();
+
+    // Becoming active again draws from the start of video memory
+    terminal.set_active(true);
+    let redrawn = crtc::start_address() == 0 && screen.get_char(bottom - 1, 0) == terminal.get_char(bottom - 1, 0);
+
+    restore_screen(&mut screen);
+    test_assert!(in_sync);
+    test_assert!(start != 0);
+    test_assert!(redrawn);
+    Ok(())
+}
+
+fn fixed_bottom_rows_split_off_in_hardware() -> TestResult {
+    let mut screen = VgaBuffer::new();
+    let mut terminal = TerminalBuffer::new(false);
+    let bottom = RESOLUTION_Y - 1;
+    let status = VgaChar::new(HIGHLIGHT, 's');
+    let mut in_sync = true;
+
+    terminal.set_scroll_strategy(ScrollStrategy::Hardware);
+    terminal.set_fixed_rows(0, 1);
+    terminal.set_active(true);
+    terminal.set_fixed_char(bottom, 0, status);
+    for step in 0..buffer::MEMORY_CELLS / RESOLUTION_X + RESOLUTION_Y {
+        terminal.set_char(bottom - 1, step % RESOLUTION_X, scroll_marker(step));
+        terminal.scroll_down(1, Color::Black);
+        terminal.flush();
+
+        for &row in [0, bottom - 2, bottom - 1].iter() {
+            for column in 0..RESOLUTION_X {
+                in_sync &= shown_char(row, column) == terminal.get_char(row, column);
+            }
+        }
+    }
+
+    let (start, line_compare) = (crtc::start_address(), crtc::line_compare());
+    // The split off row is shown from the start of video memory
+    let split = unsafe { ptr::read_volatile(0xb8000 as *const VgaChar) };
+    let shown = VgaBuffer::shown();
+    let shown = [shown.get_char(bottom - 1, 0), shown.get_char(bottom, 0)];
+
+    restore_screen(&mut screen);
+    test_assert!(in_sync);
+    test_assert!(start >= RESOLUTION_X);
+    test_assert_eq!(line_compare, Some(bottom * crtc::char_height() - 1));
+    test_assert_eq!(split, status);
+    test_assert_eq!(shown, [terminal.get_char(bottom - 1, 0), status]);
+    test_assert_eq!(crtc::line_compare(), None);
+    Ok(())
+}
+
+fn fixed_rows_stay_on_screen() -> TestResult {
+    let mut screen = VgaBuffer::new();
+    let mut terminal = TerminalBuffer::new(false);
+    let bottom = RESOLUTION_Y - 1;
+
+    terminal.set_fixed_rows(1, 1);
+    terminal.set_active(true);
+    terminal.set_fixed_char(0, 0, VgaChar::new(HIGHLIGHT, 't'));
+    terminal.set_fixed_char(bottom, 0, VgaChar::new(HIGHLIGHT, 'b'));
+    terminal.set_fixed_char(1, 0, VgaChar::new(HIGHLIGHT, 'x'));
+    terminal.set_char(0, 0, VgaChar::new(HIGHLIGHT, 'a'));
+    terminal.set_char(1, 0, VgaChar::new(HIGHLIGHT, 'c'));
+    terminal.flush();
+    let drawn = [screen.get_char(0, 0), screen.get_char(1, 0), screen.get_char(bottom, 0)];
+
+    // Only the rows between the fixed rows scroll
+    terminal.scroll_down(1, Color::Black);
+    terminal.flush();
+    let scrolled = [screen.get_char(0, 0), screen.get_char(1, 0), screen.get_char(bottom, 0)];
+
+    restore_screen(&mut screen);
+    test_assert_eq!(terminal.get_fixed_char(1, 0), None);
+    test_assert_eq!(drawn, [VgaChar::new(HIGHLIGHT, 't'), VgaChar::new(HIGHLIGHT, 'a'), VgaChar::new(HIGHLIGHT, 'b')]);
+    test_assert_eq!(scrolled, [VgaChar::new(HIGHLIGHT, 't'), VgaChar::new(HIGHLIGHT, 'c'), VgaChar::new(HIGHLIGHT, 'b')]);
+    Ok(())
+}
+
+fn pointer_inverts_cell() -> TestResult {
+    let mut screen = VgaBuffer::new();
+    let mut terminal = TerminalBuffer::new(false);
+
+    terminal.set_active(true);
+    terminal.set_char(0, 0, VgaChar::new(HIGHLIGHT, 'a'));
+    terminal.set_pointer(Some((0, 0)));
+    terminal.flush();
+    let pointed = screen.get_char(0, 0);
+
+    // Moving the pointer puts the cell back, and it's clamped to the screen
+    terminal.set_pointer(Some((RESOLUTION_Y, RESOLUTION_X)));
+    terminal.flush();
+    let left = screen.get_char(0, 0);
+    let corner = terminal.pointer();
+
+    restore_screen(&mut screen);
+    test_assert_eq!(pointed, VgaChar::new(HIGHLIGHT.inverted(), 'a'));
+    test_assert_eq!(left, VgaChar::new(HIGHLIGHT, 'a'));
+    test_assert_eq!(corner, Some((RESOLUTION_Y - 1, RESOLUTION_X - 1)));
+    test_assert_eq!(terminal.get_char(0, 0), VgaChar::new(HIGHLIGHT, 'a'));
+    Ok(())
+}
+
+/// Most bytes a dump of a screen in one format can take, with attributes being the longest as
+/// two hex digits per cell and a newline per row
+const DUMP_CAPACITY: usize = (2 * MAX_COLUMNS + 1) * MAX_ROWS;
+
+/// Collects text written to it, up to `DUMP_CAPACITY` bytes
+struct TextCollector {
+    bytes: [u8; DUMP_CAPACITY],
+    len: usize,
+}
+
+impl TextCollector {
+    fn new() -> Self {
+        TextCollector {
+            bytes: [0; DUMP_CAPACITY],
+            len: 0,
+        }
+    }
+
+    fn text(&self) -> &str {
+        str::from_utf8(&self.bytes[..self.len]).unwrap_or("")
+    }
+}
+
+impl fmt::Write for TextCollector {
+    fn write_str(&mut self, str: &str) -> fmt::Result {
+        let end = cmp::min(self.len + str.len(), self.bytes.len());
+        self.bytes[self.len..end].copy_from_slice(&str.as_bytes()[..end - self.len]);
+        self.len = end;
+        Ok(())
+    }
+}
+
+fn selection_inverts_and_writes_text() -> TestResult {
+    let mut screen = VgaBuffer::new();
+    let mut terminal = TerminalBuffer::new(false);
+    let mut text = TextCollector::new();
+
+    for (row, line) in ["hello world", "second"].iter().enumerate() {
+        for (column, character) in line.chars().enumerate() {
+            terminal.set_char(row, column, VgaChar::new(HIGHLIGHT, character));
+        }
+    }
+
+    // Selecting backwards, past the end of the first row
+    terminal.set_active(true);
+    terminal.select(Some(((1, 2), (0, 6))));
+    terminal.flush();
+    let shown = [screen.get_char(0, 5), screen.get_char(0, 6), screen.get_char(1, 2), screen.get_char(1, 3)];
+
+    test_assert!(terminal.write_selection(&mut text).is_ok());
+
+    // Scrolling clears the selection
+    terminal.scroll_down(1, Color::Black);
+    let cleared = terminal.selection();
+
+    restore_screen(&mut screen);
+    test_assert_eq!(text.text(), "world\nsec");
+    test_assert_eq!(shown, [
+        VgaChar::new(HIGHLIGHT, ' '), VgaChar::new(HIGHLIGHT.inverted(), 'w'),
+        VgaChar::new(HIGHLIGHT.inverted(), 'c'), VgaChar::new(HIGHLIGHT, 'o'),
+    ]);
+    test_assert_eq!(cleared, None);
+    Ok(())
+}
+
+fn dump_shown_screen() -> TestResult {
+    let mut screen = VgaBuffer::new();
+    let previous = [screen.get_char(0, 0), screen.get_char(0, 1)];
+    let mut attributes = TextCollector::new();
+
+    screen.set_char(0, 0, VgaChar::new(HIGHLIGHT, 'o'));
+    screen.set_char(0, 1, VgaChar::new(HIGHLIGHT, 'k'));
+    let shown = VgaBuffer::shown();
+    let result = write_attributes(&shown, &mut attributes);
+
+    screen.set_char(0, 0, previous[0]);
+    screen.set_char(0, 1, previous[1]);
+    test_assert!(result.is_ok());
+    test_assert_eq!((shown.width(), shown.height()), mode().size());
+    test_assert_eq!(attributes.len, (2 * shown.width() + 1) * shown.height());
+    test_assert!(attributes.text().starts_with("1e1e"));
+    Ok(())
+}
+
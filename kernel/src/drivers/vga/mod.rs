@@ -0,0 +1,51 @@
+mod ansi;
+mod buffer;
+mod color;
+mod crtc;
+mod cp437;
+mod dirty;
+mod dump;
+mod font;
+mod graphics;
+mod mode;
+mod palette;
+mod psf;
+mod registers;
+mod scrollback;
+mod terminal;
+mod writer;
+
+#[cfg(test)]
+mod tests;
+#[cfg(feature = "kernel-test")]
+pub mod hardware_tests;
+
+pub use self::buffer::{TextBuffer, VgaBuffer, MemoryBuffer, VgaChar, ScrollStrategy, RESOLUTION_X,
+                        RESOLUTION_Y, MAX_COLUMNS, MAX_ROWS};
+pub use self::color::{Color, VgaColor, ColorCodeOutOfBounds};
+pub use self::crtc::CursorShape;
+pub use self::cp437::{REPLACEMENT_GLYPH, encode as encode_cp437};
+pub use self::dirty::DirtyRows;
+pub use self::dump::{dump_screen, dump_if_requested, write_dump, write_text, write_ansi, write_attributes};
+pub use self::font::{Font, FontError};
+pub use self::graphics::{Graphics, GraphicsMode};
+pub use self::graphics::enter as enter_graphics;
+pub use self::mode::{TextMode, UnknownTextMode, mode, set_mode, load_font, set_glyph, SuspendedText,
+                     suspend_text, disable_text, configure};
+pub use self::mode::configure_font;
+#[cfg(feature = "kernel-test")]
+pub use self::mode::restore_boot_mode;
+pub use self::palette::{Rgb, Theme, DAC_SIZE, PALETTE_SIZE, DEFAULT_THEME, SOLARIZED_THEME, THEMES, read_dac,
+                        write_dac, palette_register, set_rgb, is_blink_enabled, set_blink,
+                        reset_palette_registers, apply_theme, find_theme};
+#[cfg(feature = "kernel-test")]
+pub use self::palette::{set_palette_register, rgb};
+pub use self::palette::configure as configure_palette;
+pub use self::psf::parse as parse_psf;
+pub use self::registers::RegisterState;
+pub use self::scrollback::{Scrollback, DEFAULT_SCROLLBACK_SCREENS, HISTORY_ROWS};
+pub use self::scrollback::configure as configure_scrollback;
+pub use self::terminal::configure as configure_scrolling;
+pub use self::terminal::{Terminal, TerminalBuffer, TERMINALS, TERMINAL_COUNT, LOG_TERMINAL, MAX_FIXED_ROWS,
+                         active, switch_to, force_switch_to, hold_screen, release_screen};
+pub use self::writer::{VgaWriter, VgaWriteError, WrapMode};
@@ -0,0 +1,386 @@
+//! Switching between text modes, reprogramming the registers and fitting the font to the
+//! character height. Fonts and glyphs loaded here are kept at both heights, so they survive mode
+//! switches.
+
+use core::str::FromStr;
+use core::sync::atomic::{AtomicBool, Ordering};
+use cpu;
+use drivers::framebuffer;
+use spin::Mutex;
+use super::{TERMINALS, VgaWriteError, active};
+use super::font::{self, Font, FontError, GLYPH_COUNT, BOOT_HEIGHT, SMALL_HEIGHT};
+use super::palette::{self, Rgb, DAC_SIZE};
+use super::registers::RegisterState;
+
+const SEQUENCER_9_DOT: [u8; 5] = [0x03, 0x00, 0x03, 0x00, 0x02];
+const SEQUENCER_8_DOT: [u8; 5] = [0x03, 0x01, 0x03, 0x00, 0x02];
+
+/// Graphics controller registers for text mode, with video memory at 0xB8000
+const GRAPHICS: [u8; 9] = [0x00, 0x00, 0x00, 0x00, 0x00, 0x10, 0x0E, 0x00, 0xFF];
+
+const ATTRIBUTE_9_DOT: [u8; 21] = [
+    0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x14, 0x07, 0x38, 0x39, 0x3A, 0x3B, 0x3C, 0x3D, 0x3E, 0x3F,
+    0x0C, 0x00, 0x0F, 0x08, 0x00,
+];
+
+/// Like `ATTRIBUTE_9_DOT`, without panning the 9th dot
+const ATTRIBUTE_8_DOT: [u8; 21] = [
+    0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x14, 0x07, 0x38, 0x39, 0x3A, 0x3B, 0x3C, 0x3D, 0x3E, 0x3F,
+    0x0C, 0x00, 0x0F, 0x00, 0x00,
+];
+
+const TEXT_80X25: RegisterState = RegisterState {
+    misc: 0x67,
+    sequencer: SEQUENCER_9_DOT,
+    crtc: [
+        0x5F, 0x4F, 0x50, 0x82, 0x55, 0x81, 0xBF, 0x1F, 0x00, 0x4F, 0x0D, 0x0E, 0x00, 0x00, 0x00,
+        0x50, 0x9C, 0x0E, 0x8F, 0x28, 0x1F, 0x96, 0xB9, 0xA3, 0xFF,
+    ],
+    graphics: GRAPHICS,
+    attribute: ATTRIBUTE_9_DOT,
+};
+
+/// 80x25's timings, with 8 scan line characters
+const TEXT_80X50: RegisterState = RegisterState {
+    misc: 0x67,
+    sequencer: SEQUENCER_9_DOT,
+    crtc: [
+        0x5F, 0x4F, 0x50, 0x82, 0x55, 0x81, 0xBF, 0x1F, 0x00, 0x47, 0x06, 0x07, 0x00, 0x00, 0x01,
+        0x40, 0x9C, 0x8E, 0x8F, 0x28, 0x1F, 0x96, 0xB9, 0xA3, 0xFF,
+    ],
+    graphics: GRAPHICS,
+    attribute: ATTRIBUTE_9_DOT,
+};
+
+/// 480 scan lines of 8 scan line characters, with 8 dot wide characters on the 28MHz clock
+const TEXT_90X60: RegisterState = RegisterState {
+    misc: 0xE7,
+    sequencer: SEQUENCER_8_DOT,
+    crtc: [
+        0x6B, 0x59, 0x5A, 0x82, 0x60, 0x8D, 0x0B, 0x3E, 0x00, 0x47, 0x06, 0x07, 0x00, 0x00, 0x00,
+        0x00, 0xEA, 0x0C, 0xDF, 0x2D, 0x08, 0xE8, 0x05, 0xA3, 0xFF,
+    ],
+    graphics: GRAPHICS,
+    attribute: ATTRIBUTE_8_DOT,
+};
+
+static STATE: Mutex<ModeState> = Mutex::new(ModeState::new());
+
+/// Represents a text mode
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TextMode {
+    /// The mode set up at boot, with 8x16 characters
+    Text80x25,
+    Text80x50,
+    Text90x60,
+}
+
+impl TextMode {
+    /// Gets the amount of columns and rows
+    pub fn size(&self) -> (usize, usize) {
+        match *self {
+            TextMode::Text80x25 => (80, 25),
+            TextMode::Text80x50 => (80, 50),
+            TextMode::Text90x60 => (90, 60),
+        }
+    }
+
+    /// Gets the height of a character in scan lines
+    pub fn char_height(&self) -> usize {
+        match *self {
+            TextMode::Text80x25 => 16,
+            TextMode::Text80x50 | TextMode::Text90x60 => 8,
+        }
+    }
+
+    fn registers(&self) -> &'static RegisterState {
+        match *self {
+            TextMode::Text80x25 => &TEXT_80X25,
+            TextMode::Text80x50 => &TEXT_80X50,
+            TextMode::Text90x60 => &TEXT_90X60,
+        }
+    }
+}
+
+/// Error for a text mode name which isn't known
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct UnknownTextMode;
+
+impl FromStr for TextMode {
+    type Err = UnknownTextMode;
+
+    fn from_str(str: &str) -> Result<Self, UnknownTextMode> {
+        match str {
+            "80x25" => Ok(TextMode::Text80x25),
+            "80x50" => Ok(TextMode::Text80x50),
+            "90x60" => Ok(TextMode::Text90x60),
+            _ => Err(UnknownTextMode),
+        }
+    }
+}
+
+/// The boot mode's registers and font, kept from the first change so they can be put back, and
+/// the font in use at each character height
+struct ModeState {
+    boot: Option<RegisterState>,
+    boot_font: [u8; GLYPH_COUNT * BOOT_HEIGHT],
+    font: [u8; GLYPH_COUNT * BOOT_HEIGHT],
+    small_font: [u8; GLYPH_COUNT * SMALL_HEIGHT],
+    mode: TextMode,
+}
+
+impl ModeState {
+    const fn new() -> Self {
+        ModeState {
+            boot: None,
+            boot_font: [0; GLYPH_COUNT * BOOT_HEIGHT],
+            font: [0; GLYPH_COUNT * BOOT_HEIGHT],
+            small_font: [0; GLYPH_COUNT * SMALL_HEIGHT],
+            mode: TextMode::Text80x25,
+        }
+    }
+
+    /// Saves the boot registers and font, if not saved yet
+    fn save_boot(&mut self) {
+        if self.boot.is_none() {
+            self.boot = Some(RegisterState::save());
+            font::read_glyphs(BOOT_HEIGHT, &mut self.boot_font);
+            self.reset_fonts();
+        }
+    }
+
+    /// Goes back to the boot font, halved for the small font
+    fn reset_fonts(&mut self) {
+        self.font = self.boot_font;
+        font::halve(&self.boot_font, BOOT_HEIGHT, &mut self.small_font);
+    }
+
+    /// Writes the font for the current character height to video memory
+    fn write_font(&self) {
+        if self.mode.char_height() == BOOT_HEIGHT {
+            font::write_glyphs(BOOT_HEIGHT, &self.font);
+        } else {
+            font::write_glyphs(SMALL_HEIGHT, &self.small_font);
+        }
+    }
+
+    fn load_font(&mut self, loaded: &Font) {
+        if loaded.height() == BOOT_HEIGHT {
+            self.font.copy_from_slice(loaded.glyphs());
+            font::halve(&self.font, BOOT_HEIGHT, &mut self.small_font);
+        } else {
+            self.small_font.copy_from_slice(loaded.glyphs());
+            font::double(&self.small_font, SMALL_HEIGHT, &mut self.font);
+        }
+    }
+
+    /// Replaces a glyph in both fonts, from rows of either character height
+    fn set_glyph(&mut self, index: u8, rows: &[u8]) {
+        let tall = index as usize * BOOT_HEIGHT;
+        let small = index as usize * SMALL_HEIGHT;
+
+        for row in 0..SMALL_HEIGHT {
+            let (top, bottom) = if rows.len() == BOOT_HEIGHT {
+                (rows[row * 2], rows[row * 2 + 1])
+            } else {
+                (rows[row], rows[row])
+            };
+
+            self.font[tall + row * 2] = top;
+            self.font[tall + row * 2 + 1] = bottom;
+            self.small_font[small + row] = top | bottom;
+        }
+    }
+
+    /// Gets the rows of a glyph at the current character height
+    fn glyph(&self, index: u8) -> &[u8] {
+        let height = self.mode.char_height();
+        let start = index as usize * height;
+
+        if height == BOOT_HEIGHT {
+            &self.font[start..start + height]
+        } else {
+            &self.small_font[start..start + height]
+        }
+    }
+
+    /// Writes the given registers, loads the font fitted to the character height, and resizes
+    /// every terminal
+    fn apply(&mut self, mode: TextMode, registers: &RegisterState) -> Result<(), VgaWriteError> {
+        self.mode = mode;
+
+        // The status bar isn't drawn until the font fits the new registers
+        cpu::without_interrupts(|| {
+            registers.restore();
+            self.write_font();
+        });
+
+        let (width, height) = mode.size();
+        for terminal in TERMINALS.iter() {
+            terminal.lock().resize(width, height)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Gets the current text mode
+pub fn mode() -> TextMode {
+    STATE.lock().mode
+}
+
+/// Switches to the given text mode, resizing every terminal to fit. The palette and blink setting
+/// are kept.
+pub fn set_mode(mode: TextMode) -> Result<(), VgaWriteError> {
+    let mut state = STATE.lock();
+    state.save_boot();
+    state.apply(mode, &palette::keep_palette(mode.registers()))
+}
+
+/// Whether a graphics mode has the screen, so that the text mode is only suspended once
+static SUSPENDED: AtomicBool = AtomicBool::new(false);
+
+/// Keeps the text mode's registers and palette while a graphics mode has the screen. The text
+/// mode is put back by `resume`, or when dropped.
+pub struct SuspendedText {
+    registers: RegisterState,
+    dac: [Rgb; DAC_SIZE],
+    resumed: bool,
+}
+
+/// Checks if the screen is in text mode, rather than handed over to a graphics mode
+pub fn is_text() -> bool {
+    !SUSPENDED.load(Ordering::SeqCst)
+}
+
+/// Hands the screen over to a graphics mode, or gives `None` if one already has it. The
+/// terminals keep being written to off screen.
+pub fn suspend_text() -> Option<SuspendedText> {
+    if SUSPENDED.swap(true, Ordering::SeqCst) {
+        return None;
+    }
+
+    STATE.lock().save_boot();
+    TERMINALS[active()].lock().buffer_mut().buffer_mut().set_active(false);
+
+    let mut text = SuspendedText {
+        registers: RegisterState::save(),
+        dac: [Rgb::new(0, 0, 0); DAC_SIZE],
+        resumed: false,
+    };
+    for (index, entry) in text.dac.iter_mut().enumerate() {
+        *entry = palette::read_dac(index as u8);
+    }
+
+    Some(text)
+}
+
+/// Hands the screen over for good without touching the VGA registers, such as to a framebuffer
+/// console when the bootloader left the screen in a graphics mode. The terminals keep being
+/// written to off screen.
+pub fn disable_text() {
+    SUSPENDED.store(true, Ordering::SeqCst);
+    TERMINALS[active()].lock().buffer_mut().buffer_mut().set_active(false);
+}
+
+impl SuspendedText {
+    /// Goes back to the current text mode, after a graphics mode overwrote the font, the palette
+    /// and the screen, and redraws the active terminal
+    pub fn resume(&mut self) -> Result<(), VgaWriteError> {
+        if self.resumed {
+            return Ok(());
+        }
+        self.resumed = true;
+
+        for (index, &entry) in self.dac.iter().enumerate() {
+            palette::write_dac(index as u8, entry);
+        }
+
+        let result = {
+            let mut state = STATE.lock();
+            let mode = state.mode;
+            state.apply(mode, &self.registers)
+        };
+
+        TERMINALS[active()].lock().buffer_mut().buffer_mut().set_active(true);
+        SUSPENDED.store(false, Ordering::SeqCst);
+        result
+    }
+}
+
+impl Drop for SuspendedText {
+    fn drop(&mut self) {
+        let _ = self.resume();
+    }
+}
+
+/// Puts back the registers and font as they were at boot, in 80x25, such as after a test changed them
+#[cfg(feature = "kernel-test")]
+pub fn restore_boot_mode() -> Result<(), VgaWriteError> {
+    let mut state = STATE.lock();
+
+    match state.boot.clone() {
+        Some(boot) => {
+            state.reset_fonts();
+            state.apply(TextMode::Text80x25, &boot)
+        }
+        None => Ok(()),
+    }
+}
+
+/// Switches text mode as given on the kernel command line, e.g. `vga=80x50`
+pub fn configure(command_line: &str) {
+    for arg in command_line.split_whitespace() {
+        if !arg.starts_with("vga=") {
+            continue;
+        }
+
+        let name = &arg["vga=".len()..];
+        match name.parse() {
+            Ok(mode) => if set_mode(mode).is_err() {
+                warn!("vga", "failed to switch to text mode `{}`", name);
+            },
+            Err(_) => warn!("vga", "unknown text mode `{}`", name),
+        }
+    }
+}
+
+/// Loads a font, replacing every glyph. It's scaled to fit the other character height, so it's
+/// kept when switching mode.
+pub fn load_font(font: &Font) {
+    let mut state = STATE.lock();
+    state.save_boot();
+    state.load_font(font);
+    state.write_font();
+}
+
+/// Replaces a single glyph, such as to show a custom symbol. The rows may be of either character
+/// height, and are scaled to fit the other.
+pub fn set_glyph(index: u8, rows: &[u8]) -> Result<(), FontError> {
+    if rows.len() != BOOT_HEIGHT && rows.len() != SMALL_HEIGHT {
+        return Err(FontError::UnsupportedSize { width: 8, height: rows.len() });
+    }
+
+    let mut state = STATE.lock();
+    state.save_boot();
+    state.set_glyph(index, rows);
+
+    let glyph = state.glyph(index);
+    font::write_glyph(index, glyph);
+    Ok(())
+}
+
+/// Loads the font named on the kernel command line, e.g. `vga.font=console` for the framebuffer
+/// console's font
+pub fn configure_font(command_line: &str) {
+    for arg in command_line.split_whitespace() {
+        if !arg.starts_with("vga.font=") {
+            continue;
+        }
+
+        match &arg["vga.font=".len()..] {
+            "console" => load_font(&framebuffer::font()),
+            name => warn!("vga", "unknown font `{}`", name),
+        }
+    }
+}
@@ -0,0 +1,210 @@
+//! The DAC's 256 entry palette, the attribute controller's 16 palette registers which pick an
+//! entry for each `Color`, and color themes built on them
+
+use cpu;
+use io::IOPort;
+use super::Color;
+use super::registers::{self, RegisterState};
+
+static DAC_READ_INDEX_PORT: IOPort = IOPort::new(0x3C7);
+static DAC_WRITE_INDEX_PORT: IOPort = IOPort::new(0x3C8);
+/// Takes the red, green and blue of an entry in turn, then moves on to the next entry
+static DAC_DATA_PORT: IOPort = IOPort::new(0x3C9);
+
+/// Amount of entries in the DAC palette
+pub const DAC_SIZE: usize = 256;
+
+/// Amount of attribute palette registers, one for each `Color`
+pub const PALETTE_SIZE: usize = 16;
+
+/// Attribute register controlling how attributes are shown
+const MODE_CONTROL: usize = 0x10;
+/// Mode control bit which makes the top bit of the background blink rather than brighten it
+const BLINK_BIT: u8 = 1 << 3;
+
+/// The DAC entry each `Color` is mapped to at boot
+const DEFAULT_MAPPING: [u8; PALETTE_SIZE] = [
+    0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x14, 0x07, 0x38, 0x39, 0x3A, 0x3B, 0x3C, 0x3D, 0x3E, 0x3F,
+];
+
+/// The standard VGA colors
+pub const DEFAULT_THEME: Theme = Theme {
+    name: "default",
+    colors: [
+        Rgb::from_hex(0x000000), Rgb::from_hex(0x0000AA), Rgb::from_hex(0x00AA00), Rgb::from_hex(0x00AAAA),
+        Rgb::from_hex(0xAA0000), Rgb::from_hex(0xAA00AA), Rgb::from_hex(0xAA5500), Rgb::from_hex(0xAAAAAA),
+        Rgb::from_hex(0x555555), Rgb::from_hex(0x5555FF), Rgb::from_hex(0x55FF55), Rgb::from_hex(0x55FFFF),
+        Rgb::from_hex(0xFF5555), Rgb::from_hex(0xFF55FF), Rgb::from_hex(0xFFFF55), Rgb::from_hex(0xFFFFFF),
+    ],
+};
+
+/// Solarized dark, laid out as terminals usually map it onto the 16 colors
+pub const SOLARIZED_THEME: Theme = Theme {
+    name: "solarized",
+    colors: [
+        Rgb::from_hex(0x002B36), Rgb::from_hex(0x268BD2), Rgb::from_hex(0x859900), Rgb::from_hex(0x2AA198),
+        Rgb::from_hex(0xDC322F), Rgb::from_hex(0xD33682), Rgb::from_hex(0xB58900), Rgb::from_hex(0xEEE8D5),
+        Rgb::from_hex(0x073642), Rgb::from_hex(0x839496), Rgb::from_hex(0x586E75), Rgb::from_hex(0x93A1A1),
+        Rgb::from_hex(0xCB4B16), Rgb::from_hex(0x6C71C4), Rgb::from_hex(0x657B83), Rgb::from_hex(0xFDF6E3),
+    ],
+};
+
+pub const THEMES: &'static [Theme] = &[DEFAULT_THEME, SOLARIZED_THEME];
+
+/// Represents a color of 8 bits per channel. The DAC only keeps the top 6 bits of each.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Rgb {
+    pub red: u8,
+    pub green: u8,
+    pub blue: u8,
+}
+
+impl Rgb {
+    pub const fn new(red: u8, green: u8, blue: u8) -> Self {
+        Rgb {
+            red: red,
+            green: green,
+            blue: blue,
+        }
+    }
+
+    /// Creates a color from `0xRRGGBB`
+    pub const fn from_hex(hex: u32) -> Self {
+        Rgb::new((hex >> 16) as u8, (hex >> 8) as u8, hex as u8)
+    }
+}
+
+/// Represents a color for each `Color`, in order
+#[derive(Copy, Clone, Debug)]
+pub struct Theme {
+    pub name: &'static str,
+    pub colors: [Rgb; PALETTE_SIZE],
+}
+
+/// Reads an entry of the DAC palette
+pub fn read_dac(index: u8) -> Rgb {
+    let (red, green, blue) = cpu::without_interrupts(|| {
+        DAC_READ_INDEX_PORT.write(index);
+        (DAC_DATA_PORT.read(), DAC_DATA_PORT.read(), DAC_DATA_PORT.read())
+    });
+
+    Rgb::new(widen(red), widen(green), widen(blue))
+}
+
+/// Writes an entry of the DAC palette
+pub fn write_dac(index: u8, color: Rgb) {
+    cpu::without_interrupts(|| {
+        DAC_WRITE_INDEX_PORT.write(index);
+        DAC_DATA_PORT.write(color.red >> 2);
+        DAC_DATA_PORT.write(color.green >> 2);
+        DAC_DATA_PORT.write(color.blue >> 2);
+    });
+}
+
+/// Widens a 6 bit DAC channel to 8 bits, so that full intensity stays full
+fn widen(value: u8) -> u8 {
+    let value = value & 0x3F;
+    value << 2 | value >> 4
+}
+
+/// Gets the DAC entry a color is shown with
+pub fn palette_register(color: Color) -> u8 {
+    let index = registers::read_attribute(color as u8);
+    registers::enable_palette();
+    index
+}
+
+/// Maps a color to be shown with the given DAC entry
+#[cfg(feature = "kernel-test")]
+pub fn set_palette_register(color: Color, dac_index: u8) {
+    registers::write_attribute(color as u8, dac_index);
+    registers::enable_palette();
+}
+
+/// Sets the color a `Color` is shown as, by changing the DAC entry it's mapped to
+pub fn set_rgb(color: Color, rgb: Rgb) {
+    write_dac(palette_register(color), rgb);
+}
+
+/// Gets the color a `Color` is shown as
+#[cfg(feature = "kernel-test")]
+pub fn rgb(color: Color) -> Rgb {
+    read_dac(palette_register(color))
+}
+
+/// Checks if backgrounds of the bright colors blink rather than being shown bright
+pub fn is_blink_enabled() -> bool {
+    let mode_control = registers::read_attribute(MODE_CONTROL as u8);
+    registers::enable_palette();
+    mode_control & BLINK_BIT != 0
+}
+
+/// Sets whether backgrounds of the bright colors blink, as at boot, or are shown bright
+pub fn set_blink(enabled: bool) {
+    let mode_control = registers::read_attribute(MODE_CONTROL as u8);
+    let mode_control = if enabled { mode_control | BLINK_BIT } else { mode_control & !BLINK_BIT };
+    registers::write_attribute(MODE_CONTROL as u8, mode_control);
+    registers::enable_palette();
+}
+
+/// Maps every color back to its DAC entry from boot
+pub fn reset_palette_registers() {
+    for (index, &dac_index) in DEFAULT_MAPPING.iter().enumerate() {
+        registers::write_attribute(index as u8, dac_index);
+    }
+    registers::enable_palette();
+}
+
+/// Shows every `Color` as the theme's color for it
+pub fn apply_theme(theme: &Theme) {
+    reset_palette_registers();
+
+    for (&dac_index, &rgb) in DEFAULT_MAPPING.iter().zip(theme.colors.iter()) {
+        write_dac(dac_index, rgb);
+    }
+}
+
+/// Gets the theme with the given name
+pub fn find_theme(name: &str) -> Option<&'static Theme> {
+    THEMES.iter().find(|theme| theme.name == name)
+}
+
+/// Copies the current palette registers and blink bit into registers for another mode, so a
+/// mode switch keeps the console's colors
+pub fn keep_palette(mode_registers: &RegisterState) -> RegisterState {
+    let mut kept = mode_registers.clone();
+
+    for (index, value) in kept.attribute[..PALETTE_SIZE].iter_mut().enumerate() {
+        *value = registers::read_attribute(index as u8);
+    }
+
+    let mode_control = kept.attribute[MODE_CONTROL];
+    kept.attribute[MODE_CONTROL] = if is_blink_enabled() {
+        mode_control | BLINK_BIT
+    } else {
+        mode_control & !BLINK_BIT
+    };
+
+    kept
+}
+
+/// Applies the theme given on the kernel command line, e.g. `vga.theme=solarized`, and shows bright
+/// backgrounds instead of blinking with `vga.blink=off`
+pub fn configure(command_line: &str) {
+    for arg in command_line.split_whitespace() {
+        if arg == "vga.blink=off" {
+            set_blink(false);
+            continue;
+        }
+
+        if !arg.starts_with("vga.theme=") {
+            continue;
+        }
+
+        let name = &arg["vga.theme=".len()..];
+        match find_theme(name) {
+            Some(theme) => apply_theme(theme),
+            None => warn!("vga", "unknown theme `{}`", name),
+        }
+    }
+}
@@ -0,0 +1,74 @@
+//! Parsing of PC Screen Font files, the format of the Linux console fonts
+//!
+//! See the [format](https://www.win.tue.nl/~aeb/linux/kbd/font-formats-1.html)
+
+use super::font::{Font, FontError, GLYPH_COUNT};
+
+const PSF1_MAGIC: [u8; 2] = [0x36, 0x04];
+const PSF1_HEADER_SIZE: usize = 4;
+/// Mode bit for fonts of 512 glyphs rather than 256
+const PSF1_MODE_512: u8 = 1 << 0;
+
+const PSF2_MAGIC: [u8; 4] = [0x72, 0xB5, 0x4A, 0x86];
+const PSF2_HEADER_SIZE: usize = 32;
+
+/// Parses a PSF1 or PSF2 font. Only the first 256 glyphs are used, and any unicode table is
+/// ignored.
+pub fn parse(data: &[u8]) -> Result<Font, FontError> {
+    if data.starts_with(&PSF1_MAGIC) {
+        parse_psf1(data)
+    } else if data.starts_with(&PSF2_MAGIC) {
+        parse_psf2(data)
+    } else {
+        Err(FontError::UnknownFormat)
+    }
+}
+
+fn parse_psf1(data: &[u8]) -> Result<Font, FontError> {
+    if data.len() < PSF1_HEADER_SIZE {
+        return Err(FontError::Truncated);
+    }
+
+    let mode = data[2];
+    let height = data[3] as usize;
+    let count = if mode & PSF1_MODE_512 != 0 { 512 } else { 256 };
+
+    let glyphs = &data[PSF1_HEADER_SIZE..];
+    if glyphs.len() < count * height {
+        return Err(FontError::Truncated);
+    }
+
+    Font::new(height, glyphs)
+}
+
+fn parse_psf2(data: &[u8]) -> Result<Font, FontError> {
+    if data.len() < PSF2_HEADER_SIZE {
+        return Err(FontError::Truncated);
+    }
+
+    let header_size = read_u32(data, 8) as usize;
+    let count = read_u32(data, 16) as usize;
+    let glyph_size = read_u32(data, 20) as usize;
+    let height = read_u32(data, 24) as usize;
+    let width = read_u32(data, 28) as usize;
+
+    // Each row is a single byte only up to 8 pixels wide
+    if width == 0 || width > 8 || glyph_size != height {
+        return Err(FontError::UnsupportedSize { width: width, height: height });
+    }
+    if count < GLYPH_COUNT || header_size > data.len() {
+        return Err(FontError::Truncated);
+    }
+
+    let glyphs = &data[header_size..];
+    if glyphs.len() < count * glyph_size {
+        return Err(FontError::Truncated);
+    }
+
+    Font::new(height, glyphs)
+}
+
+fn read_u32(data: &[u8], offset: usize) -> u32 {
+    (data[offset] as u32) | (data[offset + 1] as u32) << 8 |
+        (data[offset + 2] as u32) << 16 | (data[offset + 3] as u32) << 24
+}
@@ -0,0 +1,212 @@
+//! The VGA's sequencer, graphics controller and attribute controller registers, and saving the
+//! whole register state
+
+use cpu;
+use io::IOPort;
+use super::crtc;
+
+static MISC_WRITE_PORT: IOPort = IOPort::new(0x3C2);
+static MISC_READ_PORT: IOPort = IOPort::new(0x3CC);
+
+static SEQUENCER_INDEX_PORT: IOPort = IOPort::new(0x3C4);
+static SEQUENCER_DATA_PORT: IOPort = IOPort::new(0x3C5);
+
+static GRAPHICS_INDEX_PORT: IOPort = IOPort::new(0x3CE);
+static GRAPHICS_DATA_PORT: IOPort = IOPort::new(0x3CF);
+
+/// Takes the attribute index and data in turn, alternating on each write
+static ATTRIBUTE_PORT: IOPort = IOPort::new(0x3C0);
+static ATTRIBUTE_READ_PORT: IOPort = IOPort::new(0x3C1);
+
+/// Reading this resets the attribute port to expect an index
+static INPUT_STATUS_PORT: IOPort = IOPort::new(0x3DA);
+
+/// Attribute index bit which lets the display use the palette, unblanking the screen
+const PALETTE_ENABLE_BIT: u8 = 1 << 5;
+
+/// CRTC register holding the bit which write protects CRTC registers 0 to 7
+const CRTC_VERTICAL_RETRACE_END: usize = 0x11;
+const CRTC_PROTECT_BIT: u8 = 1 << 7;
+
+pub const SEQUENCER_COUNT: usize = 5;
+pub const CRTC_COUNT: usize = 25;
+pub const GRAPHICS_COUNT: usize = 9;
+pub const ATTRIBUTE_COUNT: usize = 21;
+
+/// Represents a sequencer register index
+#[allow(dead_code)]
+#[derive(Copy, Clone)]
+#[repr(u8)]
+pub enum SequencerRegister {
+    Reset = 0x00,
+    ClockingMode = 0x01,
+    MapMask = 0x02,
+    CharacterMapSelect = 0x03,
+    MemoryMode = 0x04,
+}
+
+/// Represents a graphics controller register index
+#[allow(dead_code)]
+#[derive(Copy, Clone)]
+#[repr(u8)]
+pub enum GraphicsRegister {
+    SetReset = 0x00,
+    EnableSetReset = 0x01,
+    ColorCompare = 0x02,
+    DataRotate = 0x03,
+    ReadMapSelect = 0x04,
+    GraphicsMode = 0x05,
+    Miscellaneous = 0x06,
+    ColorDontCare = 0x07,
+    BitMask = 0x08,
+}
+
+pub fn read_misc() -> u8 {
+    MISC_READ_PORT.read()
+}
+
+pub fn write_misc(value: u8) {
+    MISC_WRITE_PORT.write(value);
+}
+
+pub fn read_sequencer(register: SequencerRegister) -> u8 {
+    read_sequencer_index(register as u8)
+}
+
+pub fn write_sequencer(register: SequencerRegister, value: u8) {
+    write_sequencer_index(register as u8, value);
+}
+
+fn read_sequencer_index(index: u8) -> u8 {
+    cpu::without_interrupts(|| {
+        SEQUENCER_INDEX_PORT.write(index);
+        SEQUENCER_DATA_PORT.read()
+    })
+}
+
+fn write_sequencer_index(index: u8, value: u8) {
+    cpu::without_interrupts(|| {
+        SEQUENCER_INDEX_PORT.write(index);
+        SEQUENCER_DATA_PORT.write(value);
+    });
+}
+
+pub fn read_graphics(register: GraphicsRegister) -> u8 {
+    read_graphics_index(register as u8)
+}
+
+pub fn write_graphics(register: GraphicsRegister, value: u8) {
+    write_graphics_index(register as u8, value);
+}
+
+fn read_graphics_index(index: u8) -> u8 {
+    cpu::without_interrupts(|| {
+        GRAPHICS_INDEX_PORT.write(index);
+        GRAPHICS_DATA_PORT.read()
+    })
+}
+
+fn write_graphics_index(index: u8, value: u8) {
+    cpu::without_interrupts(|| {
+        GRAPHICS_INDEX_PORT.write(index);
+        GRAPHICS_DATA_PORT.write(value);
+    });
+}
+
+/// Reads an attribute controller register. The screen is blank until `enable_palette`.
+pub fn read_attribute(index: u8) -> u8 {
+    cpu::without_interrupts(|| {
+        INPUT_STATUS_PORT.read();
+        ATTRIBUTE_PORT.write(index);
+        ATTRIBUTE_READ_PORT.read()
+    })
+}
+
+/// Writes an attribute controller register. The screen is blank until `enable_palette`.
+pub fn write_attribute(index: u8, value: u8) {
+    cpu::without_interrupts(|| {
+        INPUT_STATUS_PORT.read();
+        ATTRIBUTE_PORT.write(index);
+        ATTRIBUTE_PORT.write(value);
+    });
+}
+
+/// Gives the palette back to the display after accessing the attribute controller, which
+/// unblanks the screen
+pub fn enable_palette() {
+    cpu::without_interrupts(|| {
+        INPUT_STATUS_PORT.read();
+        ATTRIBUTE_PORT.write(PALETTE_ENABLE_BIT);
+    });
+}
+
+/// Represents the value of every VGA register which sets up a display mode
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RegisterState {
+    pub misc: u8,
+    pub sequencer: [u8; SEQUENCER_COUNT],
+    pub crtc: [u8; CRTC_COUNT],
+    pub graphics: [u8; GRAPHICS_COUNT],
+    pub attribute: [u8; ATTRIBUTE_COUNT],
+}
+
+impl RegisterState {
+    /// Reads the current state of the registers
+    pub fn save() -> Self {
+        let mut state = RegisterState {
+            misc: read_misc(),
+            sequencer: [0; SEQUENCER_COUNT],
+            crtc: [0; CRTC_COUNT],
+            graphics: [0; GRAPHICS_COUNT],
+            attribute: [0; ATTRIBUTE_COUNT],
+        };
+
+        for (index, value) in state.sequencer.iter_mut().enumerate() {
+            *value = read_sequencer_index(index as u8);
+        }
+        for (index, value) in state.crtc.iter_mut().enumerate() {
+            *value = crtc::read_index(index as u8);
+        }
+        for (index, value) in state.graphics.iter_mut().enumerate() {
+            *value = read_graphics_index(index as u8);
+        }
+        for (index, value) in state.attribute.iter_mut().enumerate() {
+            *value = read_attribute(index as u8);
+        }
+        enable_palette();
+
+        state
+    }
+
+    /// Writes every register. Interrupts are disabled meanwhile, so that the timer interrupt doesn't
+    /// draw the status bar while the registers are only partly written.
+    pub fn restore(&self) {
+        cpu::without_interrupts(|| self.write());
+    }
+
+    fn write(&self) {
+        write_misc(self.misc);
+
+        for (index, &value) in self.sequencer.iter().enumerate() {
+            write_sequencer_index(index as u8, value);
+        }
+
+        // Unprotect the CRTC registers while writing them, then protect them as saved
+        let vertical_retrace_end = crtc::read_index(CRTC_VERTICAL_RETRACE_END as u8);
+        crtc::write_index(CRTC_VERTICAL_RETRACE_END as u8, vertical_retrace_end & !CRTC_PROTECT_BIT);
+
+        for (index, &value) in self.crtc.iter().enumerate() {
+            let value = if index == CRTC_VERTICAL_RETRACE_END { value & !CRTC_PROTECT_BIT } else { value };
+            crtc::write_index(index as u8, value);
+        }
+        crtc::write_index(CRTC_VERTICAL_RETRACE_END as u8, self.crtc[CRTC_VERTICAL_RETRACE_END]);
+
+        for (index, &value) in self.graphics.iter().enumerate() {
+            write_graphics_index(index as u8, value);
+        }
+        for (index, &value) in self.attribute.iter().enumerate() {
+            write_attribute(index as u8, value);
+        }
+        enable_palette();
+    }
+}
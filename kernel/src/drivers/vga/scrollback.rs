@@ -0,0 +1,291 @@
+//! History of the rows scrolled off the top of a text buffer, which can be scrolled back through
+
+use core::cmp;
+use super::{Color, TextBuffer, VgaChar, CursorShape, ScrollStrategy, TERMINALS, MAX_COLUMNS, MAX_ROWS};
+use super::buffer::BLANK;
+
+/// Amount of screens of history kept unless `vga.scrollback=` says otherwise
+pub const DEFAULT_SCROLLBACK_SCREENS: usize = 4;
+
+/// Most rows of history which can be held, which is 4 screens of the tallest text mode and more of
+/// the others
+pub const HISTORY_ROWS: usize = 4 * MAX_ROWS;
+
+/// Sets how many screens of history every terminal keeps from `vga.scrollback=<screens>` on the
+/// kernel command line
+pub fn configure(command_line: &str) {
+    for arg in command_line.split_whitespace() {
+        if !arg.starts_with("vga.scrollback=") {
+            continue;
+        }
+
+        let value = &arg["vga.scrollback=".len()..];
+        match value.parse() {
+            Ok(screens) => {
+                for terminal in TERMINALS.iter() {
+                    terminal.lock().buffer_mut().set_screens(screens);
+                }
+            }
+            Err(_) => warn!("vga", "invalid amount of scrollback screens `{}`", value),
+        }
+    }
+}
+
+/// Wraps a text buffer, keeping the rows which scroll off its top. While viewing the history the
+/// live screen is kept aside, and any output returns to it.
+pub struct Scrollback<B> {
+    buffer: B,
+    /// Ring of rows, each `MAX_COLUMNS` cells long
+    history: [VgaChar; HISTORY_ROWS * MAX_COLUMNS],
+    /// Row of the ring which the next row scrolled off is stored in
+    head: usize,
+    /// Amount of rows held in the history
+    count: usize,
+    /// Amount of screens of history kept, each as tall as the buffer
+    screens: usize,
+    /// The live screen, kept while viewing the history
+    live: [VgaChar; MAX_COLUMNS * MAX_ROWS],
+    /// Amount of rows the view is scrolled back by, or 0 when showing the live screen
+    offset: usize,
+    /// Position of the live cursor
+    cursor: (usize, usize),
+}
+
+impl<B> Scrollback<B> {
+    pub const fn new(buffer: B) -> Self {
+        Scrollback {
+            buffer: buffer,
+            history: [BLANK; HISTORY_ROWS * MAX_COLUMNS],
+            head: 0,
+            count: 0,
+            screens: DEFAULT_SCROLLBACK_SCREENS,
+            live: [BLANK; MAX_COLUMNS * MAX_ROWS],
+            offset: 0,
+            cursor: (0, 0),
+        }
+    }
+
+    /// Gets the wrapped buffer
+    pub fn buffer(&self) -> &B {
+        &self.buffer
+    }
+
+    /// Gets the wrapped buffer, mutably
+    pub fn buffer_mut(&mut self) -> &mut B {
+        &mut self.buffer
+    }
+
+    /// Gets the amount of rows held in the history
+    #[cfg(test)]
+    pub fn history_len(&self) -> usize {
+        self.count
+    }
+
+    /// Checks if the history is being shown rather than the live screen
+    #[cfg(test)]
+    pub fn is_viewing(&self) -> bool {
+        self.offset != 0
+    }
+
+    /// Gets the amount of rows the view is scrolled back by
+    #[cfg(test)]
+    pub fn view_offset(&self) -> usize {
+        self.offset
+    }
+}
+
+impl<B: TextBuffer> Scrollback<B> {
+    /// Sets how many screens of history are kept, each as tall as the buffer is now or after it's
+    /// resized. No more than `HISTORY_ROWS` rows are kept, however many screens that is. Rows
+    /// beyond the new size are dropped.
+    pub fn set_screens(&mut self, screens: usize) {
+        self.return_to_live();
+
+        self.screens = screens;
+        self.count = cmp::min(self.count, self.capacity());
+    }
+
+    /// Gets the amount of rows held before the oldest are dropped
+    fn capacity(&self) -> usize {
+        cmp::min(self.screens.saturating_mul(self.buffer.height()), HISTORY_ROWS)
+    }
+
+    /// Scrolls the view back through the history by the given amount of rows
+    pub fn view_up(&mut self, rows: usize) {
+        if self.count == 0 {
+            return;
+        }
+
+        if self.offset == 0 {
+            self.save_live();
+        }
+
+        self.offset = cmp::min(self.offset + rows, self.count);
+        self.redraw();
+    }
+
+    /// Scrolls the view forward by the given amount of rows, returning to the live screen at the
+    /// end of the history
+    pub fn view_down(&mut self, rows: usize) {
+        if self.offset == 0 {
+            return;
+        }
+
+        if rows >= self.offset {
+            self.return_to_live();
+        } else {
+            self.offset -= rows;
+            self.redraw();
+        }
+    }
+
+    /// Shows the live screen again, if viewing the history
+    pub fn return_to_live(&mut self) {
+        if self.offset == 0 {
+            return;
+        }
+        self.offset = 0;
+
+        let width = self.width();
+        for row in 0..self.buffer.height() {
+            for column in 0..width {
+                self.buffer.set_char(row, column, self.live[row * MAX_COLUMNS + column]);
+            }
+        }
+
+        let (row, column) = self.cursor;
+        self.buffer.set_cursor(row, column);
+        self.buffer.flush();
+    }
+
+    /// Copies the live screen aside before the view covers it
+    fn save_live(&mut self) {
+        let width = self.width();
+        for row in 0..self.buffer.height() {
+            for column in 0..width {
+                self.live[row * MAX_COLUMNS + column] = self.buffer.get_char(row, column);
+            }
+        }
+    }
+
+    /// Draws the history rows above the top of the live screen, shifted down by the offset
+    fn redraw(&mut self) {
+        let width = self.width();
+
+        for row in 0..self.buffer.height() {
+            for column in 0..width {
+                let value = if row < self.offset {
+                    let start = self.history_row(self.count - self.offset + row);
+                    self.history[start + column]
+                } else {
+                    self.live[(row - self.offset) * MAX_COLUMNS + column]
+                };
+
+                self.buffer.set_char(row, column, value);
+            }
+        }
+
+        self.buffer.flush();
+    }
+
+    /// Gets the index of the first cell of a row of history, counting from the oldest
+    fn history_row(&self, index: usize) -> usize {
+        (self.head + HISTORY_ROWS - self.count + index) % HISTORY_ROWS * MAX_COLUMNS
+    }
+
+    /// Stores a row of the buffer as the newest row of history
+    fn push_row(&mut self, row: usize) {
+        if self.capacity() == 0 {
+            return;
+        }
+
+        // The rest of the row is blanked, in case the buffer is widened later
+        let start = self.head * MAX_COLUMNS;
+        for column in 0..MAX_COLUMNS {
+            self.history[start + column] = if column < self.buffer.width() {
+                self.buffer.get_char(row, column)
+            } else {
+                BLANK
+            };
+        }
+
+        self.head = (self.head + 1) % HISTORY_ROWS;
+        self.count = cmp::min(self.count + 1, self.capacity());
+    }
+}
+
+impl<B: TextBuffer> TextBuffer for Scrollback<B> {
+    fn width(&self) -> usize {
+        self.buffer.width()
+    }
+
+    fn height(&self) -> usize {
+        self.buffer.height()
+    }
+
+    fn set_char(&mut self, row: usize, column: usize, value: VgaChar) {
+        self.return_to_live();
+        self.buffer.set_char(row, column, value);
+    }
+
+    fn get_char(&self, row: usize, column: usize) -> VgaChar {
+        if self.offset != 0 {
+            self.live[row * MAX_COLUMNS + column]
+        } else {
+            self.buffer.get_char(row, column)
+        }
+    }
+
+    /// Changes the size of the live screen. The history is kept and shown at the new width, but the
+    /// oldest rows are dropped if its screens now hold fewer rows.
+    fn resize(&mut self, width: usize, height: usize) {
+        self.return_to_live();
+        self.buffer.resize(width, height);
+        self.count = cmp::min(self.count, self.capacity());
+    }
+
+    fn scroll_down(&mut self, amount: usize, background_color: Color) {
+        self.return_to_live();
+
+        for row in 0..cmp::min(amount, self.buffer.height()) {
+            self.push_row(row);
+        }
+        self.buffer.scroll_down(amount, background_color);
+    }
+
+    fn scroll_rows(&mut self, top: usize, bottom: usize, amount: usize, background_color: Color) {
+        self.return_to_live();
+
+        // Only rows leaving the top of the screen go into the history
+        if top == 0 {
+            for row in 0..cmp::min(amount, bottom) {
+                self.push_row(row);
+            }
+        }
+        self.buffer.scroll_rows(top, bottom, amount, background_color);
+    }
+
+    fn set_cursor(&mut self, row: usize, column: usize) {
+        self.cursor = (row, column);
+
+        if self.offset == 0 {
+            self.buffer.set_cursor(row, column);
+        }
+    }
+
+    fn set_cursor_shape(&mut self, shape: CursorShape) {
+        self.buffer.set_cursor_shape(shape);
+    }
+
+    fn set_scroll_strategy(&mut self, strategy: ScrollStrategy) {
+        self.buffer.set_scroll_strategy(strategy);
+    }
+
+    fn scroll_strategy(&self) -> ScrollStrategy {
+        self.buffer.scroll_strategy()
+    }
+
+    fn flush(&mut self) {
+        self.buffer.flush();
+    }
+}
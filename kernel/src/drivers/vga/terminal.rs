@@ -0,0 +1,477 @@
+//! Virtual terminals, each drawing off screen with only the active one mirrored to VGA memory.
+//! Changes are copied to the screen on `flush`, so only the cells which changed are written.
+
+use core::{cmp, fmt};
+use core::sync::atomic::{AtomicUsize, Ordering};
+use spin::Mutex;
+use super::{Color, VgaColor, VgaChar, TextBuffer, VgaBuffer, MemoryBuffer, Scrollback, VgaWriter,
+            CursorShape, DirtyRows, ScrollStrategy, MAX_COLUMNS};
+use super::buffer::BLANK;
+use super::{cp437, mode};
+
+/// Amount of virtual terminals, switched between with Alt+F1 and on
+pub const TERMINAL_COUNT: usize = 6;
+
+/// Terminal which kernel output is written to
+pub const LOG_TERMINAL: usize = 0;
+
+const COLOR: VgaColor = VgaColor::new(Color::White, Color::Black);
+
+/// Most rows which can be kept out of a terminal at the top and bottom of the screen together
+pub const MAX_FIXED_ROWS: usize = 4;
+
+/// A virtual terminal's writer, with its own cells, cursor, color and scrollback
+pub type Terminal = VgaWriter<Scrollback<TerminalBuffer>>;
+
+pub static TERMINALS: [Mutex<Terminal>; TERMINAL_COUNT] = [
+    Mutex::new(VgaWriter::new(COLOR, true)),
+    Mutex::new(VgaWriter::new(COLOR, false)),
+    Mutex::new(VgaWriter::new(COLOR, false)),
+    Mutex::new(VgaWriter::new(COLOR, false)),
+    Mutex::new(VgaWriter::new(COLOR, false)),
+    Mutex::new(VgaWriter::new(COLOR, false)),
+];
+
+/// Index of the terminal shown on screen
+static ACTIVE: AtomicUsize = AtomicUsize::new(0);
+
+/// Gets the index of the terminal shown on screen
+pub fn active() -> usize {
+    ACTIVE.load(Ordering::SeqCst)
+}
+
+/// Sets how every terminal scrolls from `vga.scroll=<copy|hardware>` on the kernel command line
+pub fn configure(command_line: &str) {
+    for arg in command_line.split_whitespace() {
+        if !arg.starts_with("vga.scroll=") {
+            continue;
+        }
+
+        let strategy = match &arg["vga.scroll=".len()..] {
+            "copy" => ScrollStrategy::Copy,
+            "hardware" => ScrollStrategy::Hardware,
+            name => {
+                warn!("vga", "unknown scroll strategy `{}`", name);
+                continue;
+            }
+        };
+
+        for terminal in TERMINALS.iter() {
+            terminal.lock().set_scroll_strategy(strategy);
+        }
+    }
+}
+
+/// Shows the terminal with the given index on screen. Indices out of range are ignored. While a
+/// graphics mode has the screen, the terminal is only chosen, and is shown once text mode resumes.
+pub fn switch_to(index: usize) {
+    if index >= TERMINAL_COUNT {
+        return;
+    }
+
+    let previous = ACTIVE.swap(index, Ordering::SeqCst);
+    if previous == index || !mode::is_text() {
+        return;
+    }
+
+    TERMINALS[previous].lock().buffer_mut().buffer_mut().set_active(false);
+    TERMINALS[index].lock().buffer_mut().buffer_mut().set_active(true);
+}
+
+/// Shows the terminal with the given index on screen, even if the terminals are held by code
+/// that will never run again
+pub unsafe fn force_switch_to(index: usize) {
+    for terminal in TERMINALS.iter() {
+        terminal.force_unlock();
+    }
+
+    switch_to(index);
+}
+
+/// Stops the active terminal drawing to the screen so that something else can be shown over it,
+/// after redrawing it from the start of video memory. Its output is kept off screen until
+/// `release_screen`. Gives false without holding it if the screen isn't in text mode.
+pub fn hold_screen() -> bool {
+    if !mode::is_text() {
+        return false;
+    }
+
+    let mut terminal = TERMINALS[active()].lock();
+    let buffer = terminal.buffer_mut().buffer_mut();
+    buffer.redraw();
+    buffer.set_active(false);
+    true
+}
+
+/// Redraws the active terminal over whatever was shown while the screen was held
+pub fn release_screen() {
+    TERMINALS[active()].lock().buffer_mut().buffer_mut().set_active(true);
+}
+
+/// A terminal's cells, kept in memory and mirrored to VGA memory while the terminal is active.
+/// Fixed rows at the top and bottom of the screen may be kept out of the terminal, such as for a
+/// status bar, so that only the rows between them are written to and scroll.
+pub struct TerminalBuffer {
+    /// Cells of the rows between the fixed rows
+    cells: MemoryBuffer,
+    /// Cells of the fixed rows, the top ones followed by the bottom ones
+    fixed: [[VgaChar; MAX_COLUMNS]; MAX_FIXED_ROWS],
+    /// Amount of fixed rows at the top and bottom of the screen
+    fixed_rows: (usize, usize),
+    /// Cell of the screen the mouse pointer is on, shown inverted
+    pointer: Option<(usize, usize)>,
+    /// First and last selected cells of the terminal, shown inverted
+    selection: Option<((usize, usize), (usize, usize))>,
+    screen: VgaBuffer,
+    /// Cells changed since the screen was last flushed
+    dirty: DirtyRows,
+    cursor_shape: CursorShape,
+    active: bool,
+}
+
+impl TerminalBuffer {
+    pub const fn new(active: bool) -> Self {
+        TerminalBuffer {
+            cells: MemoryBuffer::new(),
+            fixed: [[BLANK; MAX_COLUMNS]; MAX_FIXED_ROWS],
+            fixed_rows: (0, 0),
+            pointer: None,
+            selection: None,
+            screen: VgaBuffer::new(),
+            // Whatever is on screen before the first flush is replaced
+            dirty: DirtyRows::all(),
+            cursor_shape: CursorShape::Underline,
+            active: active,
+        }
+    }
+
+    /// Checks if this terminal is shown
+    #[cfg(feature = "kernel-test")]
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// Sets whether this terminal is shown. Becoming active redraws the whole screen.
+    pub fn set_active(&mut self, active: bool) {
+        self.active = active;
+
+        if active {
+            self.redraw();
+        }
+    }
+
+    /// Gets the size of the whole screen, including the fixed rows, as `(width, height)`
+    pub fn screen_size(&self) -> (usize, usize) {
+        (self.screen.width(), self.screen.height())
+    }
+
+    /// Gets the amount of fixed rows at the top and bottom of the screen
+    #[cfg(test)]
+    pub fn fixed_rows(&self) -> (usize, usize) {
+        self.fixed_rows
+    }
+
+    /// Keeps the given amounts of rows at the top and bottom of the screen out of the terminal,
+    /// blanking them. Together they're cut down to `MAX_FIXED_ROWS`. The terminal's rows shrink or
+    /// grow to fit, so its writer should be resized after, as `Terminal::set_fixed_rows` does.
+    /// With hardware scrolling, rows fixed at the bottom are split off the screen so that they
+    /// stay put, but while any rows are fixed at the top the rows are copied instead, as the
+    /// screen can only be split at the bottom.
+    pub fn set_fixed_rows(&mut self, top: usize, bottom: usize) {
+        let top = cmp::min(top, MAX_FIXED_ROWS);
+        let bottom = cmp::min(bottom, MAX_FIXED_ROWS - top);
+
+        self.fixed = [[BLANK; MAX_COLUMNS]; MAX_FIXED_ROWS];
+        self.fixed_rows = (top, bottom);
+        self.split_fixed_rows();
+
+        let (width, height) = self.screen_size();
+        self.resize(width, height);
+    }
+
+    /// Sets a cell of a fixed row, by its row on screen. Cells which aren't in a fixed row are
+    /// ignored.
+    pub fn set_fixed_char(&mut self, row: usize, column: usize, value: VgaChar) {
+        if column >= self.screen.width() {
+            return;
+        }
+
+        if let Some(index) = self.fixed_index(row) {
+            if self.fixed[index][column] != value {
+                self.fixed[index][column] = value;
+                self.dirty.mark_cell(row, column);
+            }
+        }
+    }
+
+    /// Gets a cell of a fixed row, by its row on screen, or `None` if it isn't in a fixed row
+    #[cfg(feature = "kernel-test")]
+    pub fn get_fixed_char(&self, row: usize, column: usize) -> Option<VgaChar> {
+        match self.fixed_index(row) {
+            Some(index) if column < self.screen.width() => Some(self.fixed[index][column]),
+            _ => None,
+        }
+    }
+
+    /// Gets the cell of the screen the mouse pointer is on
+    #[cfg(feature = "kernel-test")]
+    pub fn pointer(&self) -> Option<(usize, usize)> {
+        self.pointer
+    }
+
+    /// Shows the mouse pointer on a cell of the screen by inverting its colors, or hides it
+    pub fn set_pointer(&mut self, pointer: Option<(usize, usize)>) {
+        let pointer = pointer.map(|(row, column)| {
+            (cmp::min(row, self.screen.height() - 1), cmp::min(column, self.screen.width() - 1))
+        });
+
+        for &cell in [self.pointer, pointer].iter() {
+            if let Some((row, column)) = cell {
+                self.dirty.mark_cell(row, column);
+            }
+        }
+        self.pointer = pointer;
+    }
+
+    /// Gets the first and last selected cells of the terminal
+    #[cfg(feature = "kernel-test")]
+    pub fn selection(&self) -> Option<((usize, usize), (usize, usize))> {
+        self.selection
+    }
+
+    /// Selects the cells from one cell of the screen to another, in either order, or clears the
+    /// selection. Cells in fixed rows select the nearest row of the terminal.
+    pub fn select(&mut self, selection: Option<((usize, usize), (usize, usize))>) {
+        let selection = selection.map(|(from, to)| {
+            let (from, to) = (self.terminal_cell(from), self.terminal_cell(to));
+            (cmp::min(from, to), cmp::max(from, to))
+        });
+
+        let (top, width) = (self.fixed_rows.0, self.cells.width());
+        for &span in [self.selection, selection].iter() {
+            if let Some(((first, _), (last, _))) = span {
+                self.dirty.mark_rows(top + first, top + last + 1, width);
+            }
+        }
+        self.selection = selection;
+    }
+
+    /// Writes the selected text, with a line break between rows and the blanks at the end of each
+    /// row left out
+    pub fn write_selection<W: fmt::Write>(&self, out: &mut W) -> fmt::Result {
+        let ((first_row, first_column), (last_row, last_column)) = match self.selection {
+            Some(selection) => selection,
+            None => return Ok(()),
+        };
+
+        for row in first_row..last_row + 1 {
+            let start = if row == first_row { first_column } else { 0 };
+            let end = if row == last_row { last_column + 1 } else { self.cells.width() };
+            let end = (start..end).rev()
+                .find(|&column| self.cells.get_char(row, column).character != b' ')
+                .map_or(start, |column| column + 1);
+
+            for column in start..end {
+                out.write_char(cp437::decode(self.cells.get_char(row, column).character))?;
+            }
+            if row != last_row {
+                out.write_char('\n')?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Gets the cell of the terminal nearest to a cell of the screen
+    fn terminal_cell(&self, (row, column): (usize, usize)) -> (usize, usize) {
+        let row = cmp::min(row.saturating_sub(self.fixed_rows.0), self.cells.height() - 1);
+        (row, cmp::min(column, self.cells.width() - 1))
+    }
+
+    /// Checks if a cell of the screen is shown inverted, for being under the pointer or selected
+    /// but not both
+    fn is_highlighted(&self, row: usize, column: usize) -> bool {
+        let selected = self.fixed_index(row).is_none() && self.selection.map_or(false, |(first, last)| {
+            let cell = (row - self.fixed_rows.0, column);
+            first <= cell && cell <= last
+        });
+
+        selected != (self.pointer == Some((row, column)))
+    }
+
+    /// Gets the index in `fixed` of a row on screen, if it's a fixed row
+    fn fixed_index(&self, row: usize) -> Option<usize> {
+        let (top, bottom) = self.fixed_rows;
+        let first_bottom = top + self.cells.height();
+
+        if row < top {
+            Some(row)
+        } else if row >= first_bottom && row < first_bottom + bottom {
+            Some(top + row - first_bottom)
+        } else {
+            None
+        }
+    }
+
+    /// Gets the cell shown at a row and column of the screen
+    fn screen_char(&self, row: usize, column: usize) -> VgaChar {
+        let mut value = match self.fixed_index(row) {
+            Some(index) => self.fixed[index][column],
+            None => self.cells.get_char(row - self.fixed_rows.0, column),
+        };
+
+        if self.is_highlighted(row, column) {
+            value.color = value.color.inverted();
+        }
+        value
+    }
+
+    /// Splits the rows fixed at the bottom off the screen if they can scroll in hardware, or joins
+    /// them back
+    fn split_fixed_rows(&mut self) {
+        let hardware = self.screen.scroll_strategy() == ScrollStrategy::Hardware;
+        let split = if hardware && self.fixed_rows.0 == 0 { self.fixed_rows.1 } else { 0 };
+        self.screen.set_split(split);
+    }
+
+    /// Moves the screen's cursor to the terminal's cursor, below the fixed rows at the top
+    fn show_cursor(&mut self) {
+        let (row, column) = self.cells.cursor();
+        self.screen.set_cursor(row + self.fixed_rows.0, column);
+    }
+
+    /// Copies every cell and the cursor to the screen, showing it from the start of video memory
+    pub fn redraw(&mut self) {
+        // Another terminal may have scrolled the screen's start address
+        self.screen.reset_origin();
+
+        for row in 0..self.screen.height() {
+            for column in 0..self.screen.width() {
+                let value = self.screen_char(row, column);
+                self.screen.set_char(row, column, value);
+            }
+        }
+
+        self.show_cursor();
+        self.screen.set_cursor_shape(self.cursor_shape);
+        self.dirty.clear();
+    }
+}
+
+impl TextBuffer for TerminalBuffer {
+    fn width(&self) -> usize {
+        self.cells.width()
+    }
+
+    fn height(&self) -> usize {
+        self.cells.height()
+    }
+
+    fn set_char(&mut self, row: usize, column: usize, value: VgaChar) {
+        if self.cells.get_char(row, column) != value {
+            self.cells.set_char(row, column, value);
+            self.dirty.mark_cell(self.fixed_rows.0 + row, column);
+        }
+    }
+
+    fn get_char(&self, row: usize, column: usize) -> VgaChar {
+        self.cells.get_char(row, column)
+    }
+
+    /// Changes the size of the screen, which the terminal gets all but the fixed rows of. If the
+    /// cursor's row no longer fits, rows are scrolled off the top to keep it on screen.
+    fn resize(&mut self, width: usize, height: usize) {
+        self.screen.resize(width, height);
+
+        let (top, bottom) = self.fixed_rows;
+        let height = cmp::max(self.screen.height().saturating_sub(top + bottom), 1);
+
+        let row = self.cells.cursor().0;
+        if row >= height {
+            self.cells.scroll_down(row + 1 - height, Color::Black);
+            let column = self.cells.cursor().1;
+            self.cells.set_cursor(height - 1, column);
+        }
+        self.cells.resize(width, height);
+        self.selection = None;
+        self.pointer = None;
+
+        if self.active {
+            self.redraw();
+        }
+    }
+
+    /// Scrolls the cells, clearing the selection. With hardware scrolling, no rows fixed at the top
+    /// and no pointer, the screen is brought up to date and scrolled along with them, so that the
+    /// rows don't have to be rewritten.
+    fn scroll_down(&mut self, amount: usize, background_color: Color) {
+        self.select(None);
+
+        let hardware = self.screen.scroll_strategy() == ScrollStrategy::Hardware;
+        if self.active && hardware && self.fixed_rows.0 == 0 && self.pointer.is_none() {
+            self.flush();
+            self.cells.scroll_down(amount, background_color);
+            self.screen.scroll_down(amount, background_color);
+            return;
+        }
+
+        self.cells.scroll_down(amount, background_color);
+
+        let (width, height, top) = (self.cells.width(), self.cells.height(), self.fixed_rows.0);
+        self.dirty.mark_rows(top, top + height, width);
+    }
+
+    fn scroll_rows(&mut self, top: usize, bottom: usize, amount: usize, background_color: Color) {
+        self.select(None);
+        self.cells.scroll_rows(top, bottom, amount, background_color);
+
+        let (width, fixed_top) = (self.cells.width(), self.fixed_rows.0);
+        self.dirty.mark_rows(fixed_top + top, fixed_top + bottom, width);
+    }
+
+    fn set_cursor(&mut self, row: usize, column: usize) {
+        self.cells.set_cursor(row, column);
+        self.dirty.mark_cursor();
+    }
+
+    fn set_scroll_strategy(&mut self, strategy: ScrollStrategy) {
+        self.screen.set_scroll_strategy(strategy);
+        self.split_fixed_rows();
+
+        if self.active {
+            self.redraw();
+        }
+    }
+
+    fn scroll_strategy(&self) -> ScrollStrategy {
+        self.screen.scroll_strategy()
+    }
+
+    fn set_cursor_shape(&mut self, shape: CursorShape) {
+        self.cursor_shape = shape;
+
+        if self.active {
+            self.screen.set_cursor_shape(shape);
+        }
+    }
+
+    /// Writes the changed cells and the cursor to the screen, if this terminal is active
+    fn flush(&mut self) {
+        if !self.active {
+            return;
+        }
+
+        for row in 0..self.screen.height() {
+            if let Some((start, end)) = self.dirty.take_row(row) {
+                for column in start..cmp::min(end, self.screen.width()) {
+                    let value = self.screen_char(row, column);
+                    self.screen.set_char(row, column, value);
+                }
+            }
+        }
+
+        if self.dirty.take_cursor() {
+            self.show_cursor();
+        }
+    }
+}
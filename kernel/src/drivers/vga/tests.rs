@@ -0,0 +1,779 @@
+use core::sync::atomic::{AtomicUsize, Ordering};
+use core::{cmp, str};
+use std::string::String;
+use spin::Mutex;
+use super::*;
+
+const COLOR: VgaColor = VgaColor::new(Color::White, Color::Black);
+const HIGHLIGHT: VgaColor = VgaColor::new(Color::Yellow, Color::Blue);
+
+/// Creates a writer over a blank in-memory buffer
+fn writer() -> VgaWriter<MemoryBuffer> {
+    VgaWriter::with_buffer(MemoryBuffer::new(), COLOR)
+}
+
+#[test]
+fn write_char_colored() {
+    let mut writer = writer();
+    assert!(writer.write_char_colored('a', HIGHLIGHT).is_ok());
+
+    assert_eq!(writer.buffer().get_char(0, 0), VgaChar::new(HIGHLIGHT, 'a'));
+    assert_eq!(writer.cursor_pos(), (0, 1));
+}
+
+#[test]
+fn write_char_uses_writer_color() {
+    let mut writer = writer();
+    writer.set_color(HIGHLIGHT);
+    assert!(writer.write_char('a').is_ok());
+
+    assert_eq!(writer.buffer().get_char(0, 0), VgaChar::new(HIGHLIGHT, 'a'));
+}
+
+#[test]
+fn wrap_at_end_of_row() {
+    let mut writer = writer();
+    for _ in 0..RESOLUTION_X {
+        assert!(writer.write_char('-').is_ok());
+    }
+    assert_eq!(writer.cursor_pos(), (0, RESOLUTION_X));
+
+    assert!(writer.write_char('a').is_ok());
+    assert_eq!(writer.buffer().get_char(1, 0), VgaChar::new(COLOR, 'a'));
+    assert_eq!(writer.cursor_pos(), (1, 1));
+}
+
+#[test]
+fn new_line() {
+    let mut writer = writer();
+    assert!(writer.write_str("ab").is_ok());
+    assert!(writer.new_line().is_ok());
+    assert_eq!(writer.cursor_pos(), (1, 0));
+
+    assert!(writer.write_str("c\nd").is_ok());
+    assert_eq!(writer.buffer().get_char(1, 0), VgaChar::new(COLOR, 'c'));
+    assert_eq!(writer.buffer().get_char(2, 0), VgaChar::new(COLOR, 'd'));
+    assert_eq!(writer.cursor_pos(), (2, 1));
+}
+
+#[test]
+fn new_line_scrolls_at_bottom() {
+    let mut writer = writer();
+    writer.set_cursor_pos((RESOLUTION_Y - 1, 0));
+    assert!(writer.write_str("a\n").is_ok());
+
+    assert_eq!(writer.buffer().get_char(RESOLUTION_Y - 2, 0), VgaChar::new(COLOR, 'a'));
+    assert_eq!(writer.buffer().get_char(RESOLUTION_Y - 1, 0), buffer::BLANK);
+    assert_eq!(writer.cursor_pos(), (RESOLUTION_Y - 1, 0));
+}
+
+#[test]
+fn backspace_char() {
+    let mut writer = writer();
+    assert!(writer.write_str("ab").is_ok());
+    assert!(writer.backspace_char().is_ok());
+
+    assert_eq!(writer.buffer().get_char(0, 0), VgaChar::new(COLOR, 'a'));
+    assert_eq!(writer.buffer().get_char(0, 1), VgaChar::new(COLOR, ' '));
+    assert_eq!(writer.cursor_pos(), (0, 1));
+}
+
+#[test]
+fn backspace_across_rows() {
+    let mut writer = writer();
+    writer.set_char(0, RESOLUTION_X - 1, 'a');
+    writer.set_cursor_pos((1, 0));
+    assert!(writer.write_char('\x08').is_ok());
+
+    assert_eq!(writer.buffer().get_char(0, RESOLUTION_X - 1), VgaChar::new(COLOR, ' '));
+    assert_eq!(writer.cursor_pos(), (0, RESOLUTION_X - 1));
+}
+
+#[test]
+fn backspace_at_origin() {
+    let mut writer = writer();
+    assert!(writer.backspace_char().is_ok());
+
+    assert_eq!(writer.cursor_pos(), (0, 0));
+}
+
+#[test]
+fn scroll_down() {
+    let mut buffer = MemoryBuffer::new();
+    buffer.set_char(1, 0, VgaChar::new(COLOR, 'a'));
+    buffer.set_char(2, 5, VgaChar::new(COLOR, 'b'));
+    buffer.scroll_down(1, Color::Blue);
+
+    let blank = VgaChar::new(VgaColor::new(Color::Black, Color::Blue), ' ');
+    assert_eq!(buffer.get_char(0, 0), VgaChar::new(COLOR, 'a'));
+    assert_eq!(buffer.get_char(1, 5), VgaChar::new(COLOR, 'b'));
+    assert_eq!(buffer.get_char(RESOLUTION_Y - 1, 0), blank);
+    assert_eq!(buffer.get_char(RESOLUTION_Y - 1, RESOLUTION_X - 1), blank);
+}
+
+#[test]
+fn scroll_down_clears_everything() {
+    let mut buffer = MemoryBuffer::new();
+    buffer.set_char(0, 0, VgaChar::new(COLOR, 'a'));
+    buffer.scroll_down(RESOLUTION_Y + 5, Color::Red);
+
+    let blank = VgaChar::new(VgaColor::new(Color::Black, Color::Red), ' ');
+    for row in 0..RESOLUTION_Y {
+        assert_eq!(buffer.get_char(row, 0), blank);
+    }
+}
+
+#[test]
+fn fill_screen() {
+    let mut writer = writer();
+    assert!(writer.write_str("abc").is_ok());
+    writer.fill_screen(Color::Green);
+
+    let blank = VgaChar::new(VgaColor::new(Color::Black, Color::Green), ' ');
+    for row in 0..RESOLUTION_Y {
+        for column in 0..RESOLUTION_X {
+            assert_eq!(writer.buffer().get_char(row, column), blank);
+        }
+    }
+}
+
+#[test]
+fn cursor_follows_writes() {
+    let mut writer = writer();
+    assert!(writer.write_str("ab\nc").is_ok());
+    assert_eq!(writer.buffer().cursor(), (1, 1));
+
+    writer.set_cursor_pos((3, 4));
+    assert_eq!(writer.buffer().cursor(), (3, 4));
+}
+
+#[test]
+fn cursor_stays_on_row_before_wrap() {
+    let mut writer = writer();
+    for _ in 0..RESOLUTION_X {
+        assert!(writer.write_char('-').is_ok());
+    }
+
+    assert_eq!(writer.buffer().cursor(), (0, RESOLUTION_X - 1));
+}
+
+#[test]
+fn cursor_follows_backspace() {
+    let mut writer = writer();
+    assert!(writer.write_str("ab").is_ok());
+    assert!(writer.backspace_char().is_ok());
+
+    assert_eq!(writer.buffer().cursor(), (0, 1));
+}
+
+#[test]
+fn ansi_foreground_and_background() {
+    let mut writer = writer();
+    assert!(writer.write_str("\x1b[33;44ma").is_ok());
+
+    assert_eq!(writer.buffer().get_char(0, 0), VgaChar::new(VgaColor::new(Color::Brown, Color::Blue), 'a'));
+    assert_eq!(writer.cursor_pos(), (0, 1));
+}
+
+#[test]
+fn ansi_bright_colors() {
+    let mut writer = writer();
+    assert!(writer.write_str("\x1b[93;104ma\x1b[0;1;31mb").is_ok());
+
+    assert_eq!(writer.buffer().get_char(0, 0), VgaChar::new(VgaColor::new(Color::Yellow, Color::LightBlue), 'a'));
+    assert_eq!(writer.buffer().get_char(0, 1), VgaChar::new(VgaColor::new(Color::LightRed, Color::Black), 'b'));
+}
+
+#[test]
+fn ansi_normal_intensity_only_undoes_bold() {
+    let mut writer = writer();
+    assert!(writer.write_str("\x1b[93;22ma\x1b[33;1;22mb").is_ok());
+
+    assert_eq!(writer.buffer().get_char(0, 0), VgaChar::new(VgaColor::new(Color::Yellow, Color::Black), 'a'));
+    assert_eq!(writer.buffer().get_char(0, 1), VgaChar::new(VgaColor::new(Color::Brown, Color::Black), 'b'));
+}
+
+#[test]
+fn ansi_reset() {
+    let mut writer = writer();
+    assert!(writer.write_str("\x1b[31;7ma\x1b[mb").is_ok());
+
+    assert_eq!(writer.buffer().get_char(0, 0), VgaChar::new(VgaColor::new(Color::Black, Color::Red), 'a'));
+    assert_eq!(writer.buffer().get_char(0, 1), VgaChar::new(COLOR, 'b'));
+}
+
+#[test]
+fn ansi_cursor_position() {
+    let mut writer = writer();
+    assert!(writer.write_str("\x1b[5;10Ha").is_ok());
+    assert_eq!(writer.buffer().get_char(4, 9), VgaChar::new(COLOR, 'a'));
+
+    assert!(writer.write_str("\x1b[H").is_ok());
+    assert_eq!(writer.cursor_pos(), (0, 0));
+    assert_eq!(writer.buffer().cursor(), (0, 0));
+}
+
+#[test]
+fn ansi_cursor_movement_clamped() {
+    let mut writer = writer();
+    assert!(writer.write_str("\x1b[3B\x1b[4C").is_ok());
+    assert_eq!(writer.cursor_pos(), (3, 4));
+
+    assert!(writer.write_str("\x1b[A\x1b[2D").is_ok());
+    assert_eq!(writer.cursor_pos(), (2, 2));
+
+    assert!(writer.write_str("\x1b[99A\x1b[999C").is_ok());
+    assert_eq!(writer.cursor_pos(), (0, RESOLUTION_X - 1));
+}
+
+#[test]
+fn ansi_erase_in_line() {
+    let mut writer = writer();
+    assert!(writer.write_str("abcd\x1b[3G\x1b[K").is_ok());
+
+    assert_eq!(writer.buffer().get_char(0, 1), VgaChar::new(COLOR, 'b'));
+    assert_eq!(writer.buffer().get_char(0, 2), VgaChar::new(COLOR, ' '));
+    assert_eq!(writer.buffer().get_char(0, 3), VgaChar::new(COLOR, ' '));
+
+    assert!(writer.write_str("\x1b[1K").is_ok());
+    assert_eq!(writer.buffer().get_char(0, 0), VgaChar::new(COLOR, ' '));
+    assert_eq!(writer.cursor_pos(), (0, 2));
+}
+
+#[test]
+fn ansi_erase_in_display() {
+    let mut writer = writer();
+    assert!(writer.write_str("a\nb\nc\x1b[2;1H\x1b[J").is_ok());
+
+    assert_eq!(writer.buffer().get_char(0, 0), VgaChar::new(COLOR, 'a'));
+    assert_eq!(writer.buffer().get_char(1, 0), VgaChar::new(COLOR, ' '));
+    assert_eq!(writer.buffer().get_char(2, 0), VgaChar::new(COLOR, ' '));
+
+    assert!(writer.write_str("\x1b[44m\x1b[2J").is_ok());
+    let blank = VgaChar::new(VgaColor::new(Color::White, Color::Blue), ' ');
+    assert_eq!(writer.buffer().get_char(0, 0), blank);
+    assert_eq!(writer.buffer().get_char(RESOLUTION_Y - 1, RESOLUTION_X - 1), blank);
+}
+
+#[test]
+fn ansi_save_restore_cursor() {
+    let mut writer = writer();
+    assert!(writer.write_str("ab\x1b[s\x1b[10;10H\x1b[uc").is_ok());
+    assert_eq!(writer.buffer().get_char(0, 2), VgaChar::new(COLOR, 'c'));
+
+    assert!(writer.write_str("\x1b7\nd\x1b8e").is_ok());
+    assert_eq!(writer.buffer().get_char(0, 3), VgaChar::new(COLOR, 'e'));
+    assert_eq!(writer.cursor_pos(), (0, 4));
+}
+
+#[test]
+fn ansi_scroll_region() {
+    let mut writer = writer();
+    writer.set_char(0, 0, 'x');
+    writer.set_char(RESOLUTION_Y - 1, 0, 'y');
+
+    // Rows 2 to 4, 1-indexed
+    assert!(writer.write_str("\x1b[2;4r").is_ok());
+    assert_eq!(writer.cursor_pos(), (0, 0));
+
+    assert!(writer.write_str("\x1b[2;1Ha\nb\nc\nd").is_ok());
+    assert_eq!(writer.buffer().get_char(1, 0), VgaChar::new(COLOR, 'b'));
+    assert_eq!(writer.buffer().get_char(2, 0), VgaChar::new(COLOR, 'c'));
+    assert_eq!(writer.buffer().get_char(3, 0), VgaChar::new(COLOR, 'd'));
+    assert_eq!(writer.cursor_pos(), (3, 1));
+
+    // Rows outside of the region are kept
+    assert_eq!(writer.buffer().get_char(0, 0), VgaChar::new(COLOR, 'x'));
+    assert_eq!(writer.buffer().get_char(RESOLUTION_Y - 1, 0), VgaChar::new(COLOR, 'y'));
+}
+
+#[test]
+fn ansi_split_across_writes() {
+    let mut writer = writer();
+    for character in "\x1b[32ma".chars() {
+        assert!(writer.write_char(character).is_ok());
+    }
+
+    assert_eq!(writer.buffer().get_char(0, 0), VgaChar::new(VgaColor::new(Color::Green, Color::Black), 'a'));
+    assert_eq!(writer.cursor_pos(), (0, 1));
+}
+
+#[test]
+fn ansi_colored_write_restores_color() {
+    let mut writer = writer();
+    assert!(writer.write_str_colored("a\x1b[31mb", HIGHLIGHT).is_ok());
+    assert!(writer.write_str("c").is_ok());
+
+    assert_eq!(writer.buffer().get_char(0, 0), VgaChar::new(HIGHLIGHT, 'a'));
+    assert_eq!(writer.buffer().get_char(0, 1), VgaChar::new(VgaColor::new(Color::Red, Color::Blue), 'b'));
+    assert_eq!(writer.buffer().get_char(0, 2), VgaChar::new(COLOR, 'c'));
+}
+
+#[test]
+fn cp437_encode() {
+    assert_eq!(cp437::encode('a'), Some(b'a'));
+    assert_eq!(cp437::encode('é'), Some(0x82));
+    assert_eq!(cp437::encode('£'), Some(0x9C));
+    assert_eq!(cp437::encode('→'), Some(0x1A));
+    assert_eq!(cp437::encode('╔'), Some(0xC9));
+    assert_eq!(cp437::encode('■'), Some(0xFE));
+    assert_eq!(cp437::encode('β'), Some(0xE1));
+    assert_eq!(cp437::encode('€'), None);
+}
+
+#[test]
+fn cp437_round_trip() {
+    for glyph in 0x80..0x100u16 {
+        let glyph = glyph as u8;
+        assert_eq!(cp437::encode(cp437::decode(glyph)), Some(glyph));
+    }
+    for glyph in 0x01..0x20u8 {
+        assert_eq!(cp437::encode(cp437::decode(glyph)), Some(glyph));
+    }
+}
+
+#[test]
+fn writes_box_drawing_and_accents() {
+    let mut writer = writer();
+    assert!(writer.write_str("┌─┐é").is_ok());
+
+    assert_eq!(writer.buffer().get_char(0, 0).character, 0xDA);
+    assert_eq!(writer.buffer().get_char(0, 1).character, 0xC4);
+    assert_eq!(writer.buffer().get_char(0, 2).character, 0xBF);
+    assert_eq!(writer.buffer().get_char(0, 3).character, 0x82);
+    assert_eq!(writer.cursor_pos(), (0, 4));
+}
+
+#[test]
+fn replacement_glyph() {
+    let mut writer = writer();
+    assert!(writer.write_char('€').is_ok());
+    assert_eq!(writer.buffer().get_char(0, 0).character, REPLACEMENT_GLYPH);
+
+    writer.set_replacement_glyph(0xFE);
+    assert!(writer.write_char('€').is_ok());
+    writer.set_char(1, 0, '\u{1F33C}');
+    assert_eq!(writer.buffer().get_char(0, 1).character, 0xFE);
+    assert_eq!(writer.buffer().get_char(1, 0).character, 0xFE);
+}
+
+#[test]
+fn tab_default_stops() {
+    let mut writer = writer();
+    assert!(writer.write_str("a\tb\t\tc").is_ok());
+
+    assert_eq!(writer.buffer().get_char(0, 8), VgaChar::new(COLOR, 'b'));
+    assert_eq!(writer.buffer().get_char(0, 24), VgaChar::new(COLOR, 'c'));
+    assert_eq!(writer.buffer().get_char(0, 1), buffer::BLANK);
+}
+
+#[test]
+fn tab_custom_stops() {
+    let mut writer = writer();
+    writer.set_tab_width(4);
+    assert!(writer.write_str("\ta").is_ok());
+    assert_eq!(writer.cursor_pos(), (0, 5));
+
+    // Without stops left, tabs stop at the last column rather than wrapping
+    writer.clear_tab_stops();
+    writer.set_tab_stop(10);
+    assert!(writer.write_str("\t\t\t").is_ok());
+    assert_eq!(writer.cursor_pos(), (0, RESOLUTION_X - 1));
+}
+
+#[test]
+fn tab_stops_from_escapes() {
+    let mut writer = writer();
+    assert!(writer.write_str("\x1b[3g\x1b[6G\x1bH\r\t").is_ok());
+    assert_eq!(writer.cursor_pos(), (0, 5));
+
+    assert!(writer.write_str("\x1b[g\r\t").is_ok());
+    assert_eq!(writer.cursor_pos(), (0, RESOLUTION_X - 1));
+}
+
+#[test]
+fn carriage_return() {
+    let mut writer = writer();
+    assert!(writer.write_str("abc\rd").is_ok());
+
+    assert_eq!(writer.buffer().get_char(0, 0), VgaChar::new(COLOR, 'd'));
+    assert_eq!(writer.buffer().get_char(0, 1), VgaChar::new(COLOR, 'b'));
+    assert_eq!(writer.cursor_pos(), (0, 1));
+}
+
+#[test]
+fn form_feed_clears() {
+    let mut writer = writer();
+    assert!(writer.write_str("a\nbc\x0c").is_ok());
+
+    assert_eq!(writer.buffer().get_char(0, 0), VgaChar::new(COLOR, ' '));
+    assert_eq!(writer.buffer().get_char(1, 1), VgaChar::new(COLOR, ' '));
+    assert_eq!(writer.cursor_pos(), (0, 0));
+}
+
+static BELLS: AtomicUsize = AtomicUsize::new(0);
+
+fn count_bell() {
+    BELLS.fetch_add(1, Ordering::SeqCst);
+}
+
+#[test]
+fn bell_calls_hook() {
+    let mut writer = writer();
+    let before = BELLS.load(Ordering::SeqCst);
+
+    assert!(writer.write_char('\x07').is_ok());
+    writer.set_bell(Some(count_bell));
+    assert!(writer.write_str("\x07\x07").is_ok());
+
+    assert_eq!(BELLS.load(Ordering::SeqCst) - before, 2);
+    assert_eq!(writer.cursor_pos(), (0, 0));
+    assert_eq!(writer.buffer().get_char(0, 0), VgaChar::new(VgaColor::new(Color::Black, Color::Black), ' '));
+}
+
+#[test]
+fn other_controls_ignored() {
+    let mut writer = writer();
+    assert!(writer.write_str("a\x01\x0b\x7fb").is_ok());
+
+    assert_eq!(writer.buffer().get_char(0, 1), VgaChar::new(COLOR, 'b'));
+    assert_eq!(writer.cursor_pos(), (0, 2));
+}
+
+#[test]
+fn truncate_mode() {
+    let mut writer = writer();
+    writer.set_wrap_mode(WrapMode::Truncate);
+    for _ in 0..RESOLUTION_X {
+        assert!(writer.write_char('-').is_ok());
+    }
+    assert!(writer.write_str("ab\nc").is_ok());
+
+    assert_eq!(writer.buffer().get_char(0, RESOLUTION_X - 1), VgaChar::new(COLOR, '-'));
+    assert_eq!(writer.buffer().get_char(1, 0), VgaChar::new(COLOR, 'c'));
+
+    // Wrapping can be turned back on with DECAWM
+    assert!(writer.write_str("\x1b[?7h\x1b[80Gde").is_ok());
+    assert_eq!(writer.buffer().get_char(2, 0), VgaChar::new(COLOR, 'e'));
+}
+
+/// Scrollback is too large for the test stack, so the tests share one
+static SCROLLBACK: Mutex<Scrollback<MemoryBuffer>> = Mutex::new(Scrollback::new(MemoryBuffer::new()));
+
+/// Writes the row number to the first cell of each row, scrolling the given amount of rows off
+fn fill_scrollback(scrollback: &mut Scrollback<MemoryBuffer>, scrolled: usize) {
+    // Dropping every row forgets the history of earlier tests
+    scrollback.set_screens(0);
+    scrollback.set_screens(DEFAULT_SCROLLBACK_SCREENS);
+
+    for row in 0..(RESOLUTION_Y + scrolled) {
+        let visible_row = cmp::min(row, RESOLUTION_Y - 1);
+        if row >= RESOLUTION_Y {
+            scrollback.scroll_down(1, Color::Black);
+        }
+        scrollback.set_char(visible_row, 0, VgaChar::new(COLOR, (b'A' + (row % 26) as u8) as char));
+    }
+}
+
+#[test]
+fn scrollback_keeps_scrolled_rows() {
+    let mut scrollback = SCROLLBACK.lock();
+    fill_scrollback(&mut scrollback, 3);
+
+    assert_eq!(scrollback.history_len(), 3);
+    assert_eq!(scrollback.get_char(0, 0), VgaChar::new(COLOR, 'D'));
+}
+
+#[test]
+fn scrollback_view_and_return() {
+    let mut scrollback = SCROLLBACK.lock();
+    fill_scrollback(&mut scrollback, 3);
+    scrollback.set_cursor(RESOLUTION_Y - 1, 1);
+
+    scrollback.view_up(2);
+    assert!(scrollback.is_viewing());
+    assert_eq!(scrollback.buffer().get_char(0, 0), VgaChar::new(COLOR, 'B'));
+    assert_eq!(scrollback.buffer().get_char(2, 0), VgaChar::new(COLOR, 'D'));
+
+    // Can't scroll past the oldest row
+    scrollback.view_up(100);
+    assert_eq!(scrollback.view_offset(), 3);
+    assert_eq!(scrollback.buffer().get_char(0, 0), VgaChar::new(COLOR, 'A'));
+
+    scrollback.view_down(1);
+    assert_eq!(scrollback.buffer().get_char(0, 0), VgaChar::new(COLOR, 'B'));
+
+    scrollback.view_down(100);
+    assert!(!scrollback.is_viewing());
+    assert_eq!(scrollback.buffer().get_char(0, 0), VgaChar::new(COLOR, 'D'));
+    assert_eq!(scrollback.buffer().cursor(), (RESOLUTION_Y - 1, 1));
+}
+
+#[test]
+fn scrollback_returns_on_output() {
+    let mut scrollback = SCROLLBACK.lock();
+    fill_scrollback(&mut scrollback, 3);
+
+    scrollback.view_up(3);
+    // Reads see the live screen while viewing
+    assert_eq!(scrollback.get_char(0, 0), VgaChar::new(COLOR, 'D'));
+
+    scrollback.set_char(1, 1, VgaChar::new(COLOR, 'x'));
+    assert!(!scrollback.is_viewing());
+    assert_eq!(scrollback.buffer().get_char(0, 0), VgaChar::new(COLOR, 'D'));
+    assert_eq!(scrollback.buffer().get_char(1, 1), VgaChar::new(COLOR, 'x'));
+}
+
+#[test]
+fn scrollback_capacity() {
+    let mut scrollback = SCROLLBACK.lock();
+    fill_scrollback(&mut scrollback, DEFAULT_SCROLLBACK_SCREENS * RESOLUTION_Y + 5);
+    assert_eq!(scrollback.history_len(), DEFAULT_SCROLLBACK_SCREENS * RESOLUTION_Y);
+
+    // The oldest rows were dropped
+    scrollback.view_up(DEFAULT_SCROLLBACK_SCREENS * RESOLUTION_Y);
+    assert_eq!(scrollback.buffer().get_char(0, 0), VgaChar::new(COLOR, (b'A' + 5) as char));
+    scrollback.return_to_live();
+
+    scrollback.set_screens(1);
+    assert_eq!(scrollback.history_len(), RESOLUTION_Y);
+    scrollback.set_screens(0);
+    assert_eq!(scrollback.history_len(), 0);
+}
+
+#[test]
+fn scrollback_screens_follow_height() {
+    let mut scrollback = SCROLLBACK.lock();
+    fill_scrollback(&mut scrollback, 0);
+
+    // Screens are as tall as the buffer, up to the rows the history holds
+    scrollback.resize(RESOLUTION_X, 50);
+    scrollback.set_screens(2);
+    for _ in 0..3 {
+        scrollback.scroll_down(50, Color::Black);
+    }
+    let two_screens = scrollback.history_len();
+
+    scrollback.set_screens(100);
+    for _ in 0..10 {
+        scrollback.scroll_down(50, Color::Black);
+    }
+    let full = scrollback.history_len();
+
+    // Shrinking the screens drops the oldest rows
+    scrollback.set_screens(DEFAULT_SCROLLBACK_SCREENS);
+    scrollback.resize(RESOLUTION_X, RESOLUTION_Y);
+    let shrunk = scrollback.history_len();
+
+    assert_eq!(two_screens, 100);
+    assert_eq!(full, HISTORY_ROWS);
+    assert_eq!(shrunk, DEFAULT_SCROLLBACK_SCREENS * RESOLUTION_Y);
+}
+
+#[test]
+fn memory_buffer_resize_keeps_cells() {
+    let mut buffer = MemoryBuffer::new();
+    buffer.set_char(1, 79, VgaChar::new(HIGHLIGHT, 'a'));
+    buffer.set_char(24, 0, VgaChar::new(HIGHLIGHT, 'b'));
+
+    buffer.resize(90, 60);
+    assert_eq!((buffer.width(), buffer.height()), (90, 60));
+    assert_eq!(buffer.get_char(1, 79), VgaChar::new(HIGHLIGHT, 'a'));
+    assert_eq!(buffer.get_char(24, 0), VgaChar::new(HIGHLIGHT, 'b'));
+    assert_eq!(buffer.get_char(1, 80), buffer::BLANK);
+    assert_eq!(buffer.get_char(59, 89), buffer::BLANK);
+
+    buffer.resize(80, 25);
+    assert_eq!(buffer.get_char(1, 79), VgaChar::new(HIGHLIGHT, 'a'));
+    assert_eq!(buffer.get_char(24, 0), VgaChar::new(HIGHLIGHT, 'b'));
+}
+
+#[test]
+fn resize_keeps_cursor_row() {
+    let mut writer = writer();
+    assert!(writer.resize(80, 50).is_ok());
+    writer.set_cursor_pos((40, 0));
+    assert!(writer.write_str("last").is_ok());
+
+    // The cursor's row is scrolled up to the bottom of the smaller screen
+    assert!(writer.resize(80, 25).is_ok());
+    assert_eq!(writer.cursor_pos(), (24, 4));
+    assert_eq!(writer.buffer().get_char(24, 0), VgaChar::new(COLOR, 'l'));
+}
+
+#[test]
+fn parse_text_mode() {
+    assert_eq!("80x25".parse::<TextMode>(), Ok(TextMode::Text80x25));
+    assert_eq!("90x60".parse::<TextMode>(), Ok(TextMode::Text90x60));
+    assert_eq!("80x60".parse::<TextMode>(), Err(UnknownTextMode));
+    assert_eq!(TextMode::Text80x50.size(), (80, 50));
+}
+
+#[test]
+fn parse_psf1() {
+    let mut data = [0; 4 + 256 * 16];
+    data[..4].copy_from_slice(&[0x36, 0x04, 0x00, 16]);
+    data[4 + b'A' as usize * 16] = 0x18;
+
+    let font = parse_psf(&data).unwrap();
+    assert_eq!(font.height(), 16);
+    assert_eq!(font.glyph(b'A')[0], 0x18);
+
+    // A 512 glyph font needs all of its glyphs
+    data[2] = 0x01;
+    assert_eq!(parse_psf(&data).err(), Some(FontError::Truncated));
+}
+
+#[test]
+fn parse_psf2() {
+    let mut data = [0; 32 + 256 * 8];
+    data[..32].copy_from_slice(&[
+        0x72, 0xB5, 0x4A, 0x86, 0, 0, 0, 0, 32, 0, 0, 0, 0, 0, 0, 0,
+        0, 1, 0, 0, 8, 0, 0, 0, 8, 0, 0, 0, 8, 0, 0, 0,
+    ]);
+    data[32 + 255 * 8 + 7] = 0xFF;
+
+    let font = parse_psf(&data).unwrap();
+    assert_eq!(font.height(), 8);
+    assert_eq!(font.glyph(255)[7], 0xFF);
+}
+
+#[test]
+fn parse_psf_errors() {
+    assert_eq!(parse_psf(&[0; 64]).err(), Some(FontError::UnknownFormat));
+    assert_eq!(parse_psf(&[0x36, 0x04]).err(), Some(FontError::Truncated));
+
+    // 8x14 glyphs can't be shown
+    let mut data = [0; 4 + 256 * 14];
+    data[..4].copy_from_slice(&[0x36, 0x04, 0x00, 14]);
+    assert_eq!(parse_psf(&data).err(), Some(FontError::UnsupportedSize { width: 8, height: 14 }));
+}
+
+#[test]
+fn dirty_rows_track_spans() {
+    let mut dirty = DirtyRows::new();
+    assert!(dirty.is_clean());
+
+    dirty.mark_cell(2, 10);
+    dirty.mark_cell(2, 4);
+    dirty.mark(3, 0, 5);
+    assert_eq!(dirty.cell_count(), 7 + 5);
+
+    assert_eq!(dirty.take_row(2), Some((4, 11)));
+    assert_eq!(dirty.take_row(2), None);
+    assert_eq!(dirty.take_row(3), Some((0, 5)));
+    assert!(dirty.is_clean());
+
+    dirty.mark_cursor();
+    assert!(!dirty.is_clean());
+    assert!(dirty.take_cursor());
+    assert!(!dirty.take_cursor());
+
+    assert_eq!(DirtyRows::all().cell_count(), MAX_COLUMNS * MAX_ROWS);
+}
+
+/// A buffer in memory which counts its flushes
+struct FlushCounter {
+    cells: MemoryBuffer,
+    flushes: usize,
+}
+
+impl TextBuffer for FlushCounter {
+    fn width(&self) -> usize {
+        self.cells.width()
+    }
+
+    fn height(&self) -> usize {
+        self.cells.height()
+    }
+
+    fn set_char(&mut self, row: usize, column: usize, value: VgaChar) {
+        self.cells.set_char(row, column, value);
+    }
+
+    fn get_char(&self, row: usize, column: usize) -> VgaChar {
+        self.cells.get_char(row, column)
+    }
+
+    fn resize(&mut self, width: usize, height: usize) {
+        self.cells.resize(width, height);
+    }
+
+    fn flush(&mut self) {
+        self.flushes += 1;
+    }
+}
+
+#[test]
+fn writer_batches_flushes() {
+    use console::{Console, Level};
+
+    let mut writer = VgaWriter::with_buffer(FlushCounter { cells: MemoryBuffer::new(), flushes: 0 }, COLOR);
+
+    // A whole string is flushed once, however many lines it scrolls
+    let lines = [b'\n'; RESOLUTION_Y * 2];
+    assert!(writer.write_str(str::from_utf8(&lines).unwrap()).is_ok());
+    assert_eq!(writer.buffer().flushes, 1);
+
+    writer.set_batching(true);
+    assert!(writer.write_str("a").is_ok());
+    writer.set_char(0, 0, 'b');
+    assert_eq!(writer.buffer().flushes, 1);
+    writer.set_batching(false);
+    assert_eq!(writer.buffer().flushes, 2);
+
+    // Printing to a console is flushed once it's done
+    Console::write(&mut writer, "a");
+    Console::write_leveled(&mut writer, Level::Info, "b");
+    assert_eq!(writer.buffer().flushes, 2);
+    Console::flush(&mut writer);
+    assert_eq!(writer.buffer().flushes, 3);
+}
+
+#[test]
+fn fixed_rows_shrink_terminal() {
+    let mut terminal = TerminalBuffer::new(false);
+    let bottom = RESOLUTION_Y - 1;
+
+    terminal.set_char(bottom, 0, VgaChar::new(HIGHLIGHT, 'a'));
+    terminal.set_cursor(bottom, 1);
+
+    // The cursor's row is scrolled up rather than covered
+    terminal.set_fixed_rows(0, 2);
+    assert_eq!(terminal.fixed_rows(), (0, 2));
+    assert_eq!(terminal.height(), RESOLUTION_Y - 2);
+    assert_eq!(terminal.screen_size(), (RESOLUTION_X, RESOLUTION_Y));
+    assert_eq!(terminal.get_char(bottom - 2, 0), VgaChar::new(HIGHLIGHT, 'a'));
+
+    // Fixed rows are cut down to fit
+    terminal.set_fixed_rows(MAX_FIXED_ROWS, 1);
+    assert_eq!(terminal.fixed_rows(), (MAX_FIXED_ROWS, 0));
+
+    terminal.set_fixed_rows(0, 0);
+    assert_eq!(terminal.height(), RESOLUTION_Y);
+}
+
+#[test]
+fn dump_formats() {
+    let mut buffer = MemoryBuffer::new();
+    let mut dump = String::new();
+
+    buffer.resize(4, 2);
+    buffer.set_char(0, 0, VgaChar::new(HIGHLIGHT, 'h'));
+    buffer.set_char(0, 1, VgaChar::new(HIGHLIGHT, 'i'));
+    buffer.set_char(1, 1, VgaChar::new(COLOR, '\u{2502}'));
+
+    assert!(write_dump(&buffer, &mut dump).is_ok());
+    assert_eq!(dump, concat!(
+        "--- screen 4x2 text ---\n",
+        "hi\n",
+        " \u{2502}\n",
+        "--- screen 4x2 ansi ---\n",
+        "\x1b[93;44mhi\x1b[30;40m  \x1b[0m\n",
+        "\x1b[30;40m \x1b[97;40m\u{2502}\x1b[30;40m  \x1b[0m\n",
+        "--- screen 4x2 attributes ---\n",
+        "1e1e0000\n",
+        "000f0000\n",
+        "--- end screen ---\n",
+    ));
+}
@@ -0,0 +1,711 @@
+use core::{cmp, fmt, mem};
+use core::convert::TryInto;
+use console::{Console, Level};
+use super::{Color, VgaColor, ColorCodeOutOfBounds, TextBuffer, VgaBuffer, VgaChar, CursorShape, Scrollback,
+            ScrollStrategy, cp437};
+use super::terminal::TerminalBuffer;
+use super::ansi::{AnsiParser, Action, Csi};
+
+/// Maximum amount of columns that tab stops can be set in
+const MAX_TAB_COLUMNS: usize = 128;
+
+/// Tab stops every 8 columns, as a bit for each column
+const DEFAULT_TAB_STOPS: [u64; MAX_TAB_COLUMNS / 64] = [0x0101_0101_0101_0101; MAX_TAB_COLUMNS / 64];
+
+/// What happens to chars written past the end of a row
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum WrapMode {
+    /// Continue on the next row
+    Wrap,
+    /// Drop the chars until the next new line
+    Truncate,
+}
+
+/// Interface to VGA, allowing write. Understands ANSI escape sequences for colors, cursor movement,
+/// erasing and scroll regions.
+pub struct VgaWriter<B: TextBuffer = VgaBuffer> {
+    column_position: usize,
+    row_position: usize,
+    color: VgaColor,
+    /// Color restored by an SGR reset
+    default_color: VgaColor,
+    /// Whether SGR bold is on, which brightens the foreground
+    bold: bool,
+    /// Whether SGR reverse video is on
+    reversed: bool,
+    saved_position: (usize, usize),
+    /// Rows that scroll on a new line at the bottom, as `(top, bottom)` with an exclusive bottom.
+    /// The whole buffer scrolls if `None`.
+    scroll_region: Option<(usize, usize)>,
+    parser: AnsiParser,
+    /// Glyph written for chars which aren't in code page 437
+    replacement_glyph: u8,
+    wrap_mode: WrapMode,
+    /// Columns which a tab moves to, as a bit for each column
+    tab_stops: [u64; MAX_TAB_COLUMNS / 64],
+    /// Called to sound the bell for `\x07`
+    bell: Option<fn()>,
+    /// Whether changes are left for an explicit `flush` rather than written to the screen after
+    /// each call
+    batching: bool,
+    buffer: B,
+}
+
+#[derive(Debug)]
+pub enum VgaWriteError {
+    ColorCodeOutOfBounds(u8)
+}
+
+impl VgaWriter<Scrollback<TerminalBuffer>> {
+    /// Creates a writer for a virtual terminal, which is shown on screen while active
+    pub const fn new(color: VgaColor, active: bool) -> Self {
+        VgaWriter {
+            column_position: 0,
+            row_position: 0,
+            color: color,
+            default_color: color,
+            bold: false,
+            reversed: false,
+            saved_position: (0, 0),
+            scroll_region: None,
+            parser: AnsiParser::new(),
+            replacement_glyph: cp437::REPLACEMENT_GLYPH,
+            wrap_mode: WrapMode::Wrap,
+            tab_stops: DEFAULT_TAB_STOPS,
+            bell: None,
+            batching: false,
+            buffer: Scrollback::new(TerminalBuffer::new(active)),
+        }
+    }
+
+    /// Keeps rows at the top and bottom of the screen out of the terminal, such as for a status
+    /// bar, leaving the rows between them to be written to and scroll. The cursor stays on the
+    /// same line of text, which is scrolled up if it would be covered.
+    pub fn set_fixed_rows(&mut self, top: usize, bottom: usize) -> Result<(), VgaWriteError> {
+        let (width, height) = self.buffer.buffer().screen_size();
+        self.buffer.return_to_live();
+        self.buffer.buffer_mut().set_fixed_rows(top, bottom);
+        self.resize(width, height)
+    }
+}
+
+#[allow(dead_code)] // For api -- may be used later
+impl<B: TextBuffer> VgaWriter<B> {
+    /// Creates a writer for the given buffer
+    pub const fn with_buffer(buffer: B, color: VgaColor) -> Self {
+        VgaWriter {
+            column_position: 0,
+            row_position: 0,
+            color: color,
+            default_color: color,
+            bold: false,
+            reversed: false,
+            saved_position: (0, 0),
+            scroll_region: None,
+            parser: AnsiParser::new(),
+            replacement_glyph: cp437::REPLACEMENT_GLYPH,
+            wrap_mode: WrapMode::Wrap,
+            tab_stops: DEFAULT_TAB_STOPS,
+            bell: None,
+            batching: false,
+            buffer: buffer,
+        }
+    }
+
+    /// Sets the color, which is also restored by an SGR reset
+    pub fn set_color(&mut self, color: VgaColor) {
+        self.color = color;
+        self.default_color = color;
+        self.bold = false;
+        self.reversed = false;
+    }
+
+    pub fn write_char(&mut self, character: char) -> Result<(), VgaWriteError> {
+        let color = self.color;
+        self.write_char_colored(character, color)?;
+
+        Ok(())
+    }
+
+    pub fn write_char_colored(&mut self, character: char, char_color: VgaColor) -> Result<(), VgaWriteError> {
+        self.put_char(character, char_color)?;
+        self.update_cursor();
+
+        Ok(())
+    }
+
+    /// Writes a char or part of an escape sequence, without moving the visible cursor
+    fn put_char(&mut self, character: char, char_color: VgaColor) -> Result<(), VgaWriteError> {
+        match self.parser.advance(character) {
+            Some(Action::Print(character)) => self.print_char(character, char_color)?,
+            Some(Action::Csi(csi)) => self.apply_csi(&csi),
+            Some(Action::SaveCursor) => self.saved_position = self.cursor_pos(),
+            Some(Action::RestoreCursor) => {
+                let (row, column) = self.saved_position;
+                self.set_position(row, column);
+            }
+            Some(Action::SetTabStop) => {
+                let column = self.column_position;
+                self.set_tab_stop(column);
+            }
+            None => {}
+        }
+
+        Ok(())
+    }
+
+    fn print_char(&mut self, character: char, char_color: VgaColor) -> Result<(), VgaWriteError> {
+        match character {
+            '\n' => self.line_feed()?,
+            '\r' => self.column_position = 0,
+            '\t' => self.tab(),
+            '\x08' => self.erase_back(),
+            '\x0c' => {
+                self.erase_in_display(2);
+                self.set_position(0, 0);
+            }
+            '\x07' => {
+                if let Some(bell) = self.bell {
+                    bell();
+                }
+            }
+            // Other control chars have no effect, rather than drawing their glyphs
+            '\x00'...'\x1f' | '\x7f' => {}
+            character => {
+                if self.column_position >= self.buffer.width() {
+                    match self.wrap_mode {
+                        WrapMode::Wrap => self.line_feed()?,
+                        WrapMode::Truncate => return Ok(()),
+                    }
+                }
+                let row = self.row_position;
+                let column = self.column_position;
+                let glyph = self.glyph(character);
+                self.buffer.set_char(row, column, VgaChar {
+                    color: char_color,
+                    character: glyph,
+                });
+                self.column_position += 1;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Backspaces one char
+    pub fn backspace_char(&mut self) -> Result<(), VgaWriteError> {
+        self.erase_back();
+        self.update_cursor();
+
+        Ok(())
+    }
+
+    fn erase_back(&mut self) {
+        if self.column_position > 0 {
+            self.column_position -= 1;
+        } else if self.row_position > 0 {
+            self.column_position = self.buffer.width() - 1;
+            self.row_position -= 1;
+        } else {
+            return;
+        }
+
+        let row = self.row_position;
+        let column = self.column_position;
+        self.set_char(row, column, ' ');
+    }
+
+    pub fn set_char(&mut self, row: usize, column: usize, character: char) {
+        let char_color = self.color;
+        self.set_char_colored(row, column, character, char_color);
+    }
+
+    pub fn set_char_colored(&mut self, row: usize, column: usize, character: char, char_color: VgaColor) {
+        let glyph = self.glyph(character);
+        self.buffer.set_char(row, column, VgaChar {
+            color: char_color,
+            character: glyph,
+        });
+        self.changed();
+    }
+
+    /// Sets the glyph written for chars which aren't in code page 437
+    pub fn set_replacement_glyph(&mut self, glyph: u8) {
+        self.replacement_glyph = glyph;
+    }
+
+    /// Sets what happens to chars written past the end of a row
+    pub fn set_wrap_mode(&mut self, mode: WrapMode) {
+        self.wrap_mode = mode;
+    }
+
+    /// Sets how the buffer scrolls when a new line is written on the last row
+    pub fn set_scroll_strategy(&mut self, strategy: ScrollStrategy) {
+        self.buffer.set_scroll_strategy(strategy);
+    }
+
+    /// Sets the function called to sound the bell, or none to ignore it
+    pub fn set_bell(&mut self, bell: Option<fn()>) {
+        self.bell = bell;
+    }
+
+    /// Sets a tab stop at the given column
+    pub fn set_tab_stop(&mut self, column: usize) {
+        if column < MAX_TAB_COLUMNS {
+            self.tab_stops[column / 64] |= 1u64 << (column % 64);
+        }
+    }
+
+    /// Removes the tab stop at the given column, if any
+    pub fn clear_tab_stop(&mut self, column: usize) {
+        if column < MAX_TAB_COLUMNS {
+            self.tab_stops[column / 64] &= !(1u64 << (column % 64));
+        }
+    }
+
+    /// Removes every tab stop, so tabs move to the end of the row
+    pub fn clear_tab_stops(&mut self) {
+        self.tab_stops = [0; MAX_TAB_COLUMNS / 64];
+    }
+
+    /// Replaces the tab stops with one every `width` columns
+    pub fn set_tab_width(&mut self, width: usize) {
+        self.clear_tab_stops();
+
+        if width > 0 {
+            for column in (0..MAX_TAB_COLUMNS).filter(|column| column % width == 0) {
+                self.set_tab_stop(column);
+            }
+        }
+    }
+
+    fn is_tab_stop(&self, column: usize) -> bool {
+        column < MAX_TAB_COLUMNS && self.tab_stops[column / 64] & (1u64 << (column % 64)) != 0
+    }
+
+    /// Moves to the next tab stop, or the last column if there are none left in the row
+    fn tab(&mut self) {
+        let last_column = self.buffer.width() - 1;
+        if self.column_position >= last_column {
+            return;
+        }
+
+        self.column_position = ((self.column_position + 1)..last_column)
+            .find(|&column| self.is_tab_stop(column))
+            .unwrap_or(last_column);
+    }
+
+    /// Gets the code page 437 glyph which shows the given char
+    fn glyph(&self, character: char) -> u8 {
+        cp437::encode(character).unwrap_or(self.replacement_glyph)
+    }
+
+    /// Writes a string, flushing once at the end rather than after each char
+    pub fn write_str(&mut self, str: &str) -> Result<(), VgaWriteError> {
+        let batching = mem::replace(&mut self.batching, true);
+        let mut result = Ok(());
+
+        for char in str.chars() {
+            // Escape sequences may change the color as we go
+            let color = self.color;
+            result = self.put_char(char, color);
+
+            if result.is_err() {
+                break;
+            }
+        }
+
+        self.batching = batching;
+        self.update_cursor();
+        result
+    }
+
+    /// Writes a string starting in the given color. Escape sequences in the string only change
+    /// the color until the end of the string.
+    pub fn write_str_colored(&mut self, str: &str, color: VgaColor) -> Result<(), VgaWriteError> {
+        let previous = (self.color, self.bold, self.reversed);
+        self.color = color;
+
+        let result = self.write_str(str);
+
+        self.color = previous.0;
+        self.bold = previous.1;
+        self.reversed = previous.2;
+        result
+    }
+
+    /// Gets the buffer this writer draws into
+    pub fn buffer(&self) -> &B {
+        &self.buffer
+    }
+
+    /// Gets the buffer this writer draws into, mutably
+    pub fn buffer_mut(&mut self) -> &mut B {
+        &mut self.buffer
+    }
+
+    pub fn new_line(&mut self) -> Result<(), VgaWriteError> {
+        self.line_feed()?;
+        self.update_cursor();
+
+        Ok(())
+    }
+
+    fn line_feed(&mut self) -> Result<(), VgaWriteError> {
+        self.column_position = 0;
+
+        let height = self.buffer.height();
+        let (top, bottom) = self.scroll_region.unwrap_or((0, height));
+
+        if self.row_position + 1 == bottom {
+            // Scroll down 1
+            let background_color = self.background_color()
+                .map_err(|e| VgaWriteError::ColorCodeOutOfBounds(e.0))?;
+
+            if top == 0 && bottom == height {
+                self.buffer.scroll_down(1, background_color);
+            } else {
+                self.buffer.scroll_rows(top, bottom, 1, background_color);
+            }
+        } else if self.row_position < height - 1 {
+            self.row_position += 1
+        }
+
+        Ok(())
+    }
+
+    /// Handles a control sequence
+    fn apply_csi(&mut self, csi: &Csi) {
+        let (row, column) = self.cursor_pos();
+        let last_row = self.buffer.height() - 1;
+        let last_column = self.buffer.width() - 1;
+        let amount = csi.param(0, 1) as usize;
+
+        match (csi.private, csi.final_byte) {
+            // Cursor movement
+            (false, 'A') => self.row_position = row.saturating_sub(amount),
+            (false, 'B') => self.row_position = cmp::min(row + amount, last_row),
+            (false, 'C') => self.column_position = cmp::min(column + amount, last_column),
+            (false, 'D') => self.column_position = cmp::min(column.saturating_sub(amount), last_column),
+            (false, 'E') => self.set_position(cmp::min(row + amount, last_row), 0),
+            (false, 'F') => self.set_position(row.saturating_sub(amount), 0),
+            (false, 'G') => self.column_position = cmp::min(amount - 1, last_column),
+            (false, 'd') => self.row_position = cmp::min(amount - 1, last_row),
+            (false, 'H') | (false, 'f') => {
+                let row = csi.param(0, 1) as usize - 1;
+                let column = csi.param(1, 1) as usize - 1;
+                self.set_position(cmp::min(row, last_row), cmp::min(column, last_column));
+            }
+
+            (false, 'J') => self.erase_in_display(csi.param(0, 0)),
+            (false, 'K') => self.erase_in_line(csi.param(0, 0)),
+            (false, 'm') => self.select_graphic_rendition(csi),
+            (false, 'g') => match csi.param(0, 0) {
+                0 => self.clear_tab_stop(column),
+                3 => self.clear_tab_stops(),
+                _ => {}
+            },
+            (false, 's') => self.saved_position = (row, column),
+            (false, 'u') => {
+                let (row, column) = self.saved_position;
+                self.set_position(row, column);
+            }
+            (false, 'r') => {
+                let top = csi.param(0, 1) as usize - 1;
+                let bottom = cmp::min(csi.param(1, last_row as u16 + 1) as usize, last_row + 1);
+                self.set_scroll_region(top, bottom);
+            }
+
+            // Turn line wrapping on and off
+            (true, 'h') if csi.param(0, 0) == 7 => self.wrap_mode = WrapMode::Wrap,
+            (true, 'l') if csi.param(0, 0) == 7 => self.wrap_mode = WrapMode::Truncate,
+
+            // Show and hide the cursor
+            (true, 'h') if csi.param(0, 0) == 25 => self.set_cursor_shape(CursorShape::Underline),
+            (true, 'l') if csi.param(0, 0) == 25 => self.set_cursor_shape(CursorShape::Hidden),
+
+            _ => {}
+        }
+    }
+
+    fn set_position(&mut self, row: usize, column: usize) {
+        self.row_position = row;
+        self.column_position = column;
+    }
+
+    /// Handles SGR, which sets the color
+    fn select_graphic_rendition(&mut self, csi: &Csi) {
+        // No parameters is the same as a reset
+        if csi.params().is_empty() {
+            self.reset_color();
+        }
+
+        for &param in csi.params() {
+            match param {
+                0 => self.reset_color(),
+                1 => {
+                    self.bold = true;
+                    self.color = self.color.brightened();
+                }
+                22 if self.bold => {
+                    self.bold = false;
+                    self.color = self.color.dimmed();
+                }
+                7 if !self.reversed => {
+                    self.reversed = true;
+                    self.color = self.color.inverted();
+                }
+                27 if self.reversed => {
+                    self.reversed = false;
+                    self.color = self.color.inverted();
+                }
+                30...37 => {
+                    let foreground = Color::from_ansi((param - 30) as u8, self.bold);
+                    self.color = self.color.with_foreground(foreground);
+                }
+                39 => {
+                    let foreground = self.default_color.foreground();
+                    self.color = self.color.with_foreground(foreground);
+                }
+                40...47 => {
+                    let background = Color::from_ansi((param - 40) as u8, false);
+                    self.color = self.color.with_background(background);
+                }
+                49 => {
+                    let background = self.default_color.background();
+                    self.color = self.color.with_background(background);
+                }
+                90...97 => {
+                    let foreground = Color::from_ansi((param - 90) as u8, true);
+                    self.color = self.color.with_foreground(foreground);
+                }
+                100...107 => {
+                    let background = Color::from_ansi((param - 100) as u8, true);
+                    self.color = self.color.with_background(background);
+                }
+                // Other attributes can't be shown in text mode
+                _ => {}
+            }
+        }
+    }
+
+    fn reset_color(&mut self) {
+        self.color = self.default_color;
+        self.bold = false;
+        self.reversed = false;
+    }
+
+    /// Erases part of the screen: 0 from the cursor to the end, 1 from the start to the cursor,
+    /// or 2 and 3 everything
+    fn erase_in_display(&mut self, mode: u16) {
+        let (row, column) = self.cursor_pos();
+        let height = self.buffer.height();
+        let width = self.buffer.width();
+
+        match mode {
+            0 => {
+                self.erase_cells(row, column, width);
+                for row in (row + 1)..height {
+                    self.erase_cells(row, 0, width);
+                }
+            }
+            1 => {
+                for row in 0..row {
+                    self.erase_cells(row, 0, width);
+                }
+                self.erase_cells(row, 0, column + 1);
+            }
+            2 | 3 => {
+                for row in 0..height {
+                    self.erase_cells(row, 0, width);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Erases part of the current row: 0 from the cursor to the end, 1 from the start to the
+    /// cursor, or 2 the whole row
+    fn erase_in_line(&mut self, mode: u16) {
+        let (row, column) = self.cursor_pos();
+        let width = self.buffer.width();
+
+        match mode {
+            0 => self.erase_cells(row, column, width),
+            1 => self.erase_cells(row, 0, column + 1),
+            2 => self.erase_cells(row, 0, width),
+            _ => {}
+        }
+    }
+
+    /// Clears the cells of a row from the start column up to the end column, in the current color
+    fn erase_cells(&mut self, row: usize, start: usize, end: usize) {
+        let blank = VgaChar {
+            color: self.color,
+            character: b' ',
+        };
+
+        for column in start..cmp::min(end, self.buffer.width()) {
+            self.buffer.set_char(row, column, blank);
+        }
+    }
+
+    /// Changes the size of the buffer, such as after changing text mode. If the cursor's row no
+    /// longer fits, rows are scrolled off the top to keep it on screen.
+    pub fn resize(&mut self, width: usize, height: usize) -> Result<(), VgaWriteError> {
+        let height = cmp::max(height, 1);
+
+        if self.row_position >= height {
+            let background_color = self.background_color()
+                .map_err(|e| VgaWriteError::ColorCodeOutOfBounds(e.0))?;
+            let amount = self.row_position + 1 - height;
+
+            self.buffer.scroll_down(amount, background_color);
+            self.row_position -= amount;
+        }
+
+        self.buffer.resize(width, height);
+
+        let last_row = self.buffer.height() - 1;
+        let width = self.buffer.width();
+        self.row_position = cmp::min(self.row_position, last_row);
+        self.column_position = cmp::min(self.column_position, width);
+        self.saved_position = (cmp::min(self.saved_position.0, last_row), cmp::min(self.saved_position.1, width - 1));
+        self.scroll_region = None;
+        self.update_cursor();
+
+        Ok(())
+    }
+
+    /// Sets the rows which scroll, from the top row up to the exclusive bottom row. Invalid
+    /// regions reset to the whole buffer. Moves the cursor home, like a VT100.
+    pub fn set_scroll_region(&mut self, top: usize, bottom: usize) {
+        let height = self.buffer.height();
+
+        self.scroll_region = if top + 1 < bottom && bottom <= height && !(top == 0 && bottom == height) {
+            Some((top, bottom))
+        } else {
+            None
+        };
+
+        self.set_position(0, 0);
+    }
+
+    /// Fills every cell with blank spaces in the given color
+    pub fn fill_screen(&mut self, fill_colour: Color) {
+        let blank = VgaChar {
+            color: VgaColor::new(Color::Black, fill_colour),
+            character: ' ' as u8,
+        };
+
+        for row in 0..self.buffer.height() {
+            for column in 0..self.buffer.width() {
+                self.buffer.set_char(row, column, blank);
+            }
+        }
+        self.changed();
+    }
+
+    /// Gets the background color for this writer
+    pub fn background_color(&mut self) -> Result<Color, ColorCodeOutOfBounds> {
+        Ok((self.color.try_into()?: (Color, Color)).0)
+    }
+
+    /// Gets the foreground color for this writer
+    pub fn foreground_color(&mut self) -> Result<Color, ColorCodeOutOfBounds> {
+        Ok((self.color.try_into()?: (Color, Color)).1)
+    }
+
+    /// Gets current pos of cursor
+    pub fn cursor_pos(&self) -> (usize, usize) {
+        (self.row_position, self.column_position)
+    }
+
+    /// Sets the current pos of cursor
+    pub fn set_cursor_pos(&mut self, pos: (usize, usize)) {
+        self.row_position = pos.0;
+        self.column_position = pos.1;
+        self.update_cursor();
+    }
+
+    /// Sets the shape of the visible cursor
+    pub fn set_cursor_shape(&mut self, shape: CursorShape) {
+        self.buffer.set_cursor_shape(shape);
+        self.changed();
+    }
+
+    /// Moves the buffer's visible cursor to this writer's position, flushing unless batching
+    fn update_cursor(&mut self) {
+        // The position is past the last column while waiting to wrap
+        let column = cmp::min(self.column_position, self.buffer.width() - 1);
+        let row = self.row_position;
+        self.buffer.set_cursor(row, column);
+        self.changed();
+    }
+
+    /// Writes the changes made so far to the screen
+    pub fn flush(&mut self) {
+        self.buffer.flush();
+    }
+
+    /// Sets whether changes are left for an explicit `flush`, such as while writing a lot of
+    /// output. Stopping batching flushes.
+    pub fn set_batching(&mut self, batching: bool) {
+        self.batching = batching;
+
+        if !batching {
+            self.flush();
+        }
+    }
+
+    /// Flushes after a change, unless batching
+    fn changed(&mut self) {
+        if !self.batching {
+            self.flush();
+        }
+    }
+
+    /// Writes a string as part of a batch which is flushed by `Console::flush`, in the given
+    /// color or else the writer's own
+    fn write_batched(&mut self, str: &str, color: Option<VgaColor>) {
+        let batching = mem::replace(&mut self.batching, true);
+
+        // The color is either the writer's own or a level's, which are always valid
+        let _ = match color {
+            Some(color) => self.write_str_colored(str, color),
+            None => self.write_str(str),
+        };
+        self.batching = batching;
+    }
+}
+
+impl<B: TextBuffer> fmt::Write for VgaWriter<B> {
+    fn write_str(&mut self, str: &str) -> Result<(), fmt::Error> {
+        self.write_str(str).map_err(|_| fmt::Error)
+    }
+}
+
+impl<B: TextBuffer + Send> Console for VgaWriter<B> {
+    fn write(&mut self, str: &str) {
+        self.write_batched(str, None);
+    }
+
+    fn write_leveled(&mut self, level: Level, str: &str) {
+        let foreground = match level {
+            Level::Error => Color::LightRed,
+            Level::Warn => Color::Yellow,
+            Level::Info => Color::White,
+            Level::Debug => Color::LightGray,
+            Level::Trace => Color::DarkGray,
+        };
+        let background = self.background_color().unwrap_or(Color::Black);
+
+        self.write_batched(str, Some(VgaColor::new(foreground, background)));
+    }
+
+    fn flush(&mut self) {
+        if !self.batching {
+            VgaWriter::flush(self);
+        }
+    }
+}
@@ -0,0 +1,240 @@
+//! Drawing shapes, images and text on a surface, clipped to a region of it
+
+use core::cmp;
+use drivers::vga::{self, Font, Rgb, REPLACEMENT_GLYPH};
+use super::{Surface, Rect, Rgba};
+
+/// Width of a glyph in pixels, as each row of a font is a byte
+const GLYPH_WIDTH: usize = 8;
+
+/// Draws on a surface. Coordinates may be off the surface, and only what's inside the clip
+/// region is drawn.
+pub struct Canvas<'a, S: Surface + 'a> {
+    surface: &'a mut S,
+    clip: Rect,
+}
+
+impl<'a, S: Surface + 'a> Canvas<'a, S> {
+    /// Creates a canvas drawing anywhere on the surface
+    pub fn new(surface: &'a mut S) -> Self {
+        let bounds = Rect::new(0, 0, surface.width(), surface.height());
+        Canvas {
+            surface: surface,
+            clip: bounds,
+        }
+    }
+
+    /// Gets the whole surface as a rectangle
+    pub fn bounds(&self) -> Rect {
+        Rect::new(0, 0, self.surface.width(), self.surface.height())
+    }
+
+    /// Gets the region drawing is clipped to
+    #[cfg(test)]
+    pub fn clip(&self) -> Rect {
+        self.clip
+    }
+
+    /// Clips drawing to the given region, inside the surface
+    pub fn set_clip(&mut self, clip: Rect) {
+        self.clip = clip.intersect(&self.bounds());
+    }
+
+    /// Lets drawing go anywhere on the surface again
+    pub fn reset_clip(&mut self) {
+        self.clip = self.bounds();
+    }
+
+    /// Writes an encoded pixel value, if it's inside the clip region
+    fn put_raw(&mut self, x: isize, y: isize, value: u32) {
+        if self.clip.contains(x, y) {
+            self.surface.put_raw(x as usize, y as usize, value);
+        }
+    }
+
+    /// Sets a pixel's color, if it's inside the clip region
+    pub fn put_pixel(&mut self, x: isize, y: isize, color: Rgb) {
+        let value = self.surface.format().encode(color);
+        self.put_raw(x, y, value);
+    }
+
+    /// Gets a pixel's color, or `None` off the surface
+    pub fn get_pixel(&self, x: isize, y: isize) -> Option<Rgb> {
+        if self.bounds().contains(x, y) {
+            let value = self.surface.get_raw(x as usize, y as usize);
+            Some(self.surface.format().decode(value))
+        } else {
+            None
+        }
+    }
+
+    /// Draws a color over a pixel, mixed with what's there by its alpha
+    pub fn blend_pixel(&mut self, x: isize, y: isize, color: Rgba) {
+        match color.alpha {
+            0 => {}
+            0xFF => self.put_pixel(x, y, color.rgb()),
+            _ => if let Some(under) = self.get_pixel(x, y) {
+                self.put_pixel(x, y, color.blend(under));
+            },
+        }
+    }
+
+    /// Fills the clip region
+    pub fn fill(&mut self, color: Rgb) {
+        let clip = self.clip;
+        self.fill_rect(clip, color);
+    }
+
+    pub fn fill_rect(&mut self, rect: Rect, color: Rgb) {
+        let value = self.surface.format().encode(color);
+        let rect = rect.intersect(&self.clip);
+
+        for y in rect.y..rect.bottom() {
+            for x in rect.x..rect.right() {
+                self.surface.put_raw(x as usize, y as usize, value);
+            }
+        }
+    }
+
+    /// Draws the outline of a rectangle, a pixel wide
+    pub fn draw_rect(&mut self, rect: Rect, color: Rgb) {
+        if rect.is_empty() {
+            return;
+        }
+
+        let (right, bottom) = (rect.right() - 1, rect.bottom() - 1);
+        self.line((rect.x, rect.y), (right, rect.y), color);
+        self.line((rect.x, bottom), (right, bottom), color);
+        self.line((rect.x, rect.y), (rect.x, bottom), color);
+        self.line((right, rect.y), (right, bottom), color);
+    }
+
+    /// Draws a line between two points, both included
+    pub fn line(&mut self, from: (isize, isize), to: (isize, isize), color: Rgb) {
+        let value = self.surface.format().encode(color);
+
+        let (mut x, mut y) = from;
+        let dx = (to.0 - x).abs();
+        let dy = -(to.1 - y).abs();
+        let step_x = if x < to.0 { 1 } else { -1 };
+        let step_y = if y < to.1 { 1 } else { -1 };
+        let mut error = dx + dy;
+
+        loop {
+            self.put_raw(x, y, value);
+            if (x, y) == to {
+                break;
+            }
+
+            let doubled = error * 2;
+            if doubled >= dy {
+                error += dy;
+                x += step_x;
+            }
+            if doubled <= dx {
+                error += dx;
+                y += step_y;
+            }
+        }
+    }
+
+    /// Draws the outline of a circle, a pixel wide
+    pub fn circle(&mut self, center: (isize, isize), radius: usize, color: Rgb) {
+        let value = self.surface.format().encode(color);
+        let (center_x, center_y) = center;
+
+        self.midpoint_circle(radius, |canvas, x, y| {
+            for &(x, y) in [(x, y), (y, x), (-y, x), (-x, y), (-x, -y), (-y, -x), (y, -x), (x, -y)].iter() {
+                canvas.put_raw(center_x + x, center_y + y, value);
+            }
+        });
+    }
+
+    /// Draws a filled circle
+    pub fn fill_circle(&mut self, center: (isize, isize), radius: usize, color: Rgb) {
+        let (center_x, center_y) = center;
+
+        self.midpoint_circle(radius, |canvas, x, y| {
+            for &(half, row) in [(x, y), (y, x)].iter() {
+                let span = Rect::new(center_x - half, center_y - row, half as usize * 2 + 1, 1);
+                canvas.fill_rect(span, color);
+                canvas.fill_rect(Rect { y: center_y + row, ..span }, color);
+            }
+        });
+    }
+
+    /// Walks the first octant of a circle with the midpoint algorithm, giving each point to be
+    /// mirrored into the others
+    fn midpoint_circle<F>(&mut self, radius: usize, mut plot: F)
+        where F: FnMut(&mut Self, isize, isize)
+    {
+        let mut x = radius as isize;
+        let mut y = 0;
+        let mut error = 1 - x;
+
+        while x >= y {
+            plot(self, x, y);
+            y += 1;
+
+            if error < 0 {
+                error += 2 * y + 1;
+            } else {
+                x -= 1;
+                error += 2 * (y - x) + 1;
+            }
+        }
+    }
+
+    /// Draws an image of the given width, with a color for each pixel row by row, blending each
+    /// by its alpha
+    pub fn blit(&mut self, x: isize, y: isize, width: usize, pixels: &[Rgba]) {
+        if width == 0 {
+            return;
+        }
+
+        for (row, line) in pixels.chunks(width).enumerate() {
+            for (column, &color) in line.iter().enumerate() {
+                self.blend_pixel(x + column as isize, y + row as isize, color);
+            }
+        }
+    }
+
+    /// Draws text with a bitmap font, from the given top left corner. Chars without a glyph are
+    /// drawn as `REPLACEMENT_GLYPH`, and new lines go back to the left. The background is left
+    /// as it is if there's no background color. Gives the width drawn in pixels.
+    pub fn text(&mut self, x: isize, y: isize, text: &str, font: &Font,
+                foreground: Rgb, background: Option<Rgb>) -> usize {
+        let format = self.surface.format();
+        let foreground = format.encode(foreground);
+        let background = background.map(|color| format.encode(color));
+
+        let (mut left, mut top) = (x, y);
+        let mut width = 0;
+
+        for character in text.chars() {
+            if character == '\n' {
+                left = x;
+                top += font.height() as isize;
+                continue;
+            }
+
+            let glyph = font.glyph(vga::encode_cp437(character).unwrap_or(REPLACEMENT_GLYPH));
+            for (row, &bits) in glyph.iter().enumerate() {
+                for column in 0..GLYPH_WIDTH {
+                    let (pixel_x, pixel_y) = (left + column as isize, top + row as isize);
+
+                    if bits & (0x80 >> column) != 0 {
+                        self.put_raw(pixel_x, pixel_y, foreground);
+                    } else if let Some(background) = background {
+                        self.put_raw(pixel_x, pixel_y, background);
+                    }
+                }
+            }
+
+            left += GLYPH_WIDTH as isize;
+            width = cmp::max(width, (left - x) as usize);
+        }
+
+        width
+    }
+}
@@ -0,0 +1,118 @@
+//! How colors are encoded as pixel values, and colors with alpha
+
+use multiboot::ColorField;
+use drivers::vga::Rgb;
+
+/// Represents how a surface's pixels encode colors
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct PixelFormat {
+    pub bytes_per_pixel: usize,
+    pub red: ColorField,
+    pub green: ColorField,
+    pub blue: ColorField,
+}
+
+/// 8 bits per channel in 32 bit pixels, with the top byte unused
+pub const XRGB8888: PixelFormat = PixelFormat {
+    bytes_per_pixel: 4,
+    red: ColorField { position: 16, size: 8 },
+    green: ColorField { position: 8, size: 8 },
+    blue: ColorField { position: 0, size: 8 },
+};
+
+/// 8 bits per channel in 24 bit pixels
+pub const RGB888: PixelFormat = PixelFormat {
+    bytes_per_pixel: 3,
+    red: ColorField { position: 16, size: 8 },
+    green: ColorField { position: 8, size: 8 },
+    blue: ColorField { position: 0, size: 8 },
+};
+
+/// 16 bit pixels, with an extra bit for green
+pub const RGB565: PixelFormat = PixelFormat {
+    bytes_per_pixel: 2,
+    red: ColorField { position: 11, size: 5 },
+    green: ColorField { position: 5, size: 6 },
+    blue: ColorField { position: 0, size: 5 },
+};
+
+impl PixelFormat {
+    pub fn new(bits_per_pixel: u8, red: ColorField, green: ColorField, blue: ColorField) -> Self {
+        PixelFormat {
+            bytes_per_pixel: bits_per_pixel as usize / 8,
+            red: red,
+            green: green,
+            blue: blue,
+        }
+    }
+
+    /// Encodes a color as a pixel value, dropping the bits of each channel that don't fit
+    pub fn encode(&self, color: Rgb) -> u32 {
+        let channel = |value: u8, field: ColorField| {
+            (value as u32 >> (8 - channel_size(field))) << field.position
+        };
+
+        channel(color.red, self.red) | channel(color.green, self.green) | channel(color.blue, self.blue)
+    }
+
+    /// Decodes a pixel value, widening each channel so that full intensity stays full
+    pub fn decode(&self, value: u32) -> Rgb {
+        let channel = |field: ColorField| {
+            let size = channel_size(field);
+            if size == 0 {
+                return 0;
+            }
+
+            let bits = (value >> field.position) & ((1 << size) - 1);
+            let widened = bits << (8 - size);
+            (widened | widened >> size) as u8
+        };
+
+        Rgb::new(channel(self.red), channel(self.green), channel(self.blue))
+    }
+}
+
+/// Gets a channel's size, up to the 8 bits of `Rgb`
+fn channel_size(field: ColorField) -> u32 {
+    if field.size > 8 { 8 } else { field.size as u32 }
+}
+
+/// Represents a color with an alpha, from 0 for transparent to 255 for opaque
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Rgba {
+    pub red: u8,
+    pub green: u8,
+    pub blue: u8,
+    pub alpha: u8,
+}
+
+impl Rgba {
+    pub const fn new(red: u8, green: u8, blue: u8, alpha: u8) -> Self {
+        Rgba {
+            red: red,
+            green: green,
+            blue: blue,
+            alpha: alpha,
+        }
+    }
+
+    /// Creates an opaque color
+    #[cfg(test)]
+    pub const fn opaque(color: Rgb) -> Self {
+        Rgba::new(color.red, color.green, color.blue, 0xFF)
+    }
+
+    pub fn rgb(&self) -> Rgb {
+        Rgb::new(self.red, self.green, self.blue)
+    }
+
+    /// Gets the color shown when this is drawn over the given color
+    pub fn blend(&self, under: Rgb) -> Rgb {
+        let alpha = self.alpha as u32;
+        let mix = |over: u8, under: u8| {
+            ((over as u32 * alpha + under as u32 * (255 - alpha) + 127) / 255) as u8
+        };
+
+        Rgb::new(mix(self.red, under.red), mix(self.green, under.green), mix(self.blue, under.blue))
+    }
+}
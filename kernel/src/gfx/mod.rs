@@ -0,0 +1,13 @@
+//! 2D drawing on pixel surfaces, such as framebuffers or VGA graphics modes: shapes, alpha blended
+//! blits and text, clipped to a region of the surface
+
+mod canvas;
+mod format;
+mod surface;
+
+#[cfg(test)]
+mod tests;
+
+pub use self::canvas::Canvas;
+pub use self::format::{PixelFormat, Rgba, XRGB8888, RGB888, RGB565};
+pub use self::surface::{Surface, Rect};
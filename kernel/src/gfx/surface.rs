@@ -0,0 +1,65 @@
+//! Surfaces of pixels to draw on, and rectangles on them
+
+use core::cmp;
+use super::PixelFormat;
+
+/// A grid of pixels, each holding a value encoded in the surface's format
+pub trait Surface {
+    fn width(&self) -> usize;
+    fn height(&self) -> usize;
+    fn format(&self) -> PixelFormat;
+
+    /// Writes an encoded pixel value. Pixels off the surface are ignored.
+    fn put_raw(&mut self, x: usize, y: usize, value: u32);
+
+    /// Reads an encoded pixel value, or 0 off the surface
+    fn get_raw(&self, x: usize, y: usize) -> u32;
+}
+
+/// Represents a rectangle, which may be partly or wholly off a surface
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Rect {
+    pub x: isize,
+    pub y: isize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Rect {
+    pub const fn new(x: isize, y: isize, width: usize, height: usize) -> Self {
+        Rect {
+            x: x,
+            y: y,
+            width: width,
+            height: height,
+        }
+    }
+
+    /// Gets the column just past the right edge
+    pub fn right(&self) -> isize {
+        self.x + self.width as isize
+    }
+
+    /// Gets the row just past the bottom edge
+    pub fn bottom(&self) -> isize {
+        self.y + self.height as isize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+
+    pub fn contains(&self, x: isize, y: isize) -> bool {
+        x >= self.x && x < self.right() && y >= self.y && y < self.bottom()
+    }
+
+    /// Gets the part of this rectangle inside the other, which is empty if they don't overlap
+    pub fn intersect(&self, other: &Rect) -> Rect {
+        let x = cmp::max(self.x, other.x);
+        let y = cmp::max(self.y, other.y);
+        let right = cmp::max(cmp::min(self.right(), other.right()), x);
+        let bottom = cmp::max(cmp::min(self.bottom(), other.bottom()), y);
+
+        Rect::new(x, y, (right - x) as usize, (bottom - y) as usize)
+    }
+}
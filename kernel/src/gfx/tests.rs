@@ -0,0 +1,239 @@
+use multiboot::ColorField;
+use drivers::framebuffer;
+use drivers::vga::Rgb;
+use super::*;
+
+const WIDTH: usize = 32;
+const HEIGHT: usize = 32;
+
+const BLACK: Rgb = Rgb::from_hex(0x000000);
+const WHITE: Rgb = Rgb::from_hex(0xFFFFFF);
+const RED: Rgb = Rgb::from_hex(0xFF0000);
+
+#[test]
+fn encode_and_decode_formats() {
+    assert_eq!(XRGB8888.encode(Rgb::from_hex(0x123456)), 0x123456);
+    assert_eq!(XRGB8888.decode(0xFF123456), Rgb::from_hex(0x123456));
+
+    assert_eq!(RGB565.encode(Rgb::new(0xFF, 0x80, 0x08)), 0xFC01);
+    assert_eq!(RGB565.decode(0xFFFF), WHITE);
+    assert_eq!(RGB565.decode(0xF800), RED);
+
+    // Blue in the high bits, as some framebuffers have it
+    let bgr = PixelFormat::new(32, ColorField { position: 0, size: 8 }, ColorField { position: 8, size: 8 },
+                               ColorField { position: 16, size: 8 });
+    assert_eq!(bgr.encode(RED), 0x0000FF);
+    assert_eq!(bgr.decode(0xFF0000), Rgb::from_hex(0x0000FF));
+}
+
+#[test]
+fn blend_colors() {
+    assert_eq!(Rgba::opaque(RED).blend(WHITE), RED);
+    assert_eq!(Rgba::new(0xFF, 0, 0, 0).blend(WHITE), WHITE);
+    assert_eq!(Rgba::new(0xFF, 0xFF, 0xFF, 0x80).blend(BLACK), Rgb::from_hex(0x808080));
+}
+
+#[test]
+fn intersect_rects() {
+    let rect = Rect::new(-4, 2, 10, 10);
+    assert_eq!(rect.intersect(&Rect::new(0, 0, 32, 32)), Rect::new(0, 2, 6, 10));
+    assert!(rect.intersect(&Rect::new(20, 20, 4, 4)).is_empty());
+    assert!(rect.contains(-4, 11));
+    assert!(!rect.contains(6, 2));
+}
+
+/// A surface of pixels in memory, each kept as a whole `u32` whatever the format
+struct MemorySurface<'a> {
+    pixels: &'a mut [u32],
+    width: usize,
+    height: usize,
+    format: PixelFormat,
+}
+
+impl<'a> MemorySurface<'a> {
+    /// Creates a surface on the given pixels, which must hold at least `width * height`
+    fn new(pixels: &'a mut [u32], width: usize, height: usize, format: PixelFormat) -> Self {
+        assert!(pixels.len() >= width * height, "Surface memory too small");
+
+        MemorySurface {
+            pixels: pixels,
+            width: width,
+            height: height,
+            format: format,
+        }
+    }
+}
+
+impl<'a> Surface for MemorySurface<'a> {
+    fn width(&self) -> usize {
+        self.width
+    }
+
+    fn height(&self) -> usize {
+        self.height
+    }
+
+    fn format(&self) -> PixelFormat {
+        self.format
+    }
+
+    fn put_raw(&mut self, x: usize, y: usize, value: u32) {
+        if x < self.width && y < self.height {
+            self.pixels[y * self.width + x] = value;
+        }
+    }
+
+    fn get_raw(&self, x: usize, y: usize) -> u32 {
+        if x < self.width && y < self.height {
+            self.pixels[y * self.width + x]
+        } else {
+            0
+        }
+    }
+}
+
+/// Gets the pixel values of a `WIDTH` by `HEIGHT` surface in the given format after drawing
+fn draw<F: FnOnce(&mut Canvas<MemorySurface>)>(format: PixelFormat, draw: F) -> [u32; WIDTH * HEIGHT] {
+    let mut pixels = [0; WIDTH * HEIGHT];
+    {
+        let mut surface = MemorySurface::new(&mut pixels, WIDTH, HEIGHT, format);
+        draw(&mut Canvas::new(&mut surface));
+    }
+    pixels
+}
+
+fn at(pixels: &[u32], x: usize, y: usize) -> u32 {
+    pixels[y * WIDTH + x]
+}
+
+/// Counts the pixels set to something other than 0
+fn count_set(pixels: &[u32]) -> usize {
+    pixels.iter().filter(|&&pixel| pixel != 0).count()
+}
+
+#[test]
+fn fill_and_outline_rects() {
+    let pixels = draw(XRGB8888, |canvas| canvas.fill_rect(Rect::new(2, 3, 4, 5), RED));
+    assert_eq!(count_set(&pixels), 4 * 5);
+    assert_eq!(at(&pixels, 2, 3), 0xFF0000);
+    assert_eq!(at(&pixels, 5, 7), 0xFF0000);
+    assert_eq!(at(&pixels, 6, 7), 0);
+
+    let pixels = draw(RGB565, |canvas| canvas.draw_rect(Rect::new(0, 0, 4, 3), WHITE));
+    assert_eq!(count_set(&pixels), 10);
+    assert_eq!(at(&pixels, 3, 2), 0xFFFF);
+    assert_eq!(at(&pixels, 1, 1), 0);
+}
+
+#[test]
+fn draw_lines() {
+    let pixels = draw(XRGB8888, |canvas| canvas.line((0, 0), (7, 7), WHITE));
+    assert_eq!(count_set(&pixels), 8);
+    for i in 0..8 {
+        assert_eq!(at(&pixels, i, i), 0xFFFFFF);
+    }
+
+    // Both ends are drawn whichever way round the line goes
+    let pixels = draw(XRGB8888, |canvas| canvas.line((10, 5), (2, 8), WHITE));
+    assert_eq!(at(&pixels, 10, 5), 0xFFFFFF);
+    assert_eq!(at(&pixels, 2, 8), 0xFFFFFF);
+    assert_eq!(count_set(&pixels), 9);
+}
+
+#[test]
+fn draw_circles() {
+    let pixels = draw(XRGB8888, |canvas| canvas.circle((16, 16), 5, WHITE));
+    for &(x, y) in [(21, 16), (11, 16), (16, 21), (16, 11)].iter() {
+        assert_eq!(at(&pixels, x, y), 0xFFFFFF);
+    }
+    assert_eq!(at(&pixels, 16, 16), 0);
+    assert_eq!(at(&pixels, 22, 16), 0);
+
+    let pixels = draw(XRGB8888, |canvas| canvas.fill_circle((16, 16), 5, WHITE));
+    assert_eq!(at(&pixels, 16, 16), 0xFFFFFF);
+    assert_eq!(at(&pixels, 19, 19), 0xFFFFFF);
+    assert_eq!(at(&pixels, 21, 21), 0);
+
+    // Each filled row is symmetric about the center
+    for y in 0..HEIGHT {
+        for x in 0..WIDTH - 16 {
+            assert_eq!(at(&pixels, 16 + x, y), at(&pixels, 16 - x, y));
+        }
+    }
+}
+
+#[test]
+fn clip_drawing() {
+    let pixels = draw(XRGB8888, |canvas| {
+        canvas.set_clip(Rect::new(4, 4, 8, 8));
+        canvas.fill(RED);
+        canvas.line((0, 0), (31, 31), WHITE);
+        canvas.fill_circle((0, 0), 40, WHITE);
+    });
+    assert_eq!(at(&pixels, 4, 4), 0xFFFFFF);
+    assert_eq!(at(&pixels, 3, 3), 0);
+    assert_eq!(at(&pixels, 12, 12), 0);
+    assert_eq!(count_set(&pixels), 8 * 8);
+
+    // Shapes off the surface are clipped to it
+    let mut clip = Rect::new(0, 0, 0, 0);
+    let pixels = draw(XRGB8888, |canvas| {
+        canvas.set_clip(Rect::new(-10, -10, 100, 100));
+        clip = canvas.clip();
+        canvas.fill_rect(Rect::new(-5, -5, 10, 10), RED);
+        canvas.line((-10, 0), (100, 0), WHITE);
+    });
+    assert_eq!(clip, Rect::new(0, 0, WIDTH, HEIGHT));
+    assert_eq!(at(&pixels, 4, 4), 0xFF0000);
+    assert_eq!(at(&pixels, 31, 0), 0xFFFFFF);
+    assert_eq!(count_set(&pixels), 5 * 5 + WIDTH - 5);
+}
+
+#[test]
+fn blit_with_alpha() {
+    let image = [
+        Rgba::opaque(RED), Rgba::new(0xFF, 0xFF, 0xFF, 0x80),
+        Rgba::new(0xFF, 0xFF, 0xFF, 0), Rgba::new(0, 0, 0xFF, 0xFF),
+    ];
+
+    let pixels = draw(XRGB8888, |canvas| {
+        canvas.fill(BLACK);
+        canvas.blit(30, 30, 2, &image);
+        canvas.blit(-1, 0, 2, &image);
+    });
+    assert_eq!(at(&pixels, 30, 30), 0xFF0000);
+    assert_eq!(at(&pixels, 31, 30), 0x808080);
+    assert_eq!(at(&pixels, 30, 31), 0);
+    assert_eq!(at(&pixels, 31, 31), 0x0000FF);
+
+    // Only the right column is on the surface
+    assert_eq!(at(&pixels, 0, 0), 0x808080);
+    assert_eq!(at(&pixels, 0, 1), 0x0000FF);
+}
+
+#[test]
+fn draw_text() {
+    let font = framebuffer::font();
+    let mut width = 0;
+
+    let pixels = draw(XRGB8888, |canvas| {
+        width = canvas.text(1, 0, "AB\nA", &font, WHITE, None);
+    });
+    assert_eq!(width, 16);
+
+    for &(x, y, character) in [(1, 0, b'A'), (9, 0, b'B'), (1, font.height(), b'A')].iter() {
+        for (row, &bits) in font.glyph(character).iter().enumerate() {
+            for column in 0..8 {
+                let expected = if bits & (0x80 >> column) != 0 { 0xFFFFFF } else { 0 };
+                assert_eq!(at(&pixels, x + column, y + row), expected);
+            }
+        }
+    }
+
+    // With a background the whole cell is drawn
+    let pixels = draw(RGB565, |canvas| {
+        canvas.text(0, 0, " ", &font, WHITE, Some(RED));
+    });
+    assert_eq!(count_set(&pixels), 8 * font.height());
+    assert_eq!(at(&pixels, 7, 15), 0xF800);
+}
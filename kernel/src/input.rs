@@ -0,0 +1,127 @@
+//! Chars typed on the keyboard or pasted from the clipboard, queued for the terminal they were
+//! typed on until they're read
+
+use spin::Mutex;
+use drivers::vga::TERMINAL_COUNT;
+
+/// Most chars which can wait to be read on each terminal
+pub const INPUT_CAPACITY: usize = 1024;
+
+/// Input of each virtual terminal, by the same index as `vga::TERMINALS`
+pub static INPUT: [Mutex<InputQueue>; TERMINAL_COUNT] = [
+    Mutex::new(InputQueue::new()),
+    Mutex::new(InputQueue::new()),
+    Mutex::new(InputQueue::new()),
+    Mutex::new(InputQueue::new()),
+    Mutex::new(InputQueue::new()),
+    Mutex::new(InputQueue::new()),
+];
+
+/// Typed chars, read in the order they were typed. Chars typed while it's full are dropped, like
+/// a keyboard's buffer.
+pub struct InputQueue {
+    chars: [char; INPUT_CAPACITY],
+    /// Index of the oldest char
+    start: usize,
+    len: usize,
+}
+
+impl InputQueue {
+    pub const fn new() -> Self {
+        InputQueue {
+            chars: ['\0'; INPUT_CAPACITY],
+            start: 0,
+            len: 0,
+        }
+    }
+
+    /// Adds a char to the end of the queue, giving false if it's full
+    pub fn push(&mut self, character: char) -> bool {
+        if self.len == INPUT_CAPACITY {
+            return false;
+        }
+
+        self.chars[(self.start + self.len) % INPUT_CAPACITY] = character;
+        self.len += 1;
+        true
+    }
+
+    /// Takes the oldest char from the queue
+    #[allow(dead_code)] // Nothing reads input yet
+    pub fn pop(&mut self) -> Option<char> {
+        if self.len == 0 {
+            return None;
+        }
+
+        let character = self.chars[self.start];
+        self.start = (self.start + 1) % INPUT_CAPACITY;
+        self.len -= 1;
+        Some(character)
+    }
+}
+
+#[cfg(feature = "kernel-test")]
+pub mod tests {
+    use core::fmt::Write;
+    use testing::{TestCase, TestResult};
+    use clipboard::CLIPBOARD;
+    use console;
+    use drivers::vga;
+    use super::*;
+
+    pub const TESTS: &'static [TestCase] = &[
+        test_case!(reads_in_typed_order),
+        test_case!(drops_when_full),
+        test_case!(pastes_into_given_terminal),
+    ];
+
+    fn reads_in_typed_order() -> TestResult {
+        let mut input = InputQueue::new();
+        test_assert_eq!(input.pop(), None);
+
+        test_assert!(input.push('a'));
+        test_assert!(input.push('\u{e9}'));
+        test_assert_eq!(input.pop(), Some('a'));
+        test_assert!(input.push('\n'));
+        test_assert_eq!(input.pop(), Some('\u{e9}'));
+        test_assert_eq!(input.pop(), Some('\n'));
+        test_assert_eq!(input.pop(), None);
+        Ok(())
+    }
+
+    fn drops_when_full() -> TestResult {
+        let mut input = InputQueue::new();
+        // Start part way through, so that the queue wraps around
+        test_assert!(input.push('x'));
+        test_assert_eq!(input.pop(), Some('x'));
+
+        for _ in 0..INPUT_CAPACITY {
+            test_assert!(input.push('a'));
+        }
+        test_assert!(!input.push('b'));
+
+        for _ in 0..INPUT_CAPACITY {
+            test_assert_eq!(input.pop(), Some('a'));
+        }
+        test_assert_eq!(input.pop(), None);
+        Ok(())
+    }
+
+    fn pastes_into_given_terminal() -> TestResult {
+        {
+            let mut clipboard = CLIPBOARD.lock();
+            clipboard.clear();
+            test_assert!(clipboard.write_str("hi").is_ok());
+        }
+
+        let active = vga::active();
+        let other = (active + 1) % TERMINAL_COUNT;
+        console::paste(other, &mut vga::TERMINALS[other].lock());
+
+        let mut input = INPUT[other].lock();
+        let pasted = [input.pop(), input.pop(), input.pop()];
+        test_assert_eq!(pasted, [Some('h'), Some('i'), None]);
+        test_assert_eq!(INPUT[active].lock().pop(), None);
+        Ok(())
+    }
+}
@@ -0,0 +1,91 @@
+use core::fmt::{self, Write};
+use backtrace::{self, Backtrace};
+use console::{self, Level};
+use drivers::serial;
+use cpu;
+
+/// Represents the state pushed by the CPU when an exception occurs
+#[repr(C)]
+pub struct ExceptionStackFrame {
+    pub instruction_pointer: u64,
+    pub code_segment: u64,
+    pub cpu_flags: u64,
+    pub stack_pointer: u64,
+    pub stack_segment: u64,
+}
+
+impl fmt::Display for ExceptionStackFrame {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f, "rip={:016x} cs={:04x} rflags={:016x}\nrsp={:016x} ss={:04x}",
+            self.instruction_pointer, self.code_segment, self.cpu_flags,
+            self.stack_pointer, self.stack_segment
+        )
+    }
+}
+
+pub extern "x86-interrupt" fn divide_error(frame: &mut ExceptionStackFrame) {
+    fatal("divide error", frame, format_args!(""), backtrace::frame_pointer());
+}
+
+/// Reports the breakpoint straight to serial. The interrupted code carries on afterwards and may be
+/// holding any console, so nothing is waited for or taken over, and the report is left out if the
+/// serial port is held.
+pub extern "x86-interrupt" fn breakpoint(frame: &mut ExceptionStackFrame) {
+    let interrupted = backtrace::saved_frame_pointer(backtrace::frame_pointer());
+
+    if let Some(mut serial) = serial::COM1.try_lock() {
+        let _ = write!(
+            serial,
+            "Breakpoint\n{}\n{}\n",
+            frame,
+            Backtrace::from_interrupt(frame.instruction_pointer as usize, interrupted)
+        );
+    }
+}
+
+pub extern "x86-interrupt" fn invalid_opcode(frame: &mut ExceptionStackFrame) {
+    fatal("invalid opcode", frame, format_args!(""), backtrace::frame_pointer());
+}
+
+pub extern "x86-interrupt" fn double_fault(frame: &mut ExceptionStackFrame, _error_code: u64) {
+    fatal("double fault", frame, format_args!(""), backtrace::frame_pointer());
+}
+
+pub extern "x86-interrupt" fn general_protection_fault(frame: &mut ExceptionStackFrame, error_code: u64) {
+    fatal(
+        "general protection fault",
+        frame,
+        format_args!("selector error code {:#x}\n", error_code),
+        backtrace::frame_pointer()
+    );
+}
+
+pub extern "x86-interrupt" fn page_fault(frame: &mut ExceptionStackFrame, error_code: u64) {
+    let address: u64;
+    unsafe { asm!("mov %cr2, $0" : "=r"(address) ::: "volatile") }
+
+    fatal(
+        "page fault",
+        frame,
+        format_args!("accessing {:#x}, error code {:#x}\n", address, error_code),
+        backtrace::frame_pointer()
+    );
+}
+
+/// Reports an exception that can't be recovered from to every console and halts
+fn fatal(name: &str, frame: &ExceptionStackFrame, details: fmt::Arguments, handler_frame_pointer: usize) -> ! {
+    unsafe { cpu::disable_interrupts() }
+
+    // The handler's frame holds the frame pointer of the interrupted code
+    let interrupted = backtrace::saved_frame_pointer(handler_frame_pointer);
+    let backtrace = Backtrace::from_interrupt(frame.instruction_pointer as usize, interrupted);
+
+    let consoles = unsafe { console::take_over() };
+    consoles.print_all(Level::Error, format_args!(
+        "\nException: {}\n{}{}\n\n{}",
+        name, details, frame, backtrace
+    ));
+
+    cpu::halt_forever()
+}
@@ -0,0 +1,79 @@
+use core::mem;
+
+/// Present, 64 bit interrupt gate, ring 0
+const INTERRUPT_GATE_OPTIONS: u16 = 0x8E00;
+
+/// Represents the interrupt descriptor table
+pub struct Idt([IdtEntry; 256]);
+
+impl Idt {
+    pub const fn new() -> Self {
+        Idt([IdtEntry::missing(); 256])
+    }
+
+    /// Sets the handler for the given interrupt vector to the function at the given address
+    pub fn set_handler(&mut self, vector: u8, handler: usize) {
+        self.0[vector as usize] = IdtEntry::new(code_selector(), handler);
+    }
+
+    /// Loads this table into the CPU
+    pub fn load(&'static self) {
+        let pointer = IdtPointer {
+            limit: (mem::size_of::<Self>() - 1) as u16,
+            base: self as *const _ as u64,
+        };
+
+        unsafe { asm!("lidt ($0)" :: "r"(&pointer) : "memory" : "volatile") }
+    }
+}
+
+/// Represents a gate descriptor in the IDT
+#[derive(Copy, Clone)]
+#[repr(C)]
+struct IdtEntry {
+    offset_low: u16,
+    selector: u16,
+    options: u16,
+    offset_middle: u16,
+    offset_high: u32,
+    reserved: u32,
+}
+
+impl IdtEntry {
+    /// Creates a non-present entry
+    const fn missing() -> Self {
+        IdtEntry {
+            offset_low: 0,
+            selector: 0,
+            options: 0,
+            offset_middle: 0,
+            offset_high: 0,
+            reserved: 0,
+        }
+    }
+
+    fn new(selector: u16, handler: usize) -> Self {
+        IdtEntry {
+            offset_low: handler as u16,
+            selector: selector,
+            options: INTERRUPT_GATE_OPTIONS,
+            offset_middle: (handler >> 16) as u16,
+            offset_high: (handler >> 32) as u32,
+            reserved: 0,
+        }
+    }
+}
+
+/// The operand of `lidt`
+#[repr(C, packed)]
+struct IdtPointer {
+    limit: u16,
+    base: u64,
+}
+
+/// Reads the current code segment selector
+fn code_selector() -> u16 {
+    let selector: u16;
+    unsafe { asm!("mov %cs, $0" : "=r"(selector)) }
+    selector
+}
@@ -0,0 +1,27 @@
+//! Handlers for the IRQs of the programmable interrupt controllers
+
+use drivers::{pic, pit};
+use status;
+use super::exceptions::ExceptionStackFrame;
+
+pub extern "x86-interrupt" fn timer(_frame: &mut ExceptionStackFrame) {
+    pit::on_timer();
+    status::on_timer();
+    pic::end_of_interrupt(pic::TIMER_IRQ);
+}
+
+/// Spurious IRQs from the master mustn't be acknowledged, as nothing is in service
+pub extern "x86-interrupt" fn master_spurious(_frame: &mut ExceptionStackFrame) {
+    if pic::in_service(pic::MASTER_SPURIOUS_IRQ) {
+        pic::end_of_interrupt(pic::MASTER_SPURIOUS_IRQ);
+    }
+}
+
+/// Spurious IRQs from the slave still went through the master's cascade, which must be acknowledged
+pub extern "x86-interrupt" fn slave_spurious(_frame: &mut ExceptionStackFrame) {
+    if pic::in_service(pic::SLAVE_SPURIOUS_IRQ) {
+        pic::end_of_interrupt(pic::SLAVE_SPURIOUS_IRQ);
+    } else {
+        pic::end_of_interrupt(pic::MASTER_SPURIOUS_IRQ);
+    }
+}
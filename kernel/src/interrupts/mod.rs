@@ -0,0 +1,47 @@
+//! Interrupt handling
+
+mod idt;
+mod exceptions;
+mod irq;
+
+use spin::Once;
+use cpu;
+use drivers::{pic, pit};
+use self::idt::Idt;
+
+/// Frequency of the timer interrupt in Hz
+pub const TIMER_FREQUENCY: u32 = 100;
+
+static IDT: Once<Idt> = Once::new();
+
+/// Sets up and loads the interrupt descriptor table, and remaps the IRQs past the exceptions with
+/// all of them masked
+pub fn init() {
+    let idt = IDT.call_once(|| {
+        let mut idt = Idt::new();
+        idt.set_handler(0, exceptions::divide_error as usize);
+        idt.set_handler(3, exceptions::breakpoint as usize);
+        idt.set_handler(6, exceptions::invalid_opcode as usize);
+        idt.set_handler(8, exceptions::double_fault as usize);
+        idt.set_handler(13, exceptions::general_protection_fault as usize);
+        idt.set_handler(14, exceptions::page_fault as usize);
+        idt.set_handler(irq_vector(pic::TIMER_IRQ), irq::timer as usize);
+        idt.set_handler(irq_vector(pic::MASTER_SPURIOUS_IRQ), irq::master_spurious as usize);
+        idt.set_handler(irq_vector(pic::SLAVE_SPURIOUS_IRQ), irq::slave_spurious as usize);
+        idt
+    });
+
+    idt.load();
+    pic::init();
+}
+
+/// Starts the timer interrupt and enables interrupts
+pub fn enable() {
+    pit::start_timer(TIMER_FREQUENCY);
+    pic::unmask(pic::TIMER_IRQ);
+    unsafe { cpu::enable_interrupts() }
+}
+
+fn irq_vector(irq: u8) -> u8 {
+    pic::IRQ_OFFSET + irq
+}
@@ -1,3 +1,5 @@
+use core::marker::PhantomData;
+
 /// Reads u8 from given port
 pub unsafe fn inb(port: u16) -> u8 {
     let inb: u8;
@@ -10,25 +12,88 @@ pub unsafe fn outb(port: u16, value: u8) {
     asm!("outb %al, %dx" :: "{dx}"(port), "{al}"(value));
 }
 
-/// Represents a port to be accessed through inb and outb
-pub struct IOPort {
+/// Reads u16 from given port
+pub unsafe fn inw(port: u16) -> u16 {
+    let inw: u16;
+    asm!("inw %dx, %ax" : "={ax}"(inw) : "{dx}"(port) :: "volatile");
+    return inw;
+}
+
+/// Writes a u16 to the given port
+pub unsafe fn outw(port: u16, value: u16) {
+    asm!("outw %ax, %dx" :: "{dx}"(port), "{ax}"(value));
+}
+
+/// Reads u32 from given port
+pub unsafe fn inl(port: u16) -> u32 {
+    let inl: u32;
+    asm!("inl %dx, %eax" : "={eax}"(inl) : "{dx}"(port) :: "volatile");
+    return inl;
+}
+
+/// Writes a u32 to the given port
+pub unsafe fn outl(port: u16, value: u32) {
+    asm!("outl %eax, %dx" :: "{dx}"(port), "{eax}"(value));
+}
+
+/// A value which can be read from and written to a port, a byte, word or double word at a time
+pub trait PortValue: Copy {
+    unsafe fn read(port: u16) -> Self;
+    unsafe fn write(port: u16, value: Self);
+}
+
+impl PortValue for u8 {
+    unsafe fn read(port: u16) -> u8 {
+        inb(port)
+    }
+
+    unsafe fn write(port: u16, value: u8) {
+        outb(port, value)
+    }
+}
+
+impl PortValue for u16 {
+    unsafe fn read(port: u16) -> u16 {
+        inw(port)
+    }
+
+    unsafe fn write(port: u16, value: u16) {
+        outw(port, value)
+    }
+}
+
+impl PortValue for u32 {
+    unsafe fn read(port: u16) -> u32 {
+        inl(port)
+    }
+
+    unsafe fn write(port: u16, value: u32) {
+        outl(port, value)
+    }
+}
+
+/// Represents a port to be accessed through inb and outb, or the wider instructions for u16 and
+/// u32 ports
+pub struct IOPort<T: PortValue = u8> {
     port: u16,
+    value: PhantomData<T>,
 }
 
-impl IOPort {
+impl<T: PortValue> IOPort<T> {
     pub const fn new(port: u16) -> Self {
         IOPort {
             port: port,
+            value: PhantomData,
         }
     }
 
-    /// Writes a byte to this port
-    pub fn write(&self, value: u8) {
-        unsafe { outb(self.port, value) }
+    /// Writes a value to this port
+    pub fn write(&self, value: T) {
+        unsafe { T::write(self.port, value) }
     }
 
-    /// Reads a byte from this port
-    pub fn read(&self) -> u8 {
-        unsafe { inb(self.port) }
+    /// Reads a value from this port
+    pub fn read(&self) -> T {
+        unsafe { T::read(self.port) }
     }
 }
@@ -1,16 +1,42 @@
 //! Lang items
 
+use core::fmt;
+use core::sync::atomic::{AtomicBool, Ordering};
+use console::{self, Level};
+use cpu::{self, Registers};
+use backtrace::Backtrace;
+
+/// Set once a panic has begun, to catch panics while panicking
+static PANICKING: AtomicBool = AtomicBool::new(false);
+
 #[lang = "eh_personality"]
 #[no_mangle]
 #[allow(private_no_mangle_fns)] // publicity is not required, but no mangle is
 extern fn eh_personality() {}
 
-// TODO error message print
 #[lang = "panic_fmt"]
 #[no_mangle]
 #[allow(private_no_mangle_fns)] // publicity is not required, but no mangle is
-extern fn rust_begin_panic() -> ! {
-    loop {
-        // Spin
+extern fn rust_begin_panic(message: fmt::Arguments, file: &'static str, line: u32, column: u32) -> ! {
+    unsafe { cpu::disable_interrupts() }
+    let registers = Registers::capture();
+
+    // Printing the panic panicked too, so give up without printing
+    if PANICKING.swap(true, Ordering::SeqCst) {
+        cpu::halt_forever();
     }
+
+    let backtrace = Backtrace::capture();
+
+    // Whatever held the consoles will never run again
+    let consoles = unsafe { console::take_over() };
+    consoles.print_all(Level::Error, format_args!(
+        "\nKernel panic at {}:{}:{}\n{}\n\n{}\n\n{}",
+        file, line, column, message, registers, backtrace
+    ));
+
+    #[cfg(feature = "kernel-test")]
+    ::testing::exit(::testing::ExitCode::Failure);
+
+    cpu::halt_forever()
 }
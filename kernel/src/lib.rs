@@ -1,6 +1,7 @@
 #![no_std]
 
 #![feature(asm)]
+#![feature(abi_x86_interrupt)]
 #![feature(lang_items)]
 #![feature(const_fn)]
 #![feature(const_unique_new)]
@@ -9,57 +10,103 @@
 #![feature(try_from)]
 #![feature(type_ascription)]
 
+#[cfg(test)]
+extern crate std;
+#[cfg(not(test))]
 extern crate rlibc;
 extern crate volatile;
 extern crate spin;
 
+// Before the consoles, which log warnings of their own
+#[macro_use]
+mod log;
+#[macro_use]
+mod console;
+#[cfg(feature = "kernel-test")]
+#[macro_use]
+mod testing;
+#[cfg(not(test))]
 mod lang;
+mod cpu;
 mod io;
-#[macro_use]
+mod time;
+mod multiboot;
+mod symbols;
+mod memory;
+mod backtrace;
+mod interrupts;
+#[cfg(feature = "kernel-test")]
+mod paging;
 mod drivers;
+mod gfx;
+mod tui;
+mod status;
+mod clipboard;
+mod input;
+mod pointer;
+mod splash;
+mod boot_menu;
 
-use drivers::vga::{self, VgaColor, Color};
+use drivers::vga;
+use drivers::input::InputEvent;
 
 const FLOWER: &'static str = include_str!("resources/art/flower.txt");
 const FLOWER_STEM: &'static str = include_str!("resources/art/flower_stem.txt");
 
-/// Kernel main function
-#[no_mangle]
-pub extern fn kmain() -> ! {
-    vga::WRITER.lock().fill_screen(Color::Black);
+/// Glyph of a flower put in place of the sun, `☼`, to start the boot message with on the VGA
+/// text screen
+const FLOWER_GLYPH: char = '☼';
+const FLOWER_GLYPH_ROWS: [u8; 16] = [
+    0x00, 0x00, 0x18, 0x5A, 0x3C, 0xE7, 0x3C, 0x5A, 0x18, 0x10, 0x16, 0x18, 0x10, 0x10, 0x00, 0x00,
+];
 
-    // Print flower
-    vga::WRITER.lock().set_color(
-        VgaColor::new(Color::LightBlue, Color::Black)
-    );
-    print!("\n{}", FLOWER);
-    vga::WRITER.lock().set_color(
-        VgaColor::new(Color::Green, Color::Black)
-    );
-    print!("{}", FLOWER_STEM);
+/// Kernel main function. Host tests build it too, so that what it uses isn't dead code to them.
+#[no_mangle]
+pub extern fn kmain(multiboot_info: usize) -> ! {
+    time::init();
 
-    // Reset colors
-    vga::WRITER.lock().set_color(
-        VgaColor::new(Color::White, Color::Black)
-    );
+    let boot_info = unsafe { multiboot::init(multiboot_info) };
+    let command_line = boot_info.command_line().unwrap_or("");
+    console::init(command_line);
+    symbols::init(boot_info);
+    memory::init(boot_info);
+    interrupts::init();
 
-    // Reset cursor position to (0, 0)
-    // It's hackish but it looks better
-    vga::WRITER.lock().set_cursor_pos((0, 0));
+    #[cfg(feature = "kernel-test")]
+    testing::run();
 
-    // Print boot message
-    vga::WRITER.lock().write_str_colored(
-        "Flower kernel boot!\n-------------------\n\n",
-         VgaColor::new(Color::Green, Color::Black)
-    ).expect("Color code should be valid");
+    // Without the splash, print flower in blue and green, then the boot message over its top left
+    // corner, which is hackish but looks better
+    print!("\x1b[2J\x1b[H");
+    if !splash::show(command_line) {
+        print!("\x1b[94m\n{}\x1b[32m{}\x1b[H", FLOWER, FLOWER_STEM);
+    }
+    if !console::on_framebuffer() {
+        let index = vga::encode_cp437(FLOWER_GLYPH).expect("Flower glyph should be in code page 437");
+        vga::set_glyph(index, &FLOWER_GLYPH_ROWS).expect("Flower glyph should be of a font's height");
+    }
+    print!("\x1b[32m{} Flower kernel boot!\n---------------------\n\n\x1b[0m", FLOWER_GLYPH);
 
     drivers::ps2::PS2.lock().initialize();
 
-    halt()
-}
+    // The boot options, the mouse pointer, screen dumps and the status bar are only of the VGA
+    // text screen. The boot options poll the keyboard, so they're shown before the mouse sends
+    // anything.
+    if !console::on_framebuffer() {
+        boot_menu::show(command_line);
+        drivers::mouse::init();
+        vga::dump_if_requested(command_line);
+        status::init();
+    }
+
+    interrupts::enable();
 
-// TODO move somewhere else
-fn halt() -> ! {
-    unsafe { asm!("hlt") }
-    loop {} // Required to trick rust
+    loop {
+        match drivers::input::poll() {
+            Some(InputEvent::Key(event)) => console::handle_key(event),
+            Some(InputEvent::Mouse(event)) => pointer::handle_mouse(event),
+            // Wait for the next timer tick before polling again
+            None => cpu::halt(),
+        }
+    }
 }
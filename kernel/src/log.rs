@@ -0,0 +1,172 @@
+//! Kernel logging, kept in a ring buffer which can be re-read like `dmesg`
+
+use core::{fmt, str};
+use spin::Mutex;
+use console::{self, Level};
+use time;
+
+/// Maximum amount of records kept before the oldest is overwritten
+const LOG_CAPACITY: usize = 128;
+
+/// Maximum length of a record's message in bytes. Longer messages are truncated.
+const MESSAGE_LENGTH: usize = 120;
+
+const EMPTY_RECORD: Record = Record {
+    timestamp: 0,
+    level: Level::Trace,
+    subsystem: "",
+    message: [0; MESSAGE_LENGTH],
+    length: 0,
+};
+
+pub static LOG: Mutex<LogBuffer> = Mutex::new(LogBuffer::new());
+
+macro_rules! log {
+    ($level:expr, $subsystem:expr, $($arg:tt)*) => ({
+        $crate::log::log($level, $subsystem, format_args!($($arg)*));
+    });
+}
+
+macro_rules! error {
+    ($subsystem:expr, $($arg:tt)*) => (log!($crate::console::Level::Error, $subsystem, $($arg)*));
+}
+
+macro_rules! warn {
+    ($subsystem:expr, $($arg:tt)*) => (log!($crate::console::Level::Warn, $subsystem, $($arg)*));
+}
+
+macro_rules! info {
+    ($subsystem:expr, $($arg:tt)*) => (log!($crate::console::Level::Info, $subsystem, $($arg)*));
+}
+
+macro_rules! debug {
+    ($subsystem:expr, $($arg:tt)*) => (log!($crate::console::Level::Debug, $subsystem, $($arg)*));
+}
+
+macro_rules! trace {
+    ($subsystem:expr, $($arg:tt)*) => (log!($crate::console::Level::Trace, $subsystem, $($arg)*));
+}
+
+/// Logs a message for the given subsystem, printing it to the consoles and storing it in the log
+pub fn log(level: Level, subsystem: &'static str, args: fmt::Arguments) {
+    let mut record = Record {
+        timestamp: time::uptime_micros(),
+        level: level,
+        subsystem: subsystem,
+        ..EMPTY_RECORD
+    };
+    let _ = fmt::write(&mut record, args);
+
+    print_record(&record);
+    LOG.lock().push(record);
+}
+
+/// Prints every record still held in the log to the consoles
+pub fn dmesg() {
+    let log = LOG.lock();
+    for record in log.records() {
+        print_record(record);
+    }
+}
+
+fn print_record(record: &Record) {
+    console::CONSOLES.lock().print(Some(record.level), format_args!(
+        "[{:5}.{:06}] {}: {}\n",
+        record.timestamp / 1_000_000,
+        record.timestamp % 1_000_000,
+        record.subsystem,
+        record.message()
+    ));
+}
+
+/// Represents a single logged message
+#[derive(Copy, Clone)]
+pub struct Record {
+    /// Microseconds since boot
+    pub timestamp: u64,
+    pub level: Level,
+    pub subsystem: &'static str,
+    message: [u8; MESSAGE_LENGTH],
+    length: usize,
+}
+
+impl Record {
+    pub fn message(&self) -> &str {
+        // Only whole chars are ever written to the message
+        unsafe { str::from_utf8_unchecked(&self.message[..self.length]) }
+    }
+}
+
+impl fmt::Write for Record {
+    /// Appends to the message, truncating on a char boundary once full
+    fn write_str(&mut self, str: &str) -> fmt::Result {
+        for char in str.chars() {
+            let char_length = char.len_utf8();
+            if self.length + char_length > MESSAGE_LENGTH {
+                break;
+            }
+
+            char.encode_utf8(&mut self.message[self.length..]);
+            self.length += char_length;
+        }
+
+        Ok(())
+    }
+}
+
+/// Ring buffer holding the most recent records
+pub struct LogBuffer {
+    records: [Record; LOG_CAPACITY],
+    /// Index that the next record will be written to
+    head: usize,
+    count: usize,
+}
+
+impl LogBuffer {
+    const fn new() -> Self {
+        LogBuffer {
+            records: [EMPTY_RECORD; LOG_CAPACITY],
+            head: 0,
+            count: 0,
+        }
+    }
+
+    /// Adds a record, overwriting the oldest if full
+    pub fn push(&mut self, record: Record) {
+        self.records[self.head] = record;
+        self.head = (self.head + 1) % LOG_CAPACITY;
+        if self.count < LOG_CAPACITY {
+            self.count += 1;
+        }
+    }
+
+    /// Iterates over the held records, from oldest to newest
+    pub fn records(&self) -> Records {
+        Records {
+            buffer: self,
+            index: 0,
+        }
+    }
+}
+
+/// Iterator over the records of a `LogBuffer`, from oldest to newest
+pub struct Records<'a> {
+    buffer: &'a LogBuffer,
+    index: usize,
+}
+
+impl<'a> Iterator for Records<'a> {
+    type Item = &'a Record;
+
+    fn next(&mut self) -> Option<&'a Record> {
+        if self.index >= self.buffer.count {
+            return None;
+        }
+
+        let oldest = (self.buffer.head + LOG_CAPACITY - self.buffer.count) % LOG_CAPACITY;
+        let record = &self.buffer.records[(oldest + self.index) % LOG_CAPACITY];
+        self.index += 1;
+
+        Some(record)
+    }
+}
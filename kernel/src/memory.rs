@@ -0,0 +1,48 @@
+//! Accounting of physical memory, from the bootloader's memory map
+
+use core::cmp;
+use core::sync::atomic::{AtomicUsize, Ordering};
+use multiboot::BootInformation;
+use symbols;
+
+/// Where the linker script loads the kernel
+const KERNEL_START: usize = 0x10_0000;
+
+static AVAILABLE: AtomicUsize = AtomicUsize::new(0);
+static FREE: AtomicUsize = AtomicUsize::new(0);
+
+/// Adds up the RAM in the memory map, and what's left of it after the kernel and the boot
+/// information
+pub fn init(boot_info: &BootInformation) {
+    let kernel_end = symbols::kernel_end(boot_info).unwrap_or(KERNEL_START);
+    let boot_info_start = boot_info.address();
+    let taken = [(KERNEL_START, kernel_end), (boot_info_start, boot_info_start + boot_info.total_size())];
+
+    let mut available = 0;
+    let mut free = 0;
+    for area in boot_info.memory_map().filter(|area| area.is_available()) {
+        let (start, end) = (area.base as usize, area.end() as usize);
+        available += end - start;
+        free += end - start - taken.iter().map(|&range| overlap((start, end), range)).sum::<usize>();
+    }
+
+    AVAILABLE.store(available, Ordering::Relaxed);
+    FREE.store(free, Ordering::Relaxed);
+    debug!("mem", "{} KiB available, {} KiB free", available / 1024, free / 1024);
+}
+
+/// Gets the bytes of RAM which the memory map says are available
+pub fn available() -> usize {
+    AVAILABLE.load(Ordering::Relaxed)
+}
+
+/// Gets the bytes of available RAM not taken by the kernel or the boot information. There's no
+/// allocator yet, so nothing else takes any.
+pub fn free() -> usize {
+    FREE.load(Ordering::Relaxed)
+}
+
+/// Gets the length of the overlap of two ranges, each with an exclusive end
+fn overlap(first: (usize, usize), second: (usize, usize)) -> usize {
+    cmp::min(first.1, second.1).saturating_sub(cmp::max(first.0, second.0))
+}
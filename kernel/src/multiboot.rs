@@ -0,0 +1,229 @@
+//! Multiboot 2 boot information
+//!
+//! See the [spec](https://www.gnu.org/software/grub/manual/multiboot2/multiboot.html#Boot-information-format)
+
+use core::{slice, str};
+use spin::Once;
+
+const TAG_END: u32 = 0;
+const TAG_COMMAND_LINE: u32 = 1;
+const TAG_MEMORY_MAP: u32 = 6;
+const TAG_FRAMEBUFFER: u32 = 8;
+
+/// Memory map area type for RAM which is free to use
+const MEMORY_AVAILABLE: u32 = 1;
+
+const NO_DATA: &'static [u8] = &[];
+
+/// Framebuffer type with direct RGB pixels, rather than a palette or EGA text
+const FRAMEBUFFER_TYPE_RGB: u8 = 1;
+
+static BOOT_INFO: Once<BootInformation> = Once::new();
+
+/// Loads the boot information at the address given by the bootloader in `ebx`
+pub unsafe fn init(address: usize) -> &'static BootInformation {
+    BOOT_INFO.call_once(|| BootInformation { address: address })
+}
+
+/// Gets the boot information, if it has been loaded
+pub fn info() -> Option<&'static BootInformation> {
+    BOOT_INFO.try()
+}
+
+/// Represents the boot information structure passed by the bootloader
+pub struct BootInformation {
+    address: usize,
+}
+
+impl BootInformation {
+    /// Gets the address of the boot information
+    pub fn address(&self) -> usize {
+        self.address
+    }
+
+    /// Gets the total size of the boot information, including the fixed header
+    pub fn total_size(&self) -> usize {
+        unsafe { *(self.address as *const u32) as usize }
+    }
+
+    /// Iterates over all tags in the boot information
+    pub fn tags(&self) -> TagIter {
+        TagIter {
+            // Tags start after the 8 byte fixed header
+            current: (self.address + 8) as *const Tag,
+            end: self.address + self.total_size(),
+        }
+    }
+
+    /// Gets the first tag of the given type
+    pub fn tag(&self, typ: u32) -> Option<&'static Tag> {
+        self.tags().find(|tag| tag.typ == typ)
+    }
+
+    /// Gets the kernel command line given by the bootloader
+    pub fn command_line(&self) -> Option<&'static str> {
+        let tag = self.tag(TAG_COMMAND_LINE)?;
+        let bytes = tag.data();
+
+        // The string is null terminated
+        let length = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+        str::from_utf8(&bytes[..length]).ok()
+    }
+
+    /// Gets the areas of physical memory described by the bootloader, which are empty if it
+    /// gave no memory map
+    pub fn memory_map(&self) -> MemoryAreaIter {
+        let data = self.tag(TAG_MEMORY_MAP).map_or(NO_DATA, |tag| tag.data());
+
+        // The entries follow their size and version
+        if data.len() < 8 {
+            return MemoryAreaIter { data: NO_DATA, entry_size: 0 };
+        }
+
+        MemoryAreaIter {
+            data: &data[8..],
+            entry_size: read_u32(data, 0) as usize,
+        }
+    }
+
+    /// Gets the linear framebuffer set up by the bootloader, if it gave one with RGB pixels
+    pub fn framebuffer(&self) -> Option<FramebufferInfo> {
+        let data = self.tag(TAG_FRAMEBUFFER)?.data();
+
+        // GRUB pads the common fields to 24 bytes before the color info
+        if data.len() < 30 || data[21] != FRAMEBUFFER_TYPE_RGB {
+            return None;
+        }
+
+        Some(FramebufferInfo {
+            address: (read_u32(data, 0) as u64 | (read_u32(data, 4) as u64) << 32) as usize,
+            pitch: read_u32(data, 8) as usize,
+            width: read_u32(data, 12) as usize,
+            height: read_u32(data, 16) as usize,
+            bits_per_pixel: data[20],
+            red: ColorField { position: data[24], size: data[25] },
+            green: ColorField { position: data[26], size: data[27] },
+            blue: ColorField { position: data[28], size: data[29] },
+        })
+    }
+}
+
+/// Represents a linear framebuffer given by the bootloader
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct FramebufferInfo {
+    pub address: usize,
+    /// Bytes between the start of each row of pixels
+    pub pitch: usize,
+    pub width: usize,
+    pub height: usize,
+    pub bits_per_pixel: u8,
+    pub red: ColorField,
+    pub green: ColorField,
+    pub blue: ColorField,
+}
+
+/// Represents where a color channel is in a pixel
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ColorField {
+    /// Lowest bit of the channel
+    pub position: u8,
+    /// Amount of bits in the channel
+    pub size: u8,
+}
+
+/// Represents an area of physical memory in the memory map
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct MemoryArea {
+    pub base: u64,
+    pub length: u64,
+    pub typ: u32,
+}
+
+impl MemoryArea {
+    /// Checks if the area is RAM which is free to use
+    pub fn is_available(&self) -> bool {
+        self.typ == MEMORY_AVAILABLE
+    }
+
+    /// Gets the address just past the end of the area
+    pub fn end(&self) -> u64 {
+        self.base.saturating_add(self.length)
+    }
+}
+
+/// Iterator over the areas of the memory map
+pub struct MemoryAreaIter {
+    data: &'static [u8],
+    entry_size: usize,
+}
+
+impl Iterator for MemoryAreaIter {
+    type Item = MemoryArea;
+
+    fn next(&mut self) -> Option<MemoryArea> {
+        // Entries are at least the 20 bytes of the base, length and type
+        if self.data.len() < self.entry_size || self.entry_size < 20 {
+            return None;
+        }
+
+        let entry = &self.data[..self.entry_size];
+        self.data = &self.data[self.entry_size..];
+
+        Some(MemoryArea {
+            base: read_u64(entry, 0),
+            length: read_u64(entry, 8),
+            typ: read_u32(entry, 16),
+        })
+    }
+}
+
+/// Represents the header of a boot information tag
+#[repr(C)]
+pub struct Tag {
+    pub typ: u32,
+    pub size: u32,
+}
+
+impl Tag {
+    /// Gets the data following this tag's header
+    pub fn data(&self) -> &'static [u8] {
+        let start = self as *const Tag as usize + 8;
+        unsafe { slice::from_raw_parts(start as *const u8, self.size as usize - 8) }
+    }
+}
+
+/// Iterator over the tags of the boot information
+pub struct TagIter {
+    current: *const Tag,
+    end: usize,
+}
+
+impl Iterator for TagIter {
+    type Item = &'static Tag;
+
+    fn next(&mut self) -> Option<&'static Tag> {
+        if self.current as usize >= self.end {
+            return None;
+        }
+
+        let tag = unsafe { &*self.current };
+        if tag.typ == TAG_END {
+            return None;
+        }
+
+        // Tags are padded to 8 byte alignment
+        let next = (self.current as usize + tag.size as usize + 7) & !7;
+        self.current = next as *const Tag;
+
+        Some(tag)
+    }
+}
+
+fn read_u32(data: &[u8], offset: usize) -> u32 {
+    (data[offset] as u32) | (data[offset + 1] as u32) << 8 |
+        (data[offset + 2] as u32) << 16 | (data[offset + 3] as u32) << 24
+}
+
+fn read_u64(data: &[u8], offset: usize) -> u64 {
+    read_u32(data, offset) as u64 | (read_u32(data, offset + 4) as u64) << 32
+}
@@ -0,0 +1,71 @@
+//! Inspection of the active page tables, for the kernel tests to check what's mapped
+
+const PRESENT: u64 = 1 << 0;
+const HUGE_PAGE: u64 = 1 << 7;
+
+/// Mask of the physical address in a page table entry
+const ADDRESS_MASK: u64 = 0x000F_FFFF_FFFF_F000;
+
+/// Reads the physical address of the active level 4 table
+fn active_table() -> u64 {
+    let cr3: u64;
+    unsafe { asm!("mov %cr3, $0" : "=r"(cr3) ::: "volatile") }
+    cr3 & ADDRESS_MASK
+}
+
+/// Translates a virtual address to a physical address through the active page tables, or `None`
+/// if it isn't mapped. The tables must be identity mapped, as set up at boot.
+pub fn translate(address: usize) -> Option<usize> {
+    let address = address as u64;
+    let mut table = active_table();
+
+    // Walk down from the level 4 table. Levels 3 and 2 may map 1GiB and 2MiB pages.
+    for level in (1..5).rev() {
+        let shift = 12 + 9 * (level - 1);
+        let index = (address >> shift) & 0x1FF;
+        let entry = unsafe { *((table + index * 8) as *const u64) };
+
+        if entry & PRESENT == 0 {
+            return None;
+        }
+
+        if level == 1 || (level < 4 && entry & HUGE_PAGE != 0) {
+            let offset = address & ((1 << shift) - 1);
+            return Some(((entry & ADDRESS_MASK & !((1 << shift) - 1)) | offset) as usize);
+        }
+
+        table = entry & ADDRESS_MASK;
+    }
+
+    None
+}
+
+#[cfg(feature = "kernel-test")]
+pub mod tests {
+    use testing::{TestCase, TestResult};
+    use super::translate;
+
+    pub const TESTS: &'static [TestCase] = &[
+        test_case!(vga_memory_identity_mapped),
+        test_case!(kernel_identity_mapped),
+        test_case!(unmapped_address),
+    ];
+
+    fn vga_memory_identity_mapped() -> TestResult {
+        test_assert_eq!(translate(0xB8000), Some(0xB8000));
+        test_assert_eq!(translate(0xB8F9E), Some(0xB8F9E));
+        Ok(())
+    }
+
+    fn kernel_identity_mapped() -> TestResult {
+        let address = translate as usize;
+        test_assert_eq!(translate(address), Some(address));
+        Ok(())
+    }
+
+    fn unmapped_address() -> TestResult {
+        // Only the first level 4 entry is mapped at boot
+        test_assert_eq!(translate(1 << 39), None);
+        Ok(())
+    }
+}
@@ -0,0 +1,217 @@
+//! The mouse pointer on the text screen. Dragging with the left button selects text on the active
+//! terminal, which is copied to the clipboard when the button is let go, and the middle button
+//! pastes the clipboard.
+
+use core::cmp;
+use spin::Mutex;
+use clipboard::CLIPBOARD;
+use console;
+use drivers::mouse::{Buttons, MouseEvent};
+use drivers::vga;
+
+/// Mouse counts moved for each column and row, with rows about twice as tall as columns are wide
+const COUNTS_PER_COLUMN: i32 = 8;
+const COUNTS_PER_ROW: i32 = 16;
+
+pub static POINTER: Mutex<Pointer> = Mutex::new(Pointer::new());
+
+/// Represents what a mouse event does to the screen
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Action {
+    /// Only the pointer moved, if anything
+    Move,
+    /// Selecting started, clearing the old selection
+    Clear,
+    /// The selection now spans between two cells
+    Select((usize, usize), (usize, usize)),
+    /// The selection spanning between two cells was finished, and should be copied
+    Copy((usize, usize), (usize, usize)),
+    Paste,
+}
+
+/// Tracks the pointer's cell on the screen and the buttons
+pub struct Pointer {
+    position: (usize, usize),
+    /// Movement not yet adding up to a whole row or column
+    remainder: (i32, i32),
+    buttons: Buttons,
+    /// Cell the left button went down on, while dragging
+    anchor: Option<(usize, usize)>,
+    /// Index of the terminal the pointer is shown on
+    terminal: usize,
+}
+
+impl Pointer {
+    pub const fn new() -> Self {
+        Pointer {
+            position: (0, 0),
+            remainder: (0, 0),
+            buttons: Buttons::new(),
+            anchor: None,
+            terminal: 0,
+        }
+    }
+
+    /// Gets the pointer's cell on the screen, as `(row, column)`
+    pub fn position(&self) -> (usize, usize) {
+        self.position
+    }
+
+    /// Moves the pointer within a screen of the given size and follows the buttons, returning what
+    /// the event does
+    pub fn handle_event(&mut self, event: &MouseEvent, (width, height): (usize, usize)) -> Action {
+        // Rows go down the screen, while the mouse counts up
+        let (rows, columns) = (self.remainder.0 - event.dy as i32, self.remainder.1 + event.dx as i32);
+        self.remainder = (rows % COUNTS_PER_ROW, columns % COUNTS_PER_COLUMN);
+        self.position = (
+            offset(self.position.0, rows / COUNTS_PER_ROW, height),
+            offset(self.position.1, columns / COUNTS_PER_COLUMN, width),
+        );
+
+        let previous = self.buttons;
+        self.buttons = event.buttons;
+
+        if event.buttons.middle && !previous.middle {
+            return Action::Paste;
+        }
+
+        match (event.buttons.left, previous.left, self.anchor) {
+            (true, false, _) => {
+                self.anchor = Some(self.position);
+                Action::Clear
+            }
+            (true, true, Some(anchor)) => Action::Select(anchor, self.position),
+            (false, true, Some(anchor)) => {
+                self.anchor = None;
+                if anchor == self.position {
+                    Action::Clear
+                } else {
+                    Action::Copy(anchor, self.position)
+                }
+            }
+            _ => Action::Move,
+        }
+    }
+}
+
+/// Moves a row or column by an amount, staying within `0..size`
+fn offset(position: usize, amount: i32, size: usize) -> usize {
+    let moved = cmp::max(position as i32 + amount, 0) as usize;
+    cmp::min(moved, size.saturating_sub(1))
+}
+
+/// Moves the pointer on the active terminal and selects, copies or pastes for a mouse event
+pub fn handle_mouse(event: MouseEvent) {
+    let mut pointer = POINTER.lock();
+    let index = vga::active();
+
+    // The pointer follows the active terminal
+    if pointer.terminal != index {
+        vga::TERMINALS[pointer.terminal].lock().buffer_mut().buffer_mut().set_pointer(None);
+        pointer.terminal = index;
+    }
+
+    let mut terminal = vga::TERMINALS[index].lock();
+    let size = terminal.buffer().buffer().screen_size();
+    let action = pointer.handle_event(&event, size);
+
+    {
+        let buffer = terminal.buffer_mut().buffer_mut();
+        buffer.set_pointer(Some(pointer.position()));
+
+        match action {
+            Action::Move | Action::Paste => {}
+            Action::Clear => buffer.select(None),
+            Action::Select(from, to) => buffer.select(Some((from, to))),
+            Action::Copy(from, to) => {
+                buffer.select(Some((from, to)));
+
+                let mut clipboard = CLIPBOARD.lock();
+                clipboard.clear();
+                let _ = buffer.write_selection(&mut *clipboard);
+            }
+        }
+    }
+
+    if action == Action::Paste {
+        console::paste(index, &mut *terminal);
+    } else {
+        terminal.flush();
+    }
+}
+
+#[cfg(feature = "kernel-test")]
+pub mod tests {
+    use testing::{TestCase, TestResult};
+    use super::*;
+
+    pub const TESTS: &'static [TestCase] = &[
+        test_case!(moves_by_cells),
+        test_case!(stays_on_screen),
+        test_case!(drag_selects_and_copies),
+        test_case!(click_clears_selection),
+        test_case!(middle_button_pastes),
+    ];
+
+    const SIZE: (usize, usize) = (80, 25);
+
+    fn event(dx: i16, dy: i16, left: bool, middle: bool) -> MouseEvent {
+        MouseEvent {
+            dx: dx,
+            dy: dy,
+            buttons: Buttons { left: left, right: false, middle: middle },
+        }
+    }
+
+    fn moves_by_cells() -> TestResult {
+        let mut pointer = Pointer::new();
+
+        test_assert_eq!(pointer.handle_event(&event(4, -8, false, false), SIZE), Action::Move);
+        test_assert_eq!(pointer.position(), (0, 0));
+
+        // Movement adds up across events
+        pointer.handle_event(&event(4, -8, false, false), SIZE);
+        test_assert_eq!(pointer.position(), (1, 1));
+        pointer.handle_event(&event(20, -32, false, false), SIZE);
+        test_assert_eq!(pointer.position(), (3, 3));
+        Ok(())
+    }
+
+    fn stays_on_screen() -> TestResult {
+        let mut pointer = Pointer::new();
+
+        pointer.handle_event(&event(-100, 100, false, false), SIZE);
+        test_assert_eq!(pointer.position(), (0, 0));
+        pointer.handle_event(&event(i16::max_value(), i16::min_value(), false, false), SIZE);
+        test_assert_eq!(pointer.position(), (24, 79));
+        Ok(())
+    }
+
+    fn drag_selects_and_copies() -> TestResult {
+        let mut pointer = Pointer::new();
+
+        test_assert_eq!(pointer.handle_event(&event(16, -16, true, false), SIZE), Action::Clear);
+        test_assert_eq!(pointer.handle_event(&event(8, 0, true, false), SIZE), Action::Select((1, 2), (1, 3)));
+        test_assert_eq!(pointer.handle_event(&event(0, 16, true, false), SIZE), Action::Select((1, 2), (0, 3)));
+        test_assert_eq!(pointer.handle_event(&event(0, 0, false, false), SIZE), Action::Copy((1, 2), (0, 3)));
+        test_assert_eq!(pointer.handle_event(&event(8, 0, false, false), SIZE), Action::Move);
+        Ok(())
+    }
+
+    fn click_clears_selection() -> TestResult {
+        let mut pointer = Pointer::new();
+
+        test_assert_eq!(pointer.handle_event(&event(0, 0, true, false), SIZE), Action::Clear);
+        test_assert_eq!(pointer.handle_event(&event(0, 0, false, false), SIZE), Action::Clear);
+        Ok(())
+    }
+
+    fn middle_button_pastes() -> TestResult {
+        let mut pointer = Pointer::new();
+
+        test_assert_eq!(pointer.handle_event(&event(0, 0, false, true), SIZE), Action::Paste);
+        // Holding the button doesn't paste again
+        test_assert_eq!(pointer.handle_event(&event(0, 0, false, true), SIZE), Action::Move);
+        Ok(())
+    }
+}
@@ -0,0 +1,143 @@
+//! A graphical boot splash, drawing a flower in a VGA graphics mode for a moment before the
+//! kernel's text output is shown. Picked with `splash=<13h|12h|off>` on the command line. With a
+//! mode set on the Bochs adapter by `video=`, it's drawn on the page the console isn't shown on.
+
+use console;
+use drivers::{bga, framebuffer};
+use drivers::vga::{self, GraphicsMode, Rgb};
+use gfx::{Canvas, Surface, Rect, Rgba};
+use time;
+
+/// How long the splash stays on screen
+const SHOW_MICROS: u64 = 1_500_000;
+
+const SKY: Rgb = Rgb::from_hex(0x000055);
+const GROUND: Rgb = Rgb::from_hex(0x555500);
+const STEM: Rgb = Rgb::from_hex(0x00AA00);
+const PETAL: Rgb = Rgb::from_hex(0x5555FF);
+const PETAL_EDGE: Rgb = Rgb::from_hex(0xFFFFFF);
+const CENTER: Rgb = Rgb::from_hex(0xFFFF55);
+const TITLE: Rgb = Rgb::from_hex(0x55FF55);
+
+/// Light falling on the flower's center, blended over it
+const CLEAR: Rgba = Rgba::new(0xFF, 0xFF, 0xFF, 0x00);
+const DIM: Rgba = Rgba::new(0xFF, 0xFF, 0xFF, 0x80);
+const BRIGHT: Rgba = Rgba::new(0xFF, 0xFF, 0xFF, 0xFF);
+const HIGHLIGHT: [Rgba; 9] = [CLEAR, DIM, CLEAR, DIM, BRIGHT, DIM, CLEAR, DIM, CLEAR];
+
+/// Offsets of the petals from the flower's center, as eighths of their distance
+const PETALS: [(isize, isize); 8] = [
+    (8, 0), (6, 6), (0, 8), (-6, 6), (-8, 0), (-6, -6), (0, -8), (6, -6),
+];
+
+/// Shows the splash picked on the command line, and waits for a moment before putting the
+/// console back. Gives whether it was shown, which it isn't if turned off or on the bootloader's
+/// framebuffer.
+pub fn show(command_line: &str) -> bool {
+    let mode = match mode(command_line) {
+        Some(mode) => mode,
+        None => return false,
+    };
+
+    if console::on_framebuffer() {
+        return show_on_adapter();
+    }
+
+    let mut graphics = match vga::enter_graphics(mode) {
+        Some(graphics) => graphics,
+        None => return false,
+    };
+
+    draw(&mut Canvas::new(&mut graphics));
+    time::wait_micros(SHOW_MICROS);
+
+    if let Err(error) = graphics.leave() {
+        warn!("splash", "couldn't go back to text mode: {:?}", error);
+    }
+
+    true
+}
+
+/// Shows the splash on the back page of the Bochs adapter, if it has the screen with more than a
+/// page, and flips back to the console's page after a moment
+fn show_on_adapter() -> bool {
+    let mut adapter = bga::ADAPTER.lock();
+    let bga = match adapter.as_mut() {
+        Some(bga) if bga.pages() > 1 => bga,
+        _ => return false,
+    };
+
+    draw(&mut Canvas::new(&mut bga.back_buffer()));
+    bga.flip();
+    time::wait_micros(SHOW_MICROS);
+    bga.flip();
+
+    true
+}
+
+/// Gets the graphics mode picked for the splash, or `None` if it's turned off. Mode 13h is used if
+/// none is picked.
+fn mode(command_line: &str) -> Option<GraphicsMode> {
+    let mut mode = Some(GraphicsMode::Mode13h);
+
+    for arg in command_line.split_whitespace() {
+        if !arg.starts_with("splash=") {
+            continue;
+        }
+
+        mode = match &arg["splash=".len()..] {
+            "13h" => Some(GraphicsMode::Mode13h),
+            "12h" => Some(GraphicsMode::Mode12h),
+            "off" => None,
+            name => {
+                warn!("splash", "unknown splash mode `{}`", name);
+                continue;
+            }
+        };
+    }
+
+    mode
+}
+
+/// Draws the flower standing on the ground, with the kernel's name under it, scaled to the
+/// surface
+fn draw<S: Surface>(canvas: &mut Canvas<S>) {
+    let bounds = canvas.bounds();
+    let (width, height) = (bounds.width as isize, bounds.height as isize);
+    let ground = height * 3 / 4;
+    let center = (width / 2, height * 3 / 8);
+    let radius = height as usize / 16;
+    let distance = height / 8;
+
+    canvas.fill(SKY);
+    canvas.fill_rect(Rect::new(0, ground, bounds.width, (height - ground) as usize), GROUND);
+    canvas.draw_rect(bounds, PETAL_EDGE);
+
+    // The flower stands on the ground, and nothing of it is drawn below it
+    canvas.set_clip(Rect::new(0, 0, bounds.width, ground as usize));
+
+    let stem_width = radius / 2 + 1;
+    canvas.fill_rect(Rect::new(center.0 - stem_width as isize / 2, center.1, stem_width,
+                               (ground - center.1) as usize), STEM);
+    let leaf = (ground - center.1) / 2 + center.1;
+    canvas.fill_circle((center.0 - radius as isize, leaf), radius / 2, STEM);
+    canvas.fill_circle((center.0 + radius as isize, leaf + radius as isize), radius / 2, STEM);
+
+    for &(x, y) in PETALS.iter() {
+        // The diagonal petals are about as far as the others
+        let (x, y) = (center.0 + x * distance / 8, center.1 + y * distance / 8);
+        canvas.fill_circle((x, y), radius, PETAL);
+        canvas.circle((x, y), radius, PETAL_EDGE);
+    }
+
+    canvas.fill_circle(center, radius, CENTER);
+    canvas.blit(center.0 - radius as isize / 2, center.1 - radius as isize / 2, 3, &HIGHLIGHT);
+
+    canvas.reset_clip();
+
+    let title = "Flower kernel";
+    let font = framebuffer::font();
+    let title_width = title.len() as isize * 8;
+    canvas.text((width - title_width) / 2, ground + (height - ground - font.height() as isize) / 2,
+                title, &font, TITLE, None);
+}
@@ -0,0 +1,246 @@
+//! A status bar on the bottom row of every virtual terminal, showing the uptime, the wall clock
+//! time, free memory, the active terminal and the keyboard's lock LEDs
+
+use core::fmt::{self, Write};
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use drivers::keyboard::{self, Leds};
+use drivers::rtc::{self, DateTime};
+use drivers::vga::{self, TextBuffer, VgaChar, VgaColor, Color, MAX_COLUMNS};
+use spin::Mutex;
+use memory;
+use time;
+
+const COLOR: VgaColor = VgaColor::new(Color::Black, Color::LightGray);
+/// Color of the names of lit LEDs, with unlit ones grayed out
+const LIT_COLOR: VgaColor = VgaColor::new(Color::Black, Color::LightGreen);
+const UNLIT_COLOR: VgaColor = VgaColor::new(Color::DarkGray, Color::LightGray);
+
+const SEPARATOR: &'static str = " \u{2502} ";
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Whether the terminals have their bottom rows kept for the status bar
+static ENABLED: AtomicBool = AtomicBool::new(false);
+/// Key of the state last shown on every terminal, from `state_key`, or 0 if none is
+static SHOWN: AtomicUsize = AtomicUsize::new(0);
+/// Time read from the real time clock when the status bar started, and the uptime in seconds then.
+/// The clock is only read once, as reading it waits for it to finish updating, which is too slow
+/// for the timer interrupt, and the time shown is counted on from it.
+static CLOCK: Mutex<Option<(DateTime, u64)>> = Mutex::new(None);
+
+/// Keeps the bottom row of every terminal for the status bar, and shows it
+pub fn init() {
+    for terminal in vga::TERMINALS.iter() {
+        terminal.lock().set_fixed_rows(0, 1).expect("Color code should be valid");
+    }
+
+    *CLOCK.lock() = Some((rtc::read(), time::uptime_micros() / 1_000_000));
+    ENABLED.store(true, Ordering::SeqCst);
+    on_timer();
+}
+
+/// Redraws the status bar if the uptime's seconds, the active terminal or the LEDs have changed.
+/// This is called from the timer interrupt, so terminals in use are skipped rather than waited
+/// for, and drawn on a later tick.
+pub fn on_timer() {
+    if !ENABLED.load(Ordering::SeqCst) {
+        return;
+    }
+
+    let uptime_seconds = time::uptime_micros() / 1_000_000;
+    let (terminal, leds) = (vga::active(), keyboard::leds());
+    let key = state_key(uptime_seconds, terminal, leds);
+    if SHOWN.load(Ordering::SeqCst) == key {
+        return;
+    }
+
+    let time = match *CLOCK.lock() {
+        Some((time, read_at)) => time.add_seconds(uptime_seconds.saturating_sub(read_at)),
+        None => return,
+    };
+
+    let status = Status {
+        uptime_seconds: uptime_seconds,
+        time: time,
+        free_memory: memory::free(),
+        terminal: terminal,
+        leds: leds,
+    };
+
+    if show(&status) {
+        SHOWN.store(key, Ordering::SeqCst);
+    }
+}
+
+/// Packs what the status bar changes with into a key, which is never 0
+fn state_key(uptime_seconds: u64, terminal: usize, leds: Leds) -> usize {
+    let leds = leds.caps_lock as usize | (leds.num_lock as usize) << 1 | (leds.scroll_lock as usize) << 2;
+    ((uptime_seconds as usize) << 8 | terminal << 3 | leds) + 1
+}
+
+/// Writes the status to the bottom row of every terminal, and flushes the active one. Returns
+/// whether every terminal could be locked.
+fn show(status: &Status) -> bool {
+    let mut line = StatusLine::new();
+    let _ = status.draw(&mut line);
+
+    let mut shown = true;
+    for terminal in vga::TERMINALS.iter() {
+        let mut terminal = match terminal.try_lock() {
+            Some(terminal) => terminal,
+            None => {
+                shown = false;
+                continue;
+            }
+        };
+
+        let buffer = terminal.buffer_mut().buffer_mut();
+        let (width, height) = buffer.screen_size();
+        for (column, &value) in line.cells.iter().take(width).enumerate() {
+            buffer.set_fixed_char(height - 1, column, value);
+        }
+        buffer.flush();
+    }
+
+    shown
+}
+
+/// What the status bar shows
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Status {
+    pub uptime_seconds: u64,
+    pub time: DateTime,
+    /// Free memory in bytes
+    pub free_memory: usize,
+    /// Index of the active terminal
+    pub terminal: usize,
+    pub leds: Leds,
+}
+
+impl Status {
+    /// Draws the status as ` VT1 │ up 1d 02:03:04 │ 2017-10-18 21:05:59 │ 130048 KiB free │ NUM CAPS SCRL`
+    fn draw(&self, line: &mut StatusLine) -> fmt::Result {
+        let seconds = self.uptime_seconds;
+
+        write!(line, " VT{}{}up ", self.terminal + 1, SEPARATOR)?;
+        if seconds >= SECONDS_PER_DAY {
+            write!(line, "{}d ", seconds / SECONDS_PER_DAY)?;
+        }
+        write!(line, "{:02}:{:02}:{:02}", seconds % SECONDS_PER_DAY / 3600, seconds % 3600 / 60, seconds % 60)?;
+        write!(line, "{}{}{}{} KiB free{}", SEPARATOR, self.time, SEPARATOR, self.free_memory / 1024, SEPARATOR)?;
+
+        let leds = [("NUM", self.leds.num_lock), ("CAPS", self.leds.caps_lock), ("SCRL", self.leds.scroll_lock)];
+        for (index, &(name, lit)) in leds.iter().enumerate() {
+            if index > 0 {
+                line.write_str(" ")?;
+            }
+
+            line.color = if lit { LIT_COLOR } else { UNLIT_COLOR };
+            line.write_str(name)?;
+            line.color = COLOR;
+        }
+
+        Ok(())
+    }
+}
+
+/// A row of status bar cells, written left to right. Whatever doesn't fit is cut off.
+struct StatusLine {
+    cells: [VgaChar; MAX_COLUMNS],
+    column: usize,
+    color: VgaColor,
+}
+
+impl StatusLine {
+    fn new() -> Self {
+        StatusLine {
+            cells: [VgaChar::new(COLOR, ' '); MAX_COLUMNS],
+            column: 0,
+            color: COLOR,
+        }
+    }
+}
+
+impl fmt::Write for StatusLine {
+    fn write_str(&mut self, str: &str) -> fmt::Result {
+        for character in str.chars() {
+            if self.column < MAX_COLUMNS {
+                self.cells[self.column] = VgaChar::new(self.color, character);
+                self.column += 1;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "kernel-test")]
+pub mod tests {
+    use testing::{TestCase, TestResult};
+    use super::*;
+
+    pub const TESTS: &'static [TestCase] = &[
+        test_case!(draws_status),
+        test_case!(draws_days_of_uptime),
+        test_case!(colors_lit_leds),
+        test_case!(cuts_off_long_lines),
+    ];
+
+    fn status(uptime_seconds: u64) -> Status {
+        Status {
+            uptime_seconds: uptime_seconds,
+            time: DateTime { year: 2017, month: 10, day: 18, hour: 21, minute: 5, second: 59 },
+            free_memory: 2 * 1024 * 1024,
+            terminal: 1,
+            leds: Leds { caps_lock: false, num_lock: true, scroll_lock: false },
+        }
+    }
+
+    /// Checks that a line starts with the given text
+    fn starts_with(line: &StatusLine, text: &str) -> bool {
+        text.chars().enumerate().all(|(column, character)| {
+            Some(line.cells[column].character) == vga::encode_cp437(character)
+        })
+    }
+
+    fn draws_status() -> TestResult {
+        let expected = " VT2 \u{2502} up 01:02:03 \u{2502} 2017-10-18 21:05:59 \u{2502} 2048 KiB free \u{2502} NUM CAPS SCRL";
+
+        let mut line = StatusLine::new();
+        test_assert!(status(3723).draw(&mut line).is_ok());
+        test_assert!(starts_with(&line, expected));
+        test_assert_eq!(line.column, expected.chars().count());
+        test_assert_eq!(line.cells[line.column], VgaChar::new(COLOR, ' '));
+        Ok(())
+    }
+
+    fn draws_days_of_uptime() -> TestResult {
+        let mut line = StatusLine::new();
+        test_assert!(status(2 * SECONDS_PER_DAY + 5).draw(&mut line).is_ok());
+        test_assert!(starts_with(&line, " VT2 \u{2502} up 2d 00:00:05 \u{2502} "));
+        Ok(())
+    }
+
+    fn colors_lit_leds() -> TestResult {
+        let mut line = StatusLine::new();
+        test_assert!(status(0).draw(&mut line).is_ok());
+
+        // The line ends with "NUM CAPS SCRL"
+        let end = line.column;
+        test_assert_eq!(line.cells[end - 13].color, LIT_COLOR);
+        test_assert_eq!(line.cells[end - 10].color, COLOR);
+        test_assert_eq!(line.cells[end - 9].color, UNLIT_COLOR);
+        test_assert_eq!(line.cells[end - 1].color, UNLIT_COLOR);
+        Ok(())
+    }
+
+    fn cuts_off_long_lines() -> TestResult {
+        let mut line = StatusLine::new();
+        for _ in 0..MAX_COLUMNS + 10 {
+            test_assert!(line.write_str("x").is_ok());
+        }
+
+        test_assert_eq!(line.column, MAX_COLUMNS);
+        test_assert_eq!(line.cells[MAX_COLUMNS - 1], VgaChar::new(COLOR, 'x'));
+        Ok(())
+    }
+}
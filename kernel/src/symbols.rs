@@ -0,0 +1,276 @@
+//! Kernel symbol lookup, from the ELF symbol table loaded by the bootloader
+
+use core::{fmt, mem, slice, str};
+use spin::Once;
+use multiboot::BootInformation;
+
+const TAG_ELF_SECTIONS: u32 = 9;
+
+const SECTION_TYPE_SYMBOL_TABLE: u32 = 2;
+/// Section flag for sections which take up memory when the kernel is loaded
+const SECTION_FLAG_ALLOC: u64 = 1 << 1;
+const SYMBOL_TYPE_FUNCTION: u8 = 2;
+
+static SYMBOL_TABLE: Once<SymbolTable> = Once::new();
+
+/// Finds the kernel's symbol table from the boot information's ELF sections tag
+pub fn init(boot_info: &BootInformation) {
+    if let Some(table) = SymbolTable::load(boot_info) {
+        SYMBOL_TABLE.call_once(|| table);
+        debug!("syms", "loaded {} symbols", table.symbols.len());
+    } else {
+        warn!("syms", "no symbol table, backtraces will not be symbolized");
+    }
+}
+
+/// Gets the address just past the last section of the kernel loaded into memory
+pub fn kernel_end(boot_info: &BootInformation) -> Option<usize> {
+    let data = boot_info.tag(TAG_ELF_SECTIONS)?.data();
+    let count = read_u32(data, 0) as usize;
+    let entry_size = read_u32(data, 4) as usize;
+
+    (0..count)
+        .take_while(|index| 12 + (index + 1) * entry_size <= data.len())
+        .map(|index| unsafe { &*(data[12 + index * entry_size..].as_ptr() as *const SectionHeader) })
+        .filter(|header| header.flags & SECTION_FLAG_ALLOC != 0)
+        .map(|header| (header.address + header.size) as usize)
+        .max()
+}
+
+/// Finds the function containing the given address
+pub fn resolve(address: usize) -> Option<Symbol> {
+    SYMBOL_TABLE.try().and_then(|table| table.resolve(address))
+}
+
+/// An ELF64 section header
+#[allow(dead_code)] // Fields mirror the ELF layout
+#[repr(C)]
+struct SectionHeader {
+    name: u32,
+    typ: u32,
+    flags: u64,
+    address: u64,
+    offset: u64,
+    size: u64,
+    link: u32,
+    info: u32,
+    alignment: u64,
+    entry_size: u64,
+}
+
+/// An ELF64 symbol table entry
+#[allow(dead_code)] // Fields mirror the ELF layout
+#[repr(C)]
+struct ElfSymbol {
+    name: u32,
+    info: u8,
+    other: u8,
+    section: u16,
+    value: u64,
+    size: u64,
+}
+
+#[derive(Copy, Clone)]
+struct SymbolTable {
+    symbols: &'static [ElfSymbol],
+    strings: &'static [u8],
+}
+
+impl SymbolTable {
+    fn load(boot_info: &BootInformation) -> Option<Self> {
+        let data = boot_info.tag(TAG_ELF_SECTIONS)?.data();
+
+        // The section headers follow the count, entry size and string table index
+        let count = read_u32(data, 0) as usize;
+        let entry_size = read_u32(data, 4) as usize;
+        let header = |index: usize| -> Option<&'static SectionHeader> {
+            if index < count && 12 + (index + 1) * entry_size <= data.len() {
+                Some(unsafe { &*(data[12 + index * entry_size..].as_ptr() as *const SectionHeader) })
+            } else {
+                None
+            }
+        };
+
+        let symbol_table = (0..count)
+            .filter_map(|index| header(index))
+            .find(|header| header.typ == SECTION_TYPE_SYMBOL_TABLE)?;
+        let string_table = header(symbol_table.link as usize)?;
+
+        // Not loaded into memory by the bootloader
+        if symbol_table.address == 0 || string_table.address == 0 {
+            return None;
+        }
+
+        unsafe {
+            Some(SymbolTable {
+                symbols: slice::from_raw_parts(
+                    symbol_table.address as *const ElfSymbol,
+                    symbol_table.size as usize / mem::size_of::<ElfSymbol>(),
+                ),
+                strings: slice::from_raw_parts(
+                    string_table.address as *const u8,
+                    string_table.size as usize,
+                ),
+            })
+        }
+    }
+
+    fn resolve(&self, address: usize) -> Option<Symbol> {
+        let address = address as u64;
+        let symbol = self.symbols.iter().find(|symbol| {
+            symbol.info & 0xF == SYMBOL_TYPE_FUNCTION &&
+                symbol.value <= address && address < symbol.value + symbol.size
+        })?;
+
+        Some(Symbol {
+            name: self.string(symbol.name as usize)?,
+            address: symbol.value as usize,
+        })
+    }
+
+    /// Gets the null terminated string at the given offset in the string table
+    fn string(&self, offset: usize) -> Option<&'static str> {
+        let bytes = self.strings.get(offset..)?;
+        let length = bytes.iter().position(|&b| b == 0)?;
+        str::from_utf8(&bytes[..length]).ok()
+    }
+}
+
+fn read_u32(data: &[u8], offset: usize) -> u32 {
+    (data[offset] as u32) | (data[offset + 1] as u32) << 8 |
+        (data[offset + 2] as u32) << 16 | (data[offset + 3] as u32) << 24
+}
+
+/// A resolved function symbol
+pub struct Symbol {
+    pub name: &'static str,
+    /// Start address of the function
+    pub address: usize,
+}
+
+impl Symbol {
+    pub fn demangled(&self) -> Demangle {
+        Demangle(self.name)
+    }
+}
+
+/// Displays a symbol name, demangling it if it is a Rust (legacy) mangled name
+pub struct Demangle<'a>(pub &'a str);
+
+impl<'a> Demangle<'a> {
+    /// Gets the path segments of the mangled name, or `None` if it isn't mangled
+    fn segments(&self) -> Option<&'a str> {
+        let name = self.0;
+        if name.starts_with("_ZN") && name.ends_with('E') {
+            let segments = &name[3..name.len() - 1];
+
+            // Check every segment is well formed before writing any
+            let mut rest = segments;
+            while !rest.is_empty() {
+                rest = next_segment(rest)?.1;
+            }
+
+            Some(segments)
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a> fmt::Display for Demangle<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut rest = match self.segments() {
+            Some(segments) => segments,
+            None => return f.write_str(self.0),
+        };
+
+        let mut first = true;
+        while let Some((segment, next)) = next_segment(rest) {
+            rest = next;
+
+            // The last segment is a hash of the symbol
+            if rest.is_empty() && is_hash(segment) {
+                break;
+            }
+
+            if !first {
+                f.write_str("::")?;
+            }
+            first = false;
+
+            write_unescaped(f, segment)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Splits a length prefixed segment off the front of a mangled path
+fn next_segment(path: &str) -> Option<(&str, &str)> {
+    let digits = path.bytes().take_while(|&b| b >= b'0' && b <= b'9').count();
+    let length: usize = path[..digits].parse().ok()?;
+    let rest = &path[digits..];
+
+    if length <= rest.len() && rest.is_char_boundary(length) {
+        Some((&rest[..length], &rest[length..]))
+    } else {
+        None
+    }
+}
+
+fn is_hash(segment: &str) -> bool {
+    segment.len() == 17 && segment.starts_with('h') && segment[1..].chars().all(|c| c.is_digit(16))
+}
+
+/// Writes a path segment, replacing the escapes used for characters that symbols can't contain
+fn write_unescaped(f: &mut fmt::Formatter, segment: &str) -> fmt::Result {
+    // Segments which would start with an escape are prefixed with an underscore
+    let mut rest = if segment.starts_with("_$") { &segment[1..] } else { segment };
+
+    while !rest.is_empty() {
+        if rest.starts_with("..") {
+            f.write_str("::")?;
+            rest = &rest[2..];
+        } else if rest.starts_with('$') {
+            let end = match rest[1..].find('$') {
+                Some(end) => end + 2,
+                None => return f.write_str(rest),
+            };
+
+            f.write_str(match &rest[..end] {
+                "$SP$" => "@",
+                "$BP$" => "*",
+                "$RF$" => "&",
+                "$LT$" => "<",
+                "$GT$" => ">",
+                "$LP$" => "(",
+                "$RP$" => ")",
+                "$C$" => ",",
+                "$u20$" => " ",
+                "$u22$" => "\"",
+                "$u27$" => "'",
+                "$u2b$" => "+",
+                "$u3b$" => ";",
+                "$u5b$" => "[",
+                "$u5d$" => "]",
+                "$u7b$" => "{",
+                "$u7d$" => "}",
+                "$u7e$" => "~",
+                escape => escape,
+            })?;
+            rest = &rest[end..];
+        } else {
+            // Write up to the next escape, or a single lone '.'
+            let end = match rest.find(|c: char| c == '$' || c == '.') {
+                Some(0) => 1,
+                Some(end) => end,
+                None => rest.len(),
+            };
+
+            f.write_str(&rest[..end])?;
+            rest = &rest[end..];
+        }
+    }
+
+    Ok(())
+}
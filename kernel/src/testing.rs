@@ -0,0 +1,177 @@
+//! In-kernel test runner, which reports over serial and exits QEMU with the result through the
+//! `isa-debug-exit` device.
+
+use core::fmt::{self, Write};
+use core::str;
+use io::IOPort;
+use drivers::serial;
+use cpu;
+
+/// Maximum length in bytes of the values reported by a failed assertion. Longer values are cut off.
+const VALUES_LENGTH: usize = 256;
+
+/// The `isa-debug-exit` device, set up by `make test`
+static EXIT_PORT: IOPort = IOPort::new(0xF4);
+
+/// Every test suite, run in order
+const SUITES: &'static [&'static [TestCase]] = &[
+    ::paging::tests::TESTS,
+    ::drivers::vga::hardware_tests::TESTS,
+    ::drivers::framebuffer::tests::TESTS,
+    ::drivers::bga::tests::TESTS,
+    ::tui::tests::TESTS,
+    ::drivers::ps2::tests::TESTS,
+    ::drivers::keyboard::tests::TESTS,
+    ::drivers::mouse::tests::TESTS,
+    ::drivers::rtc::tests::TESTS,
+    ::status::tests::TESTS,
+    ::clipboard::tests::TESTS,
+    ::input::tests::TESTS,
+    ::pointer::tests::TESTS,
+];
+
+/// Creates a `TestCase` for the given function, named after it and its module
+macro_rules! test_case {
+    ($test:ident) => ($crate::testing::TestCase {
+        name: concat!(module_path!(), "::", stringify!($test)),
+        run: $test,
+    });
+}
+
+/// Fails the test if the condition is false
+macro_rules! test_assert {
+    ($cond:expr) => (
+        if !$cond {
+            return Err($crate::testing::TestFailure {
+                message: concat!("assertion failed: ", stringify!($cond)),
+                file: file!(),
+                line: line!(),
+                values: $crate::testing::FailureValues::new(),
+            });
+        }
+    );
+}
+
+/// Fails the test if the values aren't equal, reporting both
+macro_rules! test_assert_eq {
+    ($left:expr, $right:expr) => ({
+        let (left, right) = (&$left, &$right);
+        if *left != *right {
+            let mut values = $crate::testing::FailureValues::new();
+            let _ = ::core::fmt::Write::write_fmt(
+                &mut values,
+                format_args!("    left: {:?}\n   right: {:?}\n", left, right)
+            );
+
+            return Err($crate::testing::TestFailure {
+                message: concat!("assertion failed: ", stringify!($left), " == ", stringify!($right)),
+                file: file!(),
+                line: line!(),
+                values: values,
+            });
+        }
+    });
+}
+
+/// Exit codes for QEMU, which exits with `(code << 1) | 1`
+#[derive(Copy, Clone)]
+#[repr(u8)]
+pub enum ExitCode {
+    Success = 0x10,
+    Failure = 0x11,
+}
+
+pub type TestResult = Result<(), TestFailure>;
+
+/// Represents a single registered test
+pub struct TestCase {
+    pub name: &'static str,
+    pub run: fn() -> TestResult,
+}
+
+/// Represents a failed assertion
+pub struct TestFailure {
+    pub message: &'static str,
+    pub file: &'static str,
+    pub line: u32,
+    /// Values the assertion compared, reported after the test's result
+    pub values: FailureValues,
+}
+
+/// Text describing the values of a failed assertion, which is cut off on a char boundary once full
+pub struct FailureValues {
+    bytes: [u8; VALUES_LENGTH],
+    length: usize,
+}
+
+impl FailureValues {
+    pub const fn new() -> Self {
+        FailureValues {
+            bytes: [0; VALUES_LENGTH],
+            length: 0,
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        // Only whole chars are ever written
+        unsafe { str::from_utf8_unchecked(&self.bytes[..self.length]) }
+    }
+}
+
+impl fmt::Write for FailureValues {
+    fn write_str(&mut self, str: &str) -> fmt::Result {
+        for char in str.chars() {
+            let char_length = char.len_utf8();
+            if self.length + char_length > VALUES_LENGTH {
+                break;
+            }
+
+            char.encode_utf8(&mut self.bytes[self.length..]);
+            self.length += char_length;
+        }
+
+        Ok(())
+    }
+}
+
+/// Runs every test suite and exits QEMU with the result
+pub fn run() -> ! {
+    let total = SUITES.iter().map(|suite| suite.len()).sum::<usize>();
+    report(format_args!("\nrunning {} tests\n", total));
+
+    let mut failed = 0;
+    for test in SUITES.iter().flat_map(|suite| suite.iter()) {
+        report(format_args!("test {} ... ", test.name.trim_left_matches("flower_kernel::")));
+
+        match (test.run)() {
+            Ok(()) => report(format_args!("ok\n")),
+            Err(failure) => {
+                report(format_args!(
+                    "FAILED\n    {} at {}:{}\n{}",
+                    failure.message, failure.file, failure.line, failure.values.text()
+                ));
+                failed += 1;
+            }
+        }
+    }
+
+    report(format_args!(
+        "\ntest result: {}. {} passed; {} failed\n",
+        if failed == 0 { "ok" } else { "FAILED" },
+        total - failed,
+        failed
+    ));
+
+    exit(if failed == 0 { ExitCode::Success } else { ExitCode::Failure })
+}
+
+/// Writes test output straight to the serial port
+pub fn report(args: fmt::Arguments) {
+    let _ = serial::COM1.lock().write_fmt(args);
+}
+
+/// Exits QEMU with the given code, or halts if not running under QEMU
+pub fn exit(code: ExitCode) -> ! {
+    EXIT_PORT.write(code as u8);
+    cpu::halt_forever()
+}
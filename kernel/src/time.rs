@@ -0,0 +1,48 @@
+//! Time keeping with the CPU's timestamp counter
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+use drivers::pit;
+
+/// Length of the PIT wait used to calibrate the timestamp counter
+const CALIBRATION_MICROS: u32 = 10_000;
+
+static BOOT_TSC: AtomicUsize = AtomicUsize::new(0);
+static TSC_PER_MICRO: AtomicUsize = AtomicUsize::new(0);
+
+/// Calibrates the timestamp counter against the PIT, and starts counting uptime
+pub fn init() {
+    let start = rdtsc();
+    pit::wait_micros(CALIBRATION_MICROS);
+    let end = rdtsc();
+
+    BOOT_TSC.store(start as usize, Ordering::Relaxed);
+    TSC_PER_MICRO.store(((end - start) / CALIBRATION_MICROS as u64) as usize, Ordering::Relaxed);
+}
+
+/// Gets the microseconds elapsed since `init`, or 0 if not yet calibrated
+pub fn uptime_micros() -> u64 {
+    let per_micro = TSC_PER_MICRO.load(Ordering::Relaxed) as u64;
+    if per_micro == 0 {
+        return 0;
+    }
+
+    (rdtsc() - BOOT_TSC.load(Ordering::Relaxed) as u64) / per_micro
+}
+
+/// Busy waits for the given amount of microseconds. Returns at once if not yet calibrated.
+pub fn wait_micros(micros: u64) {
+    if TSC_PER_MICRO.load(Ordering::Relaxed) == 0 {
+        return;
+    }
+
+    let end = uptime_micros() + micros;
+    while uptime_micros() < end {}
+}
+
+/// Reads the timestamp counter
+pub fn rdtsc() -> u64 {
+    let low: u32;
+    let high: u32;
+    unsafe { asm!("rdtsc" : "={eax}"(low), "={edx}"(high) ::: "volatile") }
+    (high as u64) << 32 | low as u64
+}
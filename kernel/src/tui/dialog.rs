@@ -0,0 +1,140 @@
+//! Message boxes, and showing widgets in a window until something is chosen
+
+use core::cmp;
+use drivers::keyboard::{Key, KeyEvent, KEYBOARD};
+use drivers::vga::{self, Color, VgaBuffer, VgaColor};
+use super::{Region, Widget, Response, Window};
+
+/// Columns around a button's label, for its brackets and padding
+const BUTTON_PADDING: usize = 4;
+/// Columns between buttons
+const BUTTON_GAP: usize = 2;
+
+/// A message over a row of buttons, selected with the arrow keys and chosen with Enter
+pub struct MessageBox<'a> {
+    message: &'a str,
+    buttons: &'a [&'a str],
+    selected: usize,
+    color: VgaColor,
+    selected_color: VgaColor,
+}
+
+impl<'a> MessageBox<'a> {
+    /// Creates a message box with the first button selected, in white on blue
+    pub const fn new(message: &'a str, buttons: &'a [&'a str]) -> Self {
+        MessageBox {
+            message: message,
+            buttons: buttons,
+            selected: 0,
+            color: VgaColor::new(Color::White, Color::Blue),
+            selected_color: VgaColor::new(Color::Black, Color::LightGray),
+        }
+    }
+
+    /// Gets the columns taken by the row of buttons
+    fn buttons_width(&self) -> usize {
+        let labels: usize = self.buttons.iter().map(|label| label.chars().count() + BUTTON_PADDING).sum();
+        labels + self.buttons.len().saturating_sub(1) * BUTTON_GAP
+    }
+
+    /// Gets the size of region needed to show the whole message and every button, as
+    /// `(width, height)`
+    pub fn size(&self) -> (usize, usize) {
+        let message_width = self.message.lines().map(|line| line.chars().count()).max().unwrap_or(0);
+        let lines = self.message.lines().count();
+
+        // A column of padding each side, and a blank row above the buttons
+        (cmp::max(message_width, self.buttons_width()) + 2, lines + 2)
+    }
+}
+
+impl<'a> Widget for MessageBox<'a> {
+    /// Draws the message from the top left, and the buttons centered on the bottom row
+    fn draw(&self, region: &mut Region, focused: bool) {
+        region.fill(' ', self.color);
+
+        for (row, line) in self.message.lines().enumerate() {
+            region.text(row, 1, line, self.color);
+        }
+
+        let row = region.height().saturating_sub(1);
+        let mut column = region.width().saturating_sub(self.buttons_width()) / 2;
+
+        for (index, label) in self.buttons.iter().enumerate() {
+            let color = if index == self.selected && focused { self.selected_color } else { self.color };
+            region.text(row, column, "[ ", color);
+            let written = region.text(row, column + 2, label, color);
+            region.text(row, column + 2 + written, " ]", color);
+            column += label.chars().count() + BUTTON_PADDING + BUTTON_GAP;
+        }
+    }
+
+    fn handle_key(&mut self, event: &KeyEvent) -> Response {
+        if self.buttons.is_empty() {
+            return match event.key {
+                Key::Enter | Key::KeypadEnter | Key::Escape => Response::Cancelled,
+                _ => Response::Ignored,
+            };
+        }
+
+        let last = self.buttons.len() - 1;
+        let selected = match event.key {
+            Key::Left => if self.selected == 0 { last } else { self.selected - 1 },
+            Key::Right => if self.selected == last { 0 } else { self.selected + 1 },
+            Key::Enter | Key::KeypadEnter => return Response::Chosen(self.selected),
+            Key::Escape => return Response::Cancelled,
+            _ => return Response::Ignored,
+        };
+
+        self.selected = selected;
+        Response::Changed
+    }
+}
+
+/// Shows a widget in a window centered on screen, giving it the keyboard's key presses until
+/// it chooses something or is cancelled. The size is of the inside of the window. The active
+/// terminal's output is kept off screen meanwhile, and it's redrawn afterwards. It's cancelled
+/// straight away if the screen isn't in text mode, such as while graphics are shown.
+pub fn run_modal(window: &Window, widget: &mut Widget, width: usize, height: usize) -> Response {
+    if !vga::hold_screen() {
+        return Response::Cancelled;
+    }
+
+    let mut screen = VgaBuffer::shown();
+    let mut changed = true;
+
+    loop {
+        if changed {
+            let mut region = Region::new(&mut screen, window.color);
+            let mut inside = window.draw_centered(&mut region, width + 2, height + 2);
+            widget.draw(&mut inside, true);
+        }
+
+        let event = KEYBOARD.lock().poll();
+        let response = match event {
+            Some(event) if event.pressed => widget.handle_key(&event),
+            _ => Response::Ignored,
+        };
+
+        match response {
+            Response::Chosen(_) | Response::Cancelled => {
+                vga::release_screen();
+                return response;
+            }
+            _ => changed = response == Response::Changed,
+        }
+    }
+}
+
+/// Shows a message with the given buttons in a window, giving the index of the button chosen or
+/// `None` if it was cancelled
+pub fn message_box(title: &str, message: &str, buttons: &[&str]) -> Option<usize> {
+    let mut message_box = MessageBox::new(message, buttons);
+    let (width, height) = message_box.size();
+    let width = cmp::max(width, title.chars().count() + 2);
+
+    match run_modal(&Window::new(title), &mut message_box, width, height) {
+        Response::Chosen(index) => Some(index),
+        _ => None,
+    }
+}
@@ -0,0 +1,94 @@
+//! Lists of items to choose from
+
+use drivers::keyboard::{Key, KeyEvent};
+use drivers::vga::{Color, VgaColor};
+use super::{Region, Widget, Response};
+
+/// A list of items, one of which is selected with the arrow keys and chosen with Enter. Lists
+/// longer than the region scroll to keep the selected item shown.
+pub struct Menu<'a> {
+    items: &'a [&'a str],
+    selected: usize,
+    color: VgaColor,
+    selected_color: VgaColor,
+}
+
+impl<'a> Menu<'a> {
+    /// Creates a menu with the first item selected, in white on blue
+    pub const fn new(items: &'a [&'a str]) -> Self {
+        Menu {
+            items: items,
+            selected: 0,
+            color: VgaColor::new(Color::White, Color::Blue),
+            selected_color: VgaColor::new(Color::Black, Color::LightGray),
+        }
+    }
+
+    pub fn items(&self) -> &'a [&'a str] {
+        self.items
+    }
+
+    /// Gets the index of the selected item
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    /// Selects the item with the given index. Indices out of range are ignored.
+    pub fn select(&mut self, index: usize) {
+        if index < self.items.len() {
+            self.selected = index;
+        }
+    }
+
+    /// Gets the index of the first item shown in a region of the given height
+    fn first_shown(&self, height: usize) -> usize {
+        (self.selected + 1).saturating_sub(height)
+    }
+}
+
+impl<'a> Widget for Menu<'a> {
+    /// Draws an item on each row, with arrows on the right edge if there are items above or
+    /// below the ones shown. The selected item is dimmed without focus.
+    fn draw(&self, region: &mut Region, focused: bool) {
+        region.fill(' ', self.color);
+
+        let height = region.height();
+        let first = self.first_shown(height);
+        let selected_color = if focused { self.selected_color } else { self.selected_color.dimmed() };
+
+        for (row, (index, item)) in self.items.iter().enumerate().skip(first).take(height).enumerate() {
+            let color = if index == self.selected { selected_color } else { self.color };
+            let width = region.width();
+            region.horizontal_line(row, 0, width, ' ', color);
+            region.text(row, 1, item, color);
+        }
+
+        let right = region.width().saturating_sub(1);
+        if first > 0 {
+            region.put_char(0, right, '↑', self.color);
+        }
+        if first + height < self.items.len() {
+            region.put_char(height - 1, right, '↓', self.color);
+        }
+    }
+
+    fn handle_key(&mut self, event: &KeyEvent) -> Response {
+        if self.items.is_empty() {
+            return Response::Ignored;
+        }
+
+        let last = self.items.len() - 1;
+        let selected = match event.key {
+            Key::Up => if self.selected == 0 { last } else { self.selected - 1 },
+            Key::Down => if self.selected == last { 0 } else { self.selected + 1 },
+            Key::Home | Key::PageUp => 0,
+            Key::End | Key::PageDown => last,
+            Key::Enter | Key::KeypadEnter => return Response::Chosen(self.selected),
+            Key::Escape => return Response::Cancelled,
+            _ => return Response::Ignored,
+        };
+
+        self.selected = selected;
+        Response::Changed
+    }
+}
@@ -0,0 +1,19 @@
+//! A text mode UI toolkit: clipped regions of a text buffer, bordered windows, and widgets such
+//! as menus, progress bars and message boxes which take keys while they have focus
+
+mod dialog;
+mod menu;
+mod progress;
+mod region;
+mod widget;
+mod window;
+
+#[cfg(feature = "kernel-test")]
+pub mod tests;
+
+pub use self::dialog::{MessageBox, run_modal, message_box};
+pub use self::menu::Menu;
+pub use self::progress::ProgressBar;
+pub use self::region::Region;
+pub use self::widget::{Widget, Response, Focus};
+pub use self::window::{Window, BorderStyle, BorderChars};
@@ -0,0 +1,83 @@
+//! Bars showing how far along something is
+
+use core::cmp;
+use core::fmt::Write;
+use drivers::vga::{Color, VgaColor};
+use super::{Region, Widget};
+
+/// A bar filled from the left in proportion to a value out of a total, with the percentage in
+/// its middle
+pub struct ProgressBar {
+    value: usize,
+    total: usize,
+    color: VgaColor,
+}
+
+impl ProgressBar {
+    /// Creates an empty bar, filled in light green
+    pub const fn new(total: usize) -> Self {
+        ProgressBar {
+            value: 0,
+            total: total,
+            color: VgaColor::new(Color::LightGreen, Color::DarkGray),
+        }
+    }
+
+    pub fn value(&self) -> usize {
+        self.value
+    }
+
+    pub fn total(&self) -> usize {
+        self.total
+    }
+
+    /// Sets the value, up to the total
+    pub fn set(&mut self, value: usize) {
+        self.value = cmp::min(value, self.total);
+    }
+
+    /// Gets how far along the bar is, from 0 to 100. A bar out of nothing is full.
+    pub fn percent(&self) -> usize {
+        if self.total == 0 { 100 } else { self.value * 100 / self.total }
+    }
+
+    /// Gets how many of the given amount of columns are filled
+    fn filled(&self, width: usize) -> usize {
+        if self.total == 0 { width } else { width * self.value / self.total }
+    }
+}
+
+impl Widget for ProgressBar {
+    /// Draws the bar across the top row of the region
+    fn draw(&self, region: &mut Region, _focused: bool) {
+        let width = region.width();
+        let filled = self.filled(width);
+
+        // Filled cells are drawn as spaces in the inverted color, so the percentage stays
+        // readable on them
+        region.horizontal_line(0, 0, filled, ' ', self.color.inverted());
+        region.horizontal_line(0, filled, width - filled, '░', self.color);
+
+        let percent = self.percent();
+        let length = match percent {
+            0...9 => 2,
+            10...99 => 3,
+            _ => 4,
+        };
+
+        let column = width.saturating_sub(length) / 2;
+        {
+            let mut text = region.region(0, column, length, 1);
+            text.set_color(self.color);
+            let _ = write!(text, "{}%", percent);
+        }
+
+        for column in column..cmp::min(column + length, filled) {
+            region.recolor(0, column, self.color.inverted());
+        }
+    }
+
+    fn is_focusable(&self) -> bool {
+        false
+    }
+}
@@ -0,0 +1,200 @@
+//! Rectangles of cells in a text buffer, which clip what's drawn in them and keep their own
+//! cursor and color
+
+use core::{cmp, fmt};
+use drivers::vga::{TextBuffer, VgaChar, VgaColor};
+use super::BorderStyle;
+
+/// A rectangle of cells in a text buffer. Rows and columns are relative to its top left cell,
+/// and anything drawn outside it is cut off, so widgets don't need to know where they are.
+pub struct Region<'a> {
+    buffer: &'a mut TextBuffer,
+    top: usize,
+    left: usize,
+    width: usize,
+    height: usize,
+    /// Cell which `write_str` writes to next, as `(row, column)`
+    cursor: (usize, usize),
+    color: VgaColor,
+}
+
+impl<'a> Region<'a> {
+    /// Creates a region covering the whole buffer, drawing text in the given color
+    pub fn new(buffer: &'a mut TextBuffer, color: VgaColor) -> Self {
+        let (width, height) = (buffer.width(), buffer.height());
+        Region {
+            buffer: buffer,
+            top: 0,
+            left: 0,
+            width: width,
+            height: height,
+            cursor: (0, 0),
+            color: color,
+        }
+    }
+
+    /// Gets a region inside this one from the given cell, cut off where it goes past this one.
+    /// It starts with this region's color and its own cursor.
+    pub fn region<'b>(&'b mut self, row: usize, column: usize, width: usize, height: usize) -> Region<'b> {
+        let (row, column) = (cmp::min(row, self.height), cmp::min(column, self.width));
+        Region {
+            top: self.top + row,
+            left: self.left + column,
+            width: cmp::min(width, self.width - column),
+            height: cmp::min(height, self.height - row),
+            cursor: (0, 0),
+            color: self.color,
+            buffer: &mut *self.buffer,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Gets the color text is written in
+    pub fn color(&self) -> VgaColor {
+        self.color
+    }
+
+    pub fn set_color(&mut self, color: VgaColor) {
+        self.color = color;
+    }
+
+    /// Sets the cell `write_str` writes to next, as `(row, column)`
+    pub fn set_cursor(&mut self, row: usize, column: usize) {
+        self.cursor = (row, column);
+    }
+
+    /// Gets a cell, or `None` outside the region
+    pub fn get(&self, row: usize, column: usize) -> Option<VgaChar> {
+        if row < self.height && column < self.width {
+            Some(self.buffer.get_char(self.top + row, self.left + column))
+        } else {
+            None
+        }
+    }
+
+    /// Sets a cell. Cells outside the region are ignored.
+    pub fn put(&mut self, row: usize, column: usize, value: VgaChar) {
+        if row < self.height && column < self.width {
+            self.buffer.set_char(self.top + row, self.left + column, value);
+        }
+    }
+
+    /// Sets a cell to a char, drawn with its CP437 glyph
+    pub fn put_char(&mut self, row: usize, column: usize, character: char, color: VgaColor) {
+        self.put(row, column, VgaChar::new(color, character));
+    }
+
+    /// Changes a cell's color while keeping its char, such as for highlights and shadows
+    pub fn recolor(&mut self, row: usize, column: usize, color: VgaColor) {
+        if let Some(cell) = self.get(row, column) {
+            self.put(row, column, VgaChar { color: color, ..cell });
+        }
+    }
+
+    /// Sets every cell to the given char
+    pub fn fill(&mut self, character: char, color: VgaColor) {
+        let value = VgaChar::new(color, character);
+        for row in 0..self.height {
+            for column in 0..self.width {
+                self.put(row, column, value);
+            }
+        }
+    }
+
+    /// Blanks every cell in the region's color and moves the cursor to the top left
+    pub fn clear(&mut self) {
+        let color = self.color;
+        self.fill(' ', color);
+        self.cursor = (0, 0);
+    }
+
+    /// Writes text on a row from the given column, without wrapping. Gives the amount of columns
+    /// written, which is less than the text's length if it's cut off.
+    pub fn text(&mut self, row: usize, column: usize, text: &str, color: VgaColor) -> usize {
+        let mut written = 0;
+        for (offset, character) in text.chars().enumerate() {
+            if row >= self.height || column + offset >= self.width {
+                break;
+            }
+
+            self.put_char(row, column + offset, character, color);
+            written += 1;
+        }
+        written
+    }
+
+    /// Writes text centered on a row
+    pub fn text_centered(&mut self, row: usize, text: &str, color: VgaColor) {
+        let length = text.chars().count();
+        let column = self.width.saturating_sub(length) / 2;
+        self.text(row, column, text, color);
+    }
+
+    /// Writes text from the cursor in the region's color, going to the next row at the right
+    /// edge and on new lines. Text past the bottom row is cut off.
+    pub fn write_str(&mut self, text: &str) {
+        let color = self.color;
+        for character in text.chars() {
+            let (row, column) = self.cursor;
+
+            if character == '\n' {
+                self.cursor = (row + 1, 0);
+                continue;
+            }
+
+            let (row, column) = if column >= self.width { (row + 1, 0) } else { (row, column) };
+            self.put_char(row, column, character, color);
+            self.cursor = (row, column + 1);
+        }
+    }
+
+    /// Draws a row of the given char from a cell to the right
+    pub fn horizontal_line(&mut self, row: usize, column: usize, length: usize, character: char, color: VgaColor) {
+        for offset in 0..length {
+            self.put_char(row, column + offset, character, color);
+        }
+    }
+
+    /// Draws a column of the given char from a cell down
+    pub fn vertical_line(&mut self, row: usize, column: usize, length: usize, character: char, color: VgaColor) {
+        for offset in 0..length {
+            self.put_char(row + offset, column, character, color);
+        }
+    }
+
+    /// Draws the outline of a box with its top left corner at the given cell. Boxes less than 2
+    /// cells wide or high aren't drawn.
+    pub fn draw_box(&mut self, row: usize, column: usize, width: usize, height: usize,
+                    style: BorderStyle, color: VgaColor) {
+        if width < 2 || height < 2 {
+            return;
+        }
+
+        let chars = style.chars();
+        let (right, bottom) = (column + width - 1, row + height - 1);
+
+        self.horizontal_line(row, column + 1, width - 2, chars.horizontal, color);
+        self.horizontal_line(bottom, column + 1, width - 2, chars.horizontal, color);
+        self.vertical_line(row + 1, column, height - 2, chars.vertical, color);
+        self.vertical_line(row + 1, right, height - 2, chars.vertical, color);
+
+        self.put_char(row, column, chars.top_left, color);
+        self.put_char(row, right, chars.top_right, color);
+        self.put_char(bottom, column, chars.bottom_left, color);
+        self.put_char(bottom, right, chars.bottom_right, color);
+    }
+}
+
+impl<'a> fmt::Write for Region<'a> {
+    fn write_str(&mut self, text: &str) -> fmt::Result {
+        Region::write_str(self, text);
+        Ok(())
+    }
+}
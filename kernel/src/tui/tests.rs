@@ -0,0 +1,260 @@
+use core::fmt::Write;
+use testing::{TestCase, TestResult};
+use drivers::keyboard::{Key, KeyEvent, Modifiers};
+use drivers::vga::{Color, VgaColor, VgaChar, TextBuffer, MemoryBuffer};
+use super::*;
+
+pub const TESTS: &'static [TestCase] = &[
+    test_case!(regions_clip_and_offset),
+    test_case!(regions_write_wrapped),
+    test_case!(draw_boxes),
+    test_case!(draw_windows),
+    test_case!(menus_move_selection),
+    test_case!(menus_scroll_to_selection),
+    test_case!(progress_bars_fill),
+    test_case!(message_boxes_choose_buttons),
+    test_case!(focus_skips_unfocusable),
+];
+
+const COLOR: VgaColor = VgaColor::new(Color::White, Color::Blue);
+const HIGHLIGHT: VgaColor = VgaColor::new(Color::Yellow, Color::Red);
+
+fn press(key: Key) -> KeyEvent {
+    KeyEvent {
+        key: key,
+        pressed: true,
+        modifiers: Modifiers::new(),
+    }
+}
+
+/// Gets the char drawn in a buffer's cell
+fn char_at(buffer: &MemoryBuffer, row: usize, column: usize) -> VgaChar {
+    buffer.get_char(row, column)
+}
+
+fn regions_clip_and_offset() -> TestResult {
+    let mut buffer = MemoryBuffer::new();
+    let blank = buffer.get_char(0, 0);
+    {
+        let mut screen = Region::new(&mut buffer, COLOR);
+        let mut region = screen.region(2, 3, 5, 2);
+        test_assert_eq!((region.width(), region.height()), (5, 2));
+
+        region.put_char(0, 0, 'a', HIGHLIGHT);
+        region.put_char(1, 4, 'b', HIGHLIGHT);
+        region.put_char(0, 5, 'x', HIGHLIGHT);
+        region.put_char(2, 0, 'x', HIGHLIGHT);
+        test_assert_eq!(region.get(0, 5), None);
+
+        // Regions inside are cut off at the edges of the outer one
+        let mut inner = region.region(1, 3, 10, 10);
+        test_assert_eq!((inner.width(), inner.height()), (2, 1));
+        inner.fill('c', COLOR);
+        test_assert_eq!(inner.text(0, 0, "long", COLOR), 2);
+    }
+
+    test_assert_eq!(char_at(&buffer, 2, 3), VgaChar::new(HIGHLIGHT, 'a'));
+    test_assert_eq!(char_at(&buffer, 3, 6), VgaChar::new(COLOR, 'l'));
+    test_assert_eq!(char_at(&buffer, 3, 7), VgaChar::new(COLOR, 'o'));
+    test_assert_eq!(char_at(&buffer, 2, 8), blank);
+    test_assert_eq!(char_at(&buffer, 4, 3), blank);
+    Ok(())
+}
+
+fn regions_write_wrapped() -> TestResult {
+    let mut buffer = MemoryBuffer::new();
+    let blank = buffer.get_char(0, 0);
+    {
+        let mut screen = Region::new(&mut buffer, COLOR);
+        {
+            let mut region = screen.region(1, 1, 3, 2);
+            region.write_str("abcd");
+            region.write_str("e\nfg");
+        }
+
+        // Text is written in the region's color, and through `fmt::Write`
+        let mut line = screen.region(4, 0, 10, 1);
+        line.set_color(HIGHLIGHT);
+        test_assert!(write!(line, "{}-{}", 1, 2).is_ok());
+    }
+
+    for &(row, column, character) in [(1, 1, 'a'), (1, 3, 'c'), (2, 1, 'd'), (2, 2, 'e')].iter() {
+        test_assert_eq!(char_at(&buffer, row, column), VgaChar::new(COLOR, character));
+    }
+    // The new line went past the bottom row, so the rest is cut off
+    test_assert_eq!(char_at(&buffer, 2, 3), blank);
+    test_assert_eq!(char_at(&buffer, 3, 1), blank);
+    test_assert_eq!(char_at(&buffer, 1, 4), blank);
+    test_assert_eq!(char_at(&buffer, 4, 2), VgaChar::new(HIGHLIGHT, '2'));
+    Ok(())
+}
+
+fn draw_boxes() -> TestResult {
+    let mut buffer = MemoryBuffer::new();
+    Region::new(&mut buffer, COLOR).draw_box(1, 2, 4, 3, BorderStyle::Double, COLOR);
+
+    // The CP437 glyphs of the double line box chars
+    test_assert_eq!(char_at(&buffer, 1, 2).character, 0xC9);
+    test_assert_eq!(char_at(&buffer, 1, 3).character, 0xCD);
+    test_assert_eq!(char_at(&buffer, 1, 5).character, 0xBB);
+    test_assert_eq!(char_at(&buffer, 2, 2).character, 0xBA);
+    test_assert_eq!(char_at(&buffer, 3, 2).character, 0xC8);
+    test_assert_eq!(char_at(&buffer, 3, 5).character, 0xBC);
+    test_assert_eq!(char_at(&buffer, 2, 3).character, b' ');
+    test_assert_eq!(BorderStyle::Single.chars().top_left, '┌');
+    Ok(())
+}
+
+fn draw_windows() -> TestResult {
+    let mut buffer = MemoryBuffer::new();
+    let window = Window::new("Hi");
+    {
+        let mut screen = Region::new(&mut buffer, HIGHLIGHT);
+        screen.fill('.', HIGHLIGHT);
+
+        let mut inside = window.draw(&mut screen, 2, 4, 10, 5);
+        test_assert_eq!((inside.width(), inside.height()), (8, 3));
+        test_assert_eq!(inside.color(), window.color);
+        inside.write_str("x");
+    }
+
+    test_assert_eq!(char_at(&buffer, 3, 5), VgaChar::new(window.color, 'x'));
+    test_assert_eq!(char_at(&buffer, 2, 4).character, 0xC9);
+
+    // The title is centered on the top edge, padded with spaces
+    for (offset, character) in " Hi ".chars().enumerate() {
+        test_assert_eq!(char_at(&buffer, 2, 7 + offset), VgaChar::new(window.title_color, character));
+    }
+
+    // The shadow darkens what's right of and below the window, keeping the chars
+    let shadow = VgaColor::new(Color::DarkGray, Color::Black);
+    test_assert_eq!(char_at(&buffer, 3, 14), VgaChar::new(shadow, '.'));
+    test_assert_eq!(char_at(&buffer, 3, 15), VgaChar::new(shadow, '.'));
+    test_assert_eq!(char_at(&buffer, 7, 6), VgaChar::new(shadow, '.'));
+    test_assert_eq!(char_at(&buffer, 2, 14), VgaChar::new(HIGHLIGHT, '.'));
+    test_assert_eq!(char_at(&buffer, 7, 5), VgaChar::new(HIGHLIGHT, '.'));
+    Ok(())
+}
+
+static ITEMS: [&'static str; 4] = ["Boot", "Options", "Devices", "Reboot"];
+
+fn menus_move_selection() -> TestResult {
+    let mut menu = Menu::new(&ITEMS);
+    test_assert_eq!(menu.handle_key(&press(Key::Up)), Response::Changed);
+    test_assert_eq!(menu.selected(), 3);
+    test_assert_eq!(menu.handle_key(&press(Key::Down)), Response::Changed);
+    test_assert_eq!(menu.selected(), 0);
+    menu.handle_key(&press(Key::End));
+    test_assert_eq!(menu.handle_key(&press(Key::Enter)), Response::Chosen(3));
+    test_assert_eq!(menu.handle_key(&press(Key::Escape)), Response::Cancelled);
+    test_assert_eq!(menu.handle_key(&press(Key::A)), Response::Ignored);
+
+    menu.select(1);
+    let mut buffer = MemoryBuffer::new();
+    menu.draw(&mut Region::new(&mut buffer, COLOR).region(0, 0, 12, 4), true);
+
+    let selected = VgaColor::new(Color::Black, Color::LightGray);
+    test_assert_eq!(char_at(&buffer, 1, 1), VgaChar::new(selected, 'O'));
+    test_assert_eq!(char_at(&buffer, 1, 11), VgaChar::new(selected, ' '));
+    test_assert_eq!(char_at(&buffer, 2, 1), VgaChar::new(COLOR, 'D'));
+    Ok(())
+}
+
+fn menus_scroll_to_selection() -> TestResult {
+    let mut menu = Menu::new(&ITEMS);
+    menu.select(3);
+
+    let mut buffer = MemoryBuffer::new();
+    menu.draw(&mut Region::new(&mut buffer, COLOR).region(0, 0, 10, 2), false);
+
+    // Only the last two fit, with an arrow as there are more above
+    test_assert_eq!(char_at(&buffer, 0, 1), VgaChar::new(COLOR, 'D'));
+    test_assert_eq!(char_at(&buffer, 1, 1).character, b'R');
+    test_assert_eq!(char_at(&buffer, 0, 9), VgaChar::new(COLOR, '↑'));
+    test_assert!(char_at(&buffer, 1, 9).character != 0x19);
+    Ok(())
+}
+
+fn progress_bars_fill() -> TestResult {
+    let mut bar = ProgressBar::new(200);
+    bar.set(100);
+    test_assert_eq!(bar.percent(), 50);
+    bar.set(1000);
+    test_assert_eq!(bar.value(), 200);
+    test_assert_eq!(ProgressBar::new(0).percent(), 100);
+    test_assert!(!bar.is_focusable());
+
+    bar.set(50);
+    let color = VgaColor::new(Color::LightGreen, Color::DarkGray);
+    let mut buffer = MemoryBuffer::new();
+    bar.draw(&mut Region::new(&mut buffer, HIGHLIGHT).region(0, 0, 20, 1), false);
+
+    // A quarter is filled in the bar's own color, with "25%" in the middle
+    test_assert_eq!(char_at(&buffer, 0, 0), VgaChar::new(color.inverted(), ' '));
+    test_assert_eq!(char_at(&buffer, 0, 4), VgaChar::new(color.inverted(), ' '));
+    test_assert_eq!(char_at(&buffer, 0, 5), VgaChar::new(color, '░'));
+    test_assert_eq!(char_at(&buffer, 0, 8), VgaChar::new(color, '2'));
+    test_assert_eq!(char_at(&buffer, 0, 10), VgaChar::new(color, '%'));
+    test_assert_eq!(char_at(&buffer, 0, 19), VgaChar::new(color, '░'));
+    Ok(())
+}
+
+fn message_boxes_choose_buttons() -> TestResult {
+    let buttons = ["OK", "Cancel"];
+    let mut message_box = MessageBox::new("Reboot now?\nUnsaved logs are lost", &buttons);
+
+    // The longest line and padding, by the lines and the buttons under a blank row
+    test_assert_eq!(message_box.size(), (21 + 2, 2 + 2));
+
+    test_assert_eq!(message_box.handle_key(&press(Key::Right)), Response::Changed);
+    test_assert_eq!(message_box.handle_key(&press(Key::Right)), Response::Changed);
+    test_assert_eq!(message_box.handle_key(&press(Key::Left)), Response::Changed);
+    test_assert_eq!(message_box.handle_key(&press(Key::Enter)), Response::Chosen(1));
+    test_assert_eq!(message_box.handle_key(&press(Key::Escape)), Response::Cancelled);
+
+    let mut buffer = MemoryBuffer::new();
+    message_box.draw(&mut Region::new(&mut buffer, COLOR).region(0, 0, 23, 4), true);
+
+    // "[ OK ]  [ Cancel ]" is 18 wide, so it starts 2 columns in
+    let selected = VgaColor::new(Color::Black, Color::LightGray);
+    test_assert_eq!(char_at(&buffer, 0, 1), VgaChar::new(COLOR, 'R'));
+    test_assert_eq!(char_at(&buffer, 3, 2), VgaChar::new(COLOR, '['));
+    test_assert_eq!(char_at(&buffer, 3, 4), VgaChar::new(COLOR, 'O'));
+    test_assert_eq!(char_at(&buffer, 3, 10), VgaChar::new(selected, '['));
+    test_assert_eq!(char_at(&buffer, 3, 12), VgaChar::new(selected, 'C'));
+    test_assert_eq!(char_at(&buffer, 3, 19), VgaChar::new(selected, ']'));
+    Ok(())
+}
+
+fn focus_skips_unfocusable() -> TestResult {
+    let mut first = Menu::new(&ITEMS);
+    let mut bar = ProgressBar::new(10);
+    let mut last = Menu::new(&ITEMS);
+    let mut focus = Focus::new();
+
+    {
+        let mut widgets: [&mut Widget; 3] = [&mut first, &mut bar, &mut last];
+
+        let mut tab = press(Key::Tab);
+        test_assert_eq!(focus.handle_key(&mut widgets, &tab), Response::Changed);
+        test_assert!(focus.is_focused(2));
+        test_assert_eq!(focus.handle_key(&mut widgets, &tab), Response::Changed);
+        test_assert!(focus.is_focused(0));
+
+        tab.modifiers.left_shift = true;
+        focus.handle_key(&mut widgets, &tab);
+        test_assert!(focus.is_focused(2));
+
+        // Other keys go to the focused widget, and released keys are ignored
+        test_assert_eq!(focus.handle_key(&mut widgets, &press(Key::Down)), Response::Changed);
+        let release = KeyEvent { pressed: false, ..press(Key::Down) };
+        test_assert_eq!(focus.handle_key(&mut widgets, &release), Response::Ignored);
+
+        focus.set(&widgets, 1);
+        test_assert!(focus.is_focused(2));
+    }
+
+    test_assert_eq!(first.selected(), 0);
+    test_assert_eq!(last.selected(), 1);
+    Ok(())
+}
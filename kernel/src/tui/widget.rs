@@ -0,0 +1,101 @@
+//! Widgets, which draw themselves in a region and take keys while they have focus
+
+use drivers::keyboard::{Key, KeyEvent};
+use super::Region;
+
+/// What a widget did with a key
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Response {
+    /// The key isn't used by the widget
+    Ignored,
+    /// The key changed the widget, which should be drawn again
+    Changed,
+    /// An item or button was chosen, given by its index
+    Chosen(usize),
+    /// The widget was closed without choosing anything, such as with Escape
+    Cancelled,
+}
+
+/// Something drawn in a region, which may take keys while it has focus
+pub trait Widget {
+    /// Draws the widget over the whole region, highlighted if it has focus
+    fn draw(&self, region: &mut Region, focused: bool);
+
+    /// Handles a key pressed while the widget has focus
+    fn handle_key(&mut self, _event: &KeyEvent) -> Response {
+        Response::Ignored
+    }
+
+    /// Checks if the widget takes focus. Widgets which only show something are skipped over.
+    fn is_focusable(&self) -> bool {
+        true
+    }
+}
+
+/// Tracks which of a list of widgets has focus, moved to the next focusable widget with Tab and
+/// back with Shift+Tab
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Focus {
+    index: usize,
+}
+
+impl Focus {
+    pub const fn new() -> Self {
+        Focus { index: 0 }
+    }
+
+    pub fn is_focused(&self, index: usize) -> bool {
+        self.index == index
+    }
+
+    /// Focuses the widget with the given index, if it's focusable
+    pub fn set(&mut self, widgets: &[&mut Widget], index: usize) {
+        if widgets.get(index).map_or(false, |widget| widget.is_focusable()) {
+            self.index = index;
+        }
+    }
+
+    /// Focuses the first focusable widget after the focused one, going round to the start
+    pub fn next(&mut self, widgets: &[&mut Widget]) {
+        for offset in 1..widgets.len() + 1 {
+            let index = (self.index + offset) % widgets.len();
+            if widgets[index].is_focusable() {
+                self.index = index;
+                return;
+            }
+        }
+    }
+
+    /// Focuses the first focusable widget before the focused one, going round to the end
+    pub fn previous(&mut self, widgets: &[&mut Widget]) {
+        for offset in 1..widgets.len() + 1 {
+            let index = (self.index + widgets.len() - offset) % widgets.len();
+            if widgets[index].is_focusable() {
+                self.index = index;
+                return;
+            }
+        }
+    }
+
+    /// Handles a key event, moving the focus on Tab and giving other pressed keys to the focused
+    /// widget
+    pub fn handle_key(&mut self, widgets: &mut [&mut Widget], event: &KeyEvent) -> Response {
+        if !event.pressed || event.key.is_modifier() {
+            return Response::Ignored;
+        }
+
+        if event.key == Key::Tab {
+            if event.modifiers.shift() {
+                self.previous(widgets);
+            } else {
+                self.next(widgets);
+            }
+            return Response::Changed;
+        }
+
+        match widgets.get_mut(self.index) {
+            Some(widget) => widget.handle_key(event),
+            None => Response::Ignored,
+        }
+    }
+}
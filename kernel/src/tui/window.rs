@@ -0,0 +1,120 @@
+//! Bordered windows drawn with the CP437 box drawing chars
+
+use drivers::vga::{Color, VgaColor};
+use super::Region;
+
+/// Lines which borders are drawn with
+#[allow(dead_code)] // dead variants for completeness
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BorderStyle {
+    Single,
+    Double,
+    /// Double lines across and single lines down
+    DoubleHorizontal,
+    /// Full blocks
+    Solid,
+}
+
+/// The chars a border is drawn with
+pub struct BorderChars {
+    pub top_left: char,
+    pub top_right: char,
+    pub bottom_left: char,
+    pub bottom_right: char,
+    pub horizontal: char,
+    pub vertical: char,
+}
+
+impl BorderStyle {
+    pub fn chars(self) -> BorderChars {
+        let (top_left, top_right, bottom_left, bottom_right, horizontal, vertical) = match self {
+            BorderStyle::Single => ('┌', '┐', '└', '┘', '─', '│'),
+            BorderStyle::Double => ('╔', '╗', '╚', '╝', '═', '║'),
+            BorderStyle::DoubleHorizontal => ('╒', '╕', '╘', '╛', '═', '│'),
+            BorderStyle::Solid => ('█', '█', '█', '█', '█', '█'),
+        };
+
+        BorderChars {
+            top_left: top_left,
+            top_right: top_right,
+            bottom_left: bottom_left,
+            bottom_right: bottom_right,
+            horizontal: horizontal,
+            vertical: vertical,
+        }
+    }
+}
+
+/// Color of the shadow cast on the cells right of and below a window
+const SHADOW: VgaColor = VgaColor::new(Color::DarkGray, Color::Black);
+
+/// Columns of shadow right of a window, wider than below as cells are about twice as tall as
+/// they are wide
+const SHADOW_WIDTH: usize = 2;
+
+/// A filled box with a border and a title on its top edge, which may cast a shadow
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Window<'a> {
+    pub title: &'a str,
+    pub border: BorderStyle,
+    /// Color of the border and the inside
+    pub color: VgaColor,
+    pub title_color: VgaColor,
+    pub shadow: bool,
+}
+
+impl<'a> Window<'a> {
+    /// Creates a double bordered window in white on blue with a shadow
+    pub const fn new(title: &'a str) -> Self {
+        Window {
+            title: title,
+            border: BorderStyle::Double,
+            color: VgaColor::new(Color::White, Color::Blue),
+            title_color: VgaColor::new(Color::Yellow, Color::Blue),
+            shadow: true,
+        }
+    }
+
+    /// Draws the window with its top left corner at the given cell of a region, its size
+    /// including the border. Gives the region inside the border, in the window's color.
+    pub fn draw<'b>(&self, region: &'b mut Region, row: usize, column: usize, width: usize,
+                    height: usize) -> Region<'b> {
+        if self.shadow {
+            for offset in 1..height + 1 {
+                for shadow_column in 0..SHADOW_WIDTH {
+                    region.recolor(row + offset, column + width + shadow_column, SHADOW);
+                }
+            }
+            for offset in SHADOW_WIDTH..width + SHADOW_WIDTH {
+                region.recolor(row + height, column + offset, SHADOW);
+            }
+        }
+
+        {
+            let mut frame = region.region(row, column, width, height);
+            frame.fill(' ', self.color);
+            frame.draw_box(0, 0, width, height, self.border, self.color);
+
+            if !self.title.is_empty() && width > 4 {
+                // Padded with a space each side, and cut off before the corners
+                let mut title = frame.region(0, 1, width - 2, 1);
+                let length = self.title.chars().count() + 2;
+                let column = title.width().saturating_sub(length) / 2;
+                title.put_char(0, column, ' ', self.title_color);
+                let written = title.text(0, column + 1, self.title, self.title_color);
+                title.put_char(0, column + 1 + written, ' ', self.title_color);
+            }
+        }
+
+        let mut inside = region.region(row + 1, column + 1, width.saturating_sub(2), height.saturating_sub(2));
+        inside.set_color(self.color);
+        inside
+    }
+
+    /// Draws the window centered in a region, giving the region inside its border
+    pub fn draw_centered<'b>(&self, region: &'b mut Region, width: usize, height: usize) -> Region<'b> {
+        let row = region.height().saturating_sub(height) / 2;
+        let column = region.width().saturating_sub(width) / 2;
+        self.draw(region, row, column, width, height)
+    }
+}
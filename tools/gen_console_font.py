@@ -0,0 +1,133 @@
+# Generates the framebuffer console's 8x16 PSF1 font from kernel/src/resources/fonts/console.txt
+#
+# Run from the repository root: python3 tools/gen_console_font.py
+
+import os
+
+SOURCE = "kernel/src/resources/fonts/console.txt"
+OUTPUT = "kernel/src/resources/fonts/console.psf"
+
+WIDTH = 8
+HEIGHT = 16
+
+# Where the top left of the 5x9 drawn glyphs goes in the cell
+GLYPH_TOP = 3
+GLYPH_LEFT = 1
+
+# Middle of the cell, where box drawing lines meet
+CENTER_X = 3
+CENTER_Y = 7
+
+# Lines of each box drawing character as (up, down, left, right), 1 for single and 2 for double
+BOX_DRAWING = {
+    0xB3: (1, 1, 0, 0), 0xB4: (1, 1, 1, 0), 0xB5: (1, 1, 2, 0), 0xB6: (2, 2, 1, 0),
+    0xB7: (0, 2, 1, 0), 0xB8: (0, 1, 2, 0), 0xB9: (2, 2, 2, 0), 0xBA: (2, 2, 0, 0),
+    0xBB: (0, 2, 2, 0), 0xBC: (2, 0, 2, 0), 0xBD: (2, 0, 1, 0), 0xBE: (1, 0, 2, 0),
+    0xBF: (0, 1, 1, 0), 0xC0: (1, 0, 0, 1), 0xC1: (1, 0, 1, 1), 0xC2: (0, 1, 1, 1),
+    0xC3: (1, 1, 0, 1), 0xC4: (0, 0, 1, 1), 0xC5: (1, 1, 1, 1), 0xC6: (1, 1, 0, 2),
+    0xC7: (2, 2, 0, 1), 0xC8: (2, 0, 0, 2), 0xC9: (0, 2, 0, 2), 0xCA: (2, 0, 2, 2),
+    0xCB: (0, 2, 2, 2), 0xCC: (2, 2, 0, 2), 0xCD: (0, 0, 2, 2), 0xCE: (2, 2, 2, 2),
+    0xCF: (1, 0, 2, 2), 0xD0: (2, 0, 1, 1), 0xD1: (0, 1, 2, 2), 0xD2: (0, 2, 1, 1),
+    0xD3: (2, 0, 0, 1), 0xD4: (1, 0, 0, 2), 0xD5: (0, 1, 0, 2), 0xD6: (0, 2, 0, 1),
+    0xD7: (2, 2, 1, 1), 0xD8: (1, 1, 2, 2), 0xD9: (1, 0, 1, 0), 0xDA: (0, 1, 0, 1),
+}
+
+
+def blank():
+    return [[False] * WIDTH for _ in range(HEIGHT)]
+
+
+def hollow_box():
+    glyph = blank()
+    for y in range(3, 13):
+        for x in range(1, 7):
+            glyph[y][x] = y in (3, 12) or x in (1, 6)
+    return glyph
+
+
+def box_drawing(up, down, left, right):
+    glyph = blank()
+
+    def offsets(weight):
+        return [0] if weight == 1 else [-1, 1]
+
+    for weight, rows in ((up, range(0, CENTER_Y + 1)), (down, range(CENTER_Y, HEIGHT))):
+        if weight:
+            for offset in offsets(weight):
+                for y in rows:
+                    glyph[y][CENTER_X + offset] = True
+
+    for weight, columns in ((left, range(0, CENTER_X + 1)), (right, range(CENTER_X, WIDTH))):
+        if weight:
+            for offset in offsets(weight):
+                for x in columns:
+                    glyph[CENTER_Y + offset][x] = True
+
+    return glyph
+
+
+def block(test):
+    return [[test(x, y) for x in range(WIDTH)] for y in range(HEIGHT)]
+
+
+def generated_glyphs():
+    glyphs = {code: box_drawing(*lines) for code, lines in BOX_DRAWING.items()}
+
+    # Shades, then the full, lower, left, right and upper blocks
+    glyphs[0xB0] = block(lambda x, y: x % 4 == 0 and y % 2 == 0 or x % 4 == 2 and y % 2 == 1)
+    glyphs[0xB1] = block(lambda x, y: (x + y) % 2 == 0)
+    glyphs[0xB2] = block(lambda x, y: not (x % 4 == 0 and y % 2 == 0 or x % 4 == 2 and y % 2 == 1))
+    glyphs[0xDB] = block(lambda x, y: True)
+    glyphs[0xDC] = block(lambda x, y: y >= HEIGHT // 2)
+    glyphs[0xDD] = block(lambda x, y: x < WIDTH // 2)
+    glyphs[0xDE] = block(lambda x, y: x >= WIDTH // 2)
+    glyphs[0xDF] = block(lambda x, y: y < HEIGHT // 2)
+
+    # Null and the non-breaking space are blank
+    glyphs[0x00] = blank()
+    glyphs[0xFF] = blank()
+
+    return glyphs
+
+
+def drawn_glyphs(path):
+    glyphs = {}
+
+    with open(path) as source:
+        for line in source:
+            line = line.strip()
+            if not line or line.startswith("#"):
+                continue
+
+            code, *rows = line.split()
+            glyph = blank()
+            for y, row in enumerate(rows):
+                for x, pixel in enumerate(row):
+                    glyph[GLYPH_TOP + y][GLYPH_LEFT + x] = pixel == "#"
+
+            glyphs[int(code, 16)] = glyph
+
+    return glyphs
+
+
+def row_byte(row):
+    return sum(1 << (WIDTH - 1 - x) for x, pixel in enumerate(row) if pixel)
+
+
+def main():
+    glyphs = generated_glyphs()
+    glyphs.update(drawn_glyphs(SOURCE))
+
+    # PSF1 header: magic, mode of 256 glyphs without a unicode table, and the glyph height
+    data = bytearray([0x36, 0x04, 0x00, HEIGHT])
+    for code in range(256):
+        glyph = glyphs.get(code) or hollow_box()
+        data.extend(row_byte(row) for row in glyph)
+
+    with open(OUTPUT, "wb") as output:
+        output.write(data)
+
+    print("Wrote {} glyphs to {}".format(len(glyphs), os.path.normpath(OUTPUT)))
+
+
+main()